@@ -0,0 +1,27 @@
+mod common;
+
+use std::hint::black_box;
+
+use common::{bench, input};
+use escaped_delimiter::{collect_fields, iter};
+
+fn main() {
+    // From many short fields to a few long ones.
+    for (label, field) in [
+        ("short", &b"a "[..]),
+        ("medium", b"lorem\\ ipsum "),
+        (
+            "long",
+            b"lorem ipsum\\ dolor sit\\\\ amet consectetur\\\\\\ adipiscing elit sed do ",
+        ),
+    ] {
+        let s = input(field);
+        println!("{label}:");
+        bench("iter().collect", || {
+            iter(black_box(&s), b' ', b'\\').collect::<Vec<_>>().len()
+        });
+        bench("collect_fields", || {
+            collect_fields(black_box(&s), b' ', b'\\').len()
+        });
+    }
+}
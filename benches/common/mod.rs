@@ -0,0 +1,27 @@
+//! The timing harness shared by the benches.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Repeats `field` into a slice of about 1 MiB.
+pub fn input(field: &[u8]) -> Vec<u8> {
+    let mut s = Vec::with_capacity(1 << 20);
+    while s.len() < 1 << 20 {
+        s.extend_from_slice(field);
+    }
+    s
+}
+
+/// Prints the mean time of `f` over a fixed number of rounds, after a warm-up.
+pub fn bench(name: &str, mut f: impl FnMut() -> usize) {
+    const ROUNDS: u32 = 50;
+
+    black_box(f());
+    let mut total = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        black_box(f());
+        total += start.elapsed();
+    }
+    println!("{name:<16} {:>10.3?}/iter", total / ROUNDS);
+}
@@ -0,0 +1,13 @@
+mod common;
+
+use std::hint::black_box;
+
+use common::{bench, input};
+use escaped_delimiter::{iter, iter_const};
+
+fn main() {
+    let s = input(b"lorem ipsum\\ dolor sit\\\\ amet  consectetur\\\\\\ adipiscing ");
+
+    bench("runtime", || iter(black_box(&s), b' ', b'\\').count());
+    bench("const", || iter_const::<b' ', b'\\'>(black_box(&s)).count());
+}
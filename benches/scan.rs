@@ -0,0 +1,29 @@
+//! Compare `cargo bench --bench scan` with `cargo bench --bench scan --no-default-features` to
+//! see what the `memchr` feature gains.
+
+mod common;
+
+use std::hint::black_box;
+
+use common::{bench, input};
+use escaped_delimiter::iter;
+
+fn main() {
+    for (label, field) in [
+        ("short", &b"ab "[..]),
+        ("medium", b"lorem ipsum dolor sit amet "),
+        ("long", &[b'a'; 4095][..]),
+        ("escaped", b"lorem\\ ipsum dolor\\\\ sit amet "),
+    ] {
+        let mut s = input(field);
+        if label == "long" {
+            s.iter_mut().step_by(4096).for_each(|c| *c = b' ');
+        }
+
+        println!("{label}:");
+        bench("next", || iter(black_box(&s), b' ', b'\\').count());
+        bench("next_back", || {
+            iter(black_box(&s), b' ', b'\\').rev().count()
+        });
+    }
+}
@@ -0,0 +1,966 @@
+use std::borrow::Cow;
+use std::iter::FusedIterator;
+use std::num::NonZeroUsize;
+
+use crate::{escape_field, Iter};
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the unescaped fields.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s = b"a\\ b\\\\ c";
+    /// let s_vec: Vec<_> = iter(s, b' ', b'\\').unescaped().collect();
+    /// assert_eq!(s_vec, &[&b"a b\\"[..], &b"c"[..]]);
+    /// ```
+    #[inline]
+    pub fn unescaped(self) -> Unescaped<'a> {
+        Unescaped { inner: self }
+    }
+
+    /// Returns an iterator of the fields unescaped and escaped again, which joined with the
+    /// delimiter give the canonical form of [`normalize`](crate::normalize).
+    ///
+    /// Redundant escape characters are removed, and the re-escaped field still unescapes to the
+    /// same bytes. A field with no escape characters is borrowed.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s_vec: Vec<_> = iter(b"\\a\\ b c\\\\", b' ', b'\\').reescaped().collect();
+    /// assert_eq!(s_vec, &[&b"a\\ b"[..], &b"c\\\\"[..]]);
+    /// ```
+    pub fn reescaped(self) -> impl Iterator<Item = Cow<'a, [u8]>> {
+        let (delim, escape) = (self.delim, self.escape);
+        self.unescaped().map(move |field| match field {
+            Cow::Borrowed(field) => escape_field(field, delim, escape),
+            Cow::Owned(field) => Cow::Owned(escape_field(&field, delim, escape).into_owned()),
+        })
+    }
+
+    /// Returns an iterator of the fields with unescaped `byte`s removed from both ends.
+    ///
+    /// A field which consists only of `byte`s becomes empty, but is still yielded.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s = b"  a \\ , b ";
+    /// let s_vec: Vec<_> = iter(s, b',', b'\\').trim(b' ').collect();
+    /// assert_eq!(s_vec, &[&b"a \\ "[..], &b"b"[..]]);
+    /// ```
+    #[inline]
+    pub fn trim(self, byte: u8) -> Trim<'a> {
+        Trim { byte, inner: self }
+    }
+
+    /// Same as [`Iterator::map`], but the returned iterator keeps [`as_slice`](MapFields::as_slice).
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"a bc def", b' ', b'\\').map_fields(<[u8]>::len);
+    /// assert_eq!(it.next(), Some(1));
+    /// assert_eq!(it.next_back(), Some(3));
+    /// assert_eq!(it.as_slice(), &b" bc "[..]);
+    /// ```
+    #[inline]
+    pub fn map_fields<F, T>(self, f: F) -> MapFields<'a, F>
+    where
+        F: FnMut(&'a [u8]) -> T,
+    {
+        MapFields { f, inner: self }
+    }
+
+    /// Returns an iterator of the fields and the number of escape characters in each, which
+    /// [`unescaped`](Iter::unescaped) removes.
+    ///
+    /// The length of the unescaped field is the length of the field minus the count. The count
+    /// is taken in the same scan which finds the end of the field, which reads every byte of it.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s_vec: Vec<_> = iter(b"a\\ b\\\\ c", b' ', b'\\').with_escape_counts().collect();
+    /// assert_eq!(s_vec, &[(&b"a\\ b\\\\"[..], 2), (&b"c"[..], 0)]);
+    /// ```
+    #[inline]
+    pub fn with_escape_counts(self) -> EscapeCounts<'a> {
+        EscapeCounts { inner: self }
+    }
+
+    /// Returns an iterator of overlapping pairs of consecutive fields.
+    ///
+    /// For `n` fields it yields `n - 1` pairs, like [`slice::windows`] with size 2. The returned
+    /// iterator is not double-ended, since both ends would share the fields where they meet.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s_vec: Vec<_> = iter(b"a b c", b' ', b'\\').field_pairs().collect();
+    /// assert_eq!(s_vec, &[(&b"a"[..], &b"b"[..]), (&b"b"[..], &b"c"[..])]);
+    /// ```
+    #[inline]
+    pub fn field_pairs(self) -> FieldPairs<'a> {
+        FieldPairs {
+            prev: None,
+            inner: self,
+        }
+    }
+
+    /// Returns an iterator of chunks of `n` fields, starting from the front.
+    ///
+    /// The last chunk may be shorter than `n`. The returned iterator is not double-ended, since
+    /// the size of the last chunk depends on the number of fields.
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use escaped_delimiter::iter;
+    ///
+    /// let n = NonZeroUsize::new(2).unwrap();
+    /// let s_vec: Vec<_> = iter(b"a b c", b' ', b'\\').chunks(n).collect();
+    /// assert_eq!(s_vec, &[vec![&b"a"[..], &b"b"[..]], vec![&b"c"[..]]]);
+    /// ```
+    #[inline]
+    pub fn chunks(self, n: NonZeroUsize) -> FieldChunks<'a> {
+        FieldChunks { n, inner: self }
+    }
+
+    /// Returns an iterator of chunks of `n` fields, starting from the back.
+    ///
+    /// Each chunk keeps the order of the fields, and the last chunk (the leading fields) may be
+    /// shorter than `n`, like [`slice::rchunks`].
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use escaped_delimiter::iter;
+    ///
+    /// let n = NonZeroUsize::new(2).unwrap();
+    /// let s_vec: Vec<_> = iter(b"a b c", b' ', b'\\').rchunks(n).collect();
+    /// assert_eq!(s_vec, &[vec![&b"b"[..], &b"c"[..]], vec![&b"a"[..]]]);
+    /// ```
+    #[inline]
+    pub fn rchunks(self, n: NonZeroUsize) -> RFieldChunks<'a> {
+        RFieldChunks { n, inner: self }
+    }
+
+    /// Returns an iterator of the fields which end within the first `max` bytes of the rest of
+    /// the slice, scanning no further than the byte right after them.
+    ///
+    /// The field cut off by the limit is not yielded, even in part, and
+    /// [`as_unlimited_slice`](LimitBytes::as_unlimited_slice) returns the rest from its start,
+    /// so the fields are truncated iff the rest still has a field. The returned iterator is not
+    /// double-ended, since the fields within the limit are counted from the front.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"ab cd\\ ef gh", b' ', b'\\').limit_bytes(7);
+    /// assert_eq!(it.next(), Some(&b"ab"[..]));
+    /// assert_eq!(it.as_slice(), &b" cd\\ "[..]);
+    /// assert_eq!(it.next(), None);
+    /// assert_eq!(it.as_slice(), &b"cd\\ "[..]);
+    /// assert_eq!(it.as_unlimited_slice(), &b"cd\\ ef gh"[..]);
+    /// ```
+    #[inline]
+    pub fn limit_bytes(self, max: usize) -> LimitBytes<'a> {
+        LimitBytes {
+            remaining: max,
+            inner: self,
+        }
+    }
+
+    /// Returns an iterator of the runs of consecutive fields with equal keys, each with its key.
+    ///
+    /// `key` is called once for each raw field; call [`unescape`](crate::unescape) in it to
+    /// group by the unescaped fields. The returned iterator is not double-ended, since it would
+    /// split a run where both ends meet.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s_vec: Vec<_> = iter(b"ab ac b a", b' ', b'\\').group_by(|f| f[0]).collect();
+    /// assert_eq!(s_vec, &[
+    ///     (b'a', vec![&b"ab"[..], &b"ac"[..]]),
+    ///     (b'b', vec![&b"b"[..]]),
+    ///     (b'a', vec![&b"a"[..]]),
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn group_by<K, F>(self, key: F) -> GroupBy<'a, K, F>
+    where
+        K: PartialEq,
+        F: FnMut(&[u8]) -> K,
+    {
+        GroupBy {
+            key,
+            peeked: None,
+            inner: self,
+        }
+    }
+
+    /// Returns an iterator of the fields from the back, numbered from the last field, which is
+    /// `0`.
+    ///
+    /// The fields already taken from the back are not counted, but those taken from the front do
+    /// not matter.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"a b\\ c d", b' ', b'\\');
+    /// it.next();
+    /// let s_vec: Vec<_> = it.enumerate_from_end().collect();
+    /// assert_eq!(s_vec, &[(0, &b"d"[..]), (1, &b"b\\ c"[..])]);
+    /// ```
+    #[inline]
+    pub fn enumerate_from_end(self) -> EnumerateFromEnd<'a> {
+        EnumerateFromEnd {
+            count: 0,
+            inner: self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unescaped<'a> {
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for Unescaped<'a> {
+    type Item = Cow<'a, [u8]>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        Some(self.inner.unescape(field))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Unescaped<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        Some(self.inner.unescape(field))
+    }
+}
+
+impl FusedIterator for Unescaped<'_> {}
+
+impl<'a> Unescaped<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trim<'a> {
+    byte: u8,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for Trim<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        Some(self.trim(field))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Trim<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        Some(self.trim(field))
+    }
+}
+
+impl FusedIterator for Trim<'_> {}
+
+impl<'a> Trim<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    fn trim(&self, field: &'a [u8]) -> &'a [u8] {
+        let start = field
+            .iter()
+            .position(|&c| c != self.byte)
+            .unwrap_or(field.len());
+        let field = &field[start..];
+
+        let mut end = field
+            .iter()
+            .rposition(|&c| c != self.byte)
+            .map_or(0, |i| i + 1);
+
+        // [^ESCAPE] ESCAPE* BYTE BYTE*
+        //                    ^
+        //                   end
+        if end < field.len() && self.inner.is_special(self.byte) {
+            let escapes = field[..end]
+                .iter()
+                .rev()
+                .take_while(|&&c| c == self.inner.escape)
+                .count();
+            if escapes & 1 == 1 {
+                end += 1;
+            }
+        }
+
+        &field[..end]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MapFields<'a, F> {
+    f: F,
+    inner: Iter<'a>,
+}
+
+impl<'a, F, T> Iterator for MapFields<'a, F>
+where
+    F: FnMut(&'a [u8]) -> T,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(&mut self.f)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, F, T> DoubleEndedIterator for MapFields<'a, F>
+where
+    F: FnMut(&'a [u8]) -> T,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(&mut self.f)
+    }
+}
+
+impl<'a, F, T> FusedIterator for MapFields<'a, F> where F: FnMut(&'a [u8]) -> T {}
+
+impl<'a, F> MapFields<'a, F> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeCounts<'a> {
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for EscapeCounts<'a> {
+    type Item = (&'a [u8], usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_counting()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for EscapeCounts<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back_counting()
+    }
+}
+
+impl FusedIterator for EscapeCounts<'_> {}
+
+impl<'a> EscapeCounts<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldPairs<'a> {
+    prev: Option<&'a [u8]>,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for FieldPairs<'a> {
+    type Item = (&'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev = match self.prev {
+            Some(prev) => prev,
+            None => self.inner.next()?,
+        };
+        let next = self.inner.next()?;
+        self.prev = Some(next);
+        Some((prev, next))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldChunks<'a> {
+    n: NonZeroUsize,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for FieldChunks<'a> {
+    type Item = Vec<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = self.inner.by_ref().take(self.n.get()).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+impl<'a> FieldChunks<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RFieldChunks<'a> {
+    n: NonZeroUsize,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for RFieldChunks<'a> {
+    type Item = Vec<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk: Vec<_> = self.inner.by_ref().rev().take(self.n.get()).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            chunk.reverse();
+            Some(chunk)
+        }
+    }
+}
+
+impl<'a> RFieldChunks<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitBytes<'a> {
+    remaining: usize,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for LimitBytes<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.inner.inner;
+        let window = Iter {
+            inner: &rest[..rest.len().min(self.remaining)],
+            ..self.inner
+        };
+
+        // Leading delimiters count as scanned, even if no field follows within the limit.
+        let bow = window.find_bow();
+        self.consume(bow);
+
+        let field = Iter {
+            inner: &window.inner[bow..],
+            ..window
+        };
+        let len = match field.find_delim() {
+            Some(i) => i,
+            None if field.is_empty() => return None,
+            None if window.len() == rest.len() => field.len(),
+            // The byte right after the limit may end the field.
+            None if self.inner.scanner().is_boundary(rest, window.len()) => field.len(),
+            None => return None,
+        };
+
+        let field = &field.inner[..len];
+        self.consume(len);
+        Some(field)
+    }
+}
+
+impl<'a> LimitBytes<'a> {
+    /// Returns the rest of the inner slice within the limit.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        let rest = self.inner.as_slice();
+        &rest[..rest.len().min(self.remaining)]
+    }
+
+    /// Returns the rest of the inner slice, including the bytes past the limit.
+    #[inline]
+    pub fn as_unlimited_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    #[inline]
+    fn consume(&mut self, n: usize) {
+        self.inner.inner = &self.inner.inner[n..];
+        self.remaining -= n;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupBy<'a, K, F> {
+    key: F,
+    peeked: Option<(K, &'a [u8])>,
+    inner: Iter<'a>,
+}
+
+impl<'a, K, F> Iterator for GroupBy<'a, K, F>
+where
+    K: PartialEq,
+    F: FnMut(&[u8]) -> K,
+{
+    type Item = (K, Vec<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => {
+                let field = self.inner.next()?;
+                ((self.key)(field), field)
+            }
+        };
+
+        let mut group = vec![first];
+        for field in self.inner.by_ref() {
+            let k = (self.key)(field);
+            if k != key {
+                self.peeked = Some((k, field));
+                break;
+            }
+            group.push(field);
+        }
+
+        Some((key, group))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnumerateFromEnd<'a> {
+    count: usize,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for EnumerateFromEnd<'a> {
+    type Item = (usize, &'a [u8]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        let i = self.count;
+        self.count += 1;
+        Some((i, field))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl FusedIterator for EnumerateFromEnd<'_> {}
+
+impl<'a> EnumerateFromEnd<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{unescape, EscapeRun};
+
+    #[test]
+    fn test_group_by() {
+        let s = b"XaXabYXXbXbcXcYXXaX";
+        let groups: Vec<_> = Iter::from_slice(s, b'X', b'Y').group_by(|f| f[0]).collect();
+        assert_eq!(
+            groups,
+            &[
+                (b'a', vec![&b"a"[..], b"abYX"]),
+                (b'b', vec![&b"b"[..], b"bc"]),
+                (b'c', vec![&b"cYX"[..]]),
+                (b'a', vec![&b"a"[..]]),
+            ]
+        );
+
+        let mut calls = 0;
+        let groups = Iter::from_slice(s, b'X', b'Y').group_by(|f| {
+            calls += 1;
+            f.len()
+        });
+        assert_eq!(groups.count(), 6);
+        assert_eq!(calls, 6);
+
+        let groups: Vec<_> = Iter::from_slice(b"YaXaXb", b'X', b'Y')
+            .group_by(|f| unescape(f, b'Y').into_owned())
+            .map(|(_, group)| group.len())
+            .collect();
+        assert_eq!(groups, &[2, 1]);
+
+        assert_eq!(
+            Iter::from_slice(b"XX", b'X', b'Y')
+                .group_by(|f| f[0])
+                .next(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_enumerate_from_end() {
+        let s = b"XaYXbXXcYYXdXeX";
+        let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+        for front in 0..=fields.len() {
+            let mut it = Iter::from_slice(s, b'X', b'Y');
+            it.by_ref().take(front).count();
+            let v: Vec<_> = it.enumerate_from_end().collect();
+            let expected: Vec<_> = fields[front..].iter().copied().rev().enumerate().collect();
+            assert_eq!(v, expected, "{}", front);
+        }
+
+        let mut it = Iter::from_slice(s, b'X', b'Y').enumerate_from_end();
+        assert_eq!(it.next(), Some((0, &b"e"[..])));
+        assert_eq!(it.next(), Some((1, &b"d"[..])));
+        assert_eq!(it.as_slice(), b"XaYXbXXcYYX");
+        crate::test::check_size_hint(it);
+    }
+
+    #[test]
+    fn test_limit_bytes() {
+        for s in crate::test::strings(b"aXY", 7) {
+            let ranges: Vec<_> = crate::field_ranges(&s, b'X', b'Y').collect();
+            for max in 0..=s.len() + 1 {
+                let fields: Vec<_> = Iter::from_slice(&s, b'X', b'Y').limit_bytes(max).collect();
+                let expected: Vec<_> = ranges
+                    .iter()
+                    .take_while(|r| r.end <= max)
+                    .map(|r| &s[r.clone()])
+                    .collect();
+                assert_eq!(fields, expected, "{:?} {}", s, max);
+            }
+        }
+
+        let s = b"XaXbYXXcccX";
+        let mut it = Iter::from_slice(s, b'X', b'Y').limit_bytes(8);
+        assert_eq!(it.next(), Some(&b"a"[..]));
+        assert_eq!(it.next(), Some(&b"bYX"[..]));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+        assert_eq!(it.as_slice(), b"c");
+        assert_eq!(it.as_unlimited_slice(), b"cccX");
+
+        let mut s = vec![b'a'; 1 << 20];
+        s.extend_from_slice(b"Xb");
+        let mut it = Iter::from_slice(&s, b'X', b'Y').limit_bytes(4096);
+        assert_eq!(it.next(), None);
+        assert_eq!(it.as_slice().len(), 4096);
+        assert_eq!(it.as_unlimited_slice().len(), s.len());
+    }
+
+    #[test]
+    fn test_limit_bytes_as_slice() {
+        // The fields yielded so far and the rest make up the limited region.
+        for s in crate::test::strings(b"aXY", 6) {
+            for max in 0..=s.len() + 1 {
+                let region = &s[..s.len().min(max)];
+                let mut it = Iter::from_slice(&s, b'X', b'Y').limit_bytes(max);
+                let mut fields = Vec::new();
+                loop {
+                    let rest = it.as_slice();
+                    let head = &region[..region.len() - rest.len()];
+                    assert_eq!(rest, &region[head.len()..]);
+                    assert_eq!(
+                        Iter::from_slice(head, b'X', b'Y').collect::<Vec<_>>(),
+                        fields
+                    );
+
+                    match it.next() {
+                        Some(field) => fields.push(field),
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unescaped() {
+        let s = b"abXYXcdeXYfYXXYYYXgYYX";
+        let mut words = Iter::from_slice(s, b'X', b'Y').unescaped();
+        assert_eq!(words.next().as_deref(), Some(&b"ab"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"Xcde"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"fX"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"YXgY"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = Iter::from_slice(s, b'X', b'Y').unescaped().rev();
+        assert_eq!(words.next().as_deref(), Some(&b"YXgY"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"fX"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"Xcde"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"ab"[..]));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_reescaped() {
+        let s = b"aYbXYXYYXXcYYYX";
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y').reescaped().collect();
+        assert_eq!(v, &[&b"ab"[..], b"YXYY", b"cYYYX"]);
+        assert!(matches!(v[0], Cow::Owned(_)));
+        assert!(matches!(
+            Iter::from_slice(b"ab", b'X', b'Y').reescaped().next(),
+            Some(Cow::Borrowed(_))
+        ));
+
+        for s in crate::test::strings(b"aXY", 7) {
+            let v: Vec<_> = Iter::from_slice(&s, b'X', b'Y').reescaped().collect();
+            assert_eq!(v.join(&b'X'), crate::normalize(&s, b'X', b'Y'), "{:?}", s);
+
+            // The canonical form is re-escaped to itself.
+            let normalized = v.join(&b'X');
+            let again: Vec<_> = Iter::from_slice(&normalized, b'X', b'Y')
+                .reescaped()
+                .collect();
+            assert_eq!(again, v, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_unescaped_size_hint() {
+        for s in crate::test::strings(b"aXY", 7) {
+            crate::test::check_size_hint(Iter::from_slice(&s, b'X', b'Y').unescaped());
+        }
+
+        let mut it = Iter::from_slice(b"aXbYXc", b'X', b'Y').unescaped();
+        assert_eq!(it.size_hint(), (1, Some(3)));
+        it.next_back();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn test_trim_size_hint() {
+        for s in crate::test::strings(b"a XY", 6) {
+            crate::test::check_size_hint(Iter::from_slice(&s, b'X', b'Y').trim(b' '));
+        }
+
+        let it = Iter::from_slice(b"XaX X", b'X', b'Y').trim(b' ');
+        assert_eq!(it.size_hint(), (0, Some(3)));
+        assert_eq!(it.count(), 2);
+    }
+
+    #[test]
+    fn test_trim() {
+        let s = b"  a XbX  XYX c Y  X Y";
+        let mut words = Iter::from_slice(s, b'X', b'Y').trim(b' ');
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next(), Some(&b"b"[..]));
+        assert_eq!(words.next(), Some(&b""[..]));
+        assert_eq!(words.next(), Some(&b"YX c Y "[..]));
+        assert_eq!(words.next(), Some(&b"Y"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = Iter::from_slice(s, b'X', b'Y').trim(b' ').rev();
+        assert_eq!(words.next(), Some(&b"Y"[..]));
+        assert_eq!(words.next(), Some(&b"YX c Y "[..]));
+        assert_eq!(words.next(), Some(&b""[..]));
+        assert_eq!(words.next(), Some(&b"b"[..]));
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next(), None);
+
+        let s = b"a YY X  ";
+        let mut words = Iter::from_slice(s, b'X', b'Y').trim(b' ');
+        assert_eq!(words.next(), Some(&b"a YY"[..]));
+        assert_eq!(words.next(), Some(&b""[..]));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_map_fields() {
+        let s = b"abXYXcdeXYfYXXYYYXgYYX";
+        let lens: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .map_fields(<[u8]>::len)
+            .collect();
+        assert_eq!(lens, &[2, 5, 4, 7]);
+
+        let lens: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .map_fields(<[u8]>::len)
+            .rev()
+            .collect();
+        assert_eq!(lens, &[7, 4, 5, 2]);
+
+        for s in crate::test::strings(b"aXY", 7) {
+            let words = Iter::from_slice(&s, b'X', b'Y');
+            crate::test::check_size_hint(words.map_fields(<[u8]>::len));
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let n = |n| NonZeroUsize::new(n).unwrap();
+
+        let s = b"aXbYXcXdXeYYXf";
+        let chunks: Vec<_> = Iter::from_slice(s, b'X', b'Y').chunks(n(2)).collect();
+        assert_eq!(
+            chunks,
+            &[
+                vec![&b"a"[..], &b"bYXc"[..]],
+                vec![&b"d"[..], &b"eYY"[..]],
+                vec![&b"f"[..]],
+            ]
+        );
+
+        let chunks: Vec<_> = Iter::from_slice(s, b'X', b'Y').chunks(n(5)).collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].len(), 5);
+
+        let mut chunks = Iter::from_slice(b"", b'X', b'Y').chunks(n(1));
+        assert_eq!(chunks.next(), None);
+    }
+
+    #[test]
+    fn test_rchunks() {
+        let n = |n| NonZeroUsize::new(n).unwrap();
+
+        let s = b"aXbYXcXdXeYYXf";
+        let chunks: Vec<_> = Iter::from_slice(s, b'X', b'Y').rchunks(n(2)).collect();
+        assert_eq!(
+            chunks,
+            &[
+                vec![&b"eYY"[..], &b"f"[..]],
+                vec![&b"bYXc"[..], &b"d"[..]],
+                vec![&b"a"[..]],
+            ]
+        );
+
+        let s = b"aXbXcXd";
+        let chunks: Vec<_> = Iter::from_slice(s, b'X', b'Y').rchunks(n(2)).collect();
+        assert_eq!(
+            chunks,
+            &[vec![&b"c"[..], &b"d"[..]], vec![&b"a"[..], &b"b"[..]]]
+        );
+
+        let mut chunks = Iter::from_slice(b"XX", b'X', b'Y').rchunks(n(1));
+        assert_eq!(chunks.next(), None);
+    }
+
+    #[test]
+    fn test_with_escape_counts() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"abXYXcdeXYfYXXYYYXgYYX",
+            b"YaYbXYYYYcYXdY",
+            b"XYYXYYYXYXYYY",
+        ];
+
+        for s in inputs {
+            let words = Iter::from_slice(s, b'X', b'Y');
+            let counts: Vec<_> = words.with_escape_counts().collect();
+            for (&(field, n), unescaped) in counts.iter().zip(words.unescaped()) {
+                assert_eq!(field.len() - n, unescaped.len());
+            }
+
+            let mut rcounts: Vec<_> = words.with_escape_counts().rev().collect();
+            rcounts.reverse();
+            assert_eq!(counts, rcounts);
+
+            let words = Iter::with_specials(s, b'X', b'Y', b"a");
+            for ((field, n), unescaped) in words.with_escape_counts().zip(words.unescaped()) {
+                assert_eq!(field.len() - n, unescaped.len());
+            }
+            for ((field, n), unescaped) in words
+                .with_escape_counts()
+                .rev()
+                .zip(words.unescaped().rev())
+            {
+                assert_eq!(field.len() - n, unescaped.len());
+            }
+        }
+
+        // The counts are taken in the scan for the boundaries, which splits as `Iter` does.
+        for s in crate::test::strings(b"aXY", 7) {
+            for run in [EscapeRun::Parity, EscapeRun::Sticky] {
+                let words = Iter::from_slice(&s, b'X', b'Y').escape_run(run);
+                let expected: Vec<_> = words
+                    .zip(words.unescaped())
+                    .map(|(field, unescaped)| (field, field.len() - unescaped.len()))
+                    .collect();
+                let counts: Vec<_> = words.with_escape_counts().collect();
+                assert_eq!(counts, expected, "{:?}", s);
+                let mut rcounts: Vec<_> = words.with_escape_counts().rev().collect();
+                rcounts.reverse();
+                assert_eq!(rcounts, expected, "{:?}", s);
+                crate::test::check_size_hint(words.with_escape_counts());
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_pairs() {
+        let s = b"aXbYXcXdXXe";
+        let pairs: Vec<_> = Iter::from_slice(s, b'X', b'Y').field_pairs().collect();
+        assert_eq!(
+            pairs,
+            &[
+                (&b"a"[..], &b"bYXc"[..]),
+                (&b"bYXc"[..], &b"d"[..]),
+                (&b"d"[..], &b"e"[..]),
+            ]
+        );
+
+        let mut pairs = Iter::from_slice(b"aX", b'X', b'Y').field_pairs();
+        assert_eq!(pairs.next(), None);
+        assert_eq!(pairs.next(), None);
+
+        let mut pairs = Iter::from_slice(b"", b'X', b'Y').field_pairs();
+        assert_eq!(pairs.next(), None);
+    }
+}
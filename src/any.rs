@@ -0,0 +1,223 @@
+use std::iter::FusedIterator;
+
+use crate::scanner::{Direction, Scanner};
+
+/// Same as [`iter`](crate::iter), but any byte in `delims` ends a field, such as both a space
+/// and a tab.
+///
+/// A run of delimiters is one separator even if they are different bytes, and an escape
+/// character before any of them makes it literal. [`unescape`](crate::unescape) decodes the
+/// fields. As in `iter`, a delimiter which is also the escape character always splits, and then
+/// escapes nothing.
+///
+/// # Panics
+///
+/// Panics if `delims` is empty.
+///
+/// ```
+/// use escaped_delimiter::{iter_any, unescape};
+///
+/// let s = b"a\\\tb \t c\\ d\te";
+/// let s_vec: Vec<_> = iter_any(s, b" \t", b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a\\\tb"[..], &b"c\\ d"[..], &b"e"[..]]);
+/// assert_eq!(unescape(s_vec[1], b'\\'), &b"c d"[..]);
+/// ```
+#[inline]
+pub fn iter_any<'a>(slice: &'a [u8], delims: &'a [u8], escape: u8) -> AnyIter<'a> {
+    AnyIter::from_slice(slice, delims, escape)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyIter<'a> {
+    scanner: Scanner,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for AnyIter<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.find_bow();
+        self.inner = &self.inner[pos..];
+
+        let pos = self.find_eow()?;
+        let field = &self.inner[..pos];
+        self.inner = &self.inner[pos..];
+        Some(field)
+    }
+
+    /// Fields are not empty and are separated by at least one byte, and a byte other than a
+    /// delimiter at either end is in a field.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let is_delim = |&c: &u8| self.scanner.is_delim(c);
+        let lower = match (self.inner.first(), self.inner.last()) {
+            (Some(first), Some(last)) => !(is_delim(first) && is_delim(last)) as usize,
+            _ => 0,
+        };
+        (lower, Some(self.inner.len().div_ceil(2)))
+    }
+}
+
+impl<'a> DoubleEndedIterator for AnyIter<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let pos = self.rfind_eow()?;
+        self.inner = &self.inner[..pos];
+
+        let pos = self.rfind_bow();
+        let field = &self.inner[pos..];
+        self.inner = &self.inner[..pos];
+        Some(field)
+    }
+}
+
+impl FusedIterator for AnyIter<'_> {}
+
+impl<'a> AnyIter<'a> {
+    // As in `Iter`, `self.inner` always starts at the beginning of the slice or at an unescaped
+    // delimiter, so no leading delimiter can be escaped.
+    #[inline]
+    fn find_bow(&self) -> usize {
+        self.scanner.skip_boundaries(self.inner, Direction::Forward)
+    }
+
+    /// Returns the end of the field at the start of the rest of the slice, once the leading
+    /// delimiters are skipped by `find_bow`.
+    #[inline]
+    fn find_eow(&self) -> Option<usize> {
+        if self.inner.is_empty() {
+            return None;
+        }
+        let pos = self.scanner.find_boundary(self.inner, Direction::Forward);
+        Some(pos.unwrap_or(self.inner.len()))
+    }
+
+    #[inline]
+    fn rfind_eow(&self) -> Option<usize> {
+        let trailing = self
+            .scanner
+            .skip_boundaries(self.inner, Direction::Backward);
+        Some(self.inner.len() - trailing).filter(|&end| end > 0)
+    }
+
+    #[inline]
+    fn rfind_bow(&self) -> usize {
+        self.scanner
+            .find_boundary(self.inner, Direction::Backward)
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// See [`iter_any`].
+    #[inline]
+    pub fn from_slice(inner: &'a [u8], delims: &'a [u8], escape: u8) -> Self {
+        assert!(!delims.is_empty(), "there is no delimiter");
+        Self {
+            scanner: Scanner::with_delims(delims, escape),
+            inner,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Iter;
+
+    #[test]
+    fn test_iter_any() {
+        let s = b"XaZYXbXZcYZZdYYXZ";
+        let mut words = iter_any(s, b"XZ", b'Y');
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next_back(), Some(&b"dYY"[..]));
+        assert_eq!(words.next(), Some(&b"YXb"[..]));
+        assert_eq!(words.as_slice(), b"XZcYZZ");
+        assert_eq!(words.next_back(), Some(&b"cYZ"[..]));
+        assert_eq!(words.next(), None);
+        assert_eq!(words.next_back(), None);
+
+        let v: Vec<_> = iter_any(b"a \t b\\\tc\t\\ d", b" \t", b'\\')
+            .rev()
+            .collect();
+        assert_eq!(v, &[&b"\\ d"[..], b"b\\\tc", b"a"]);
+    }
+
+    #[test]
+    fn test_iter_any_exhaustive() {
+        // Splitting on `X` and `Z` is the same as splitting on `X` once every `Z` is an `X`.
+        for s in crate::test::strings(b"aXYZ", 7) {
+            let t: Vec<_> = s
+                .iter()
+                .map(|&c| if c == b'Z' { b'X' } else { c })
+                .collect();
+            let same = |field: &[u8], other: &[u8]| {
+                let (offset, other_offset) = (
+                    field.as_ptr() as usize - s.as_ptr() as usize,
+                    other.as_ptr() as usize - t.as_ptr() as usize,
+                );
+                offset == other_offset && field.len() == other.len()
+            };
+
+            let all_same = |words: Vec<&[u8]>, expected: Vec<&[u8]>| {
+                words.len() == expected.len()
+                    && words
+                        .iter()
+                        .zip(expected)
+                        .all(|(field, other)| same(field, other))
+            };
+
+            let words = iter_any(&s, b"XZ", b'Y');
+            let expected = Iter::from_slice(&t, b'X', b'Y');
+            assert!(all_same(words.collect(), expected.collect()), "{:?}", s);
+            assert!(
+                all_same(words.rev().collect(), expected.rev().collect()),
+                "{:?}",
+                s
+            );
+
+            // From both ends at once.
+            let (mut words, mut expected) = (words, expected);
+            loop {
+                let (field, other) = (words.next(), expected.next());
+                assert_eq!(field.map(<[u8]>::len), other.map(<[u8]>::len), "{:?}", s);
+                let (field, other) = (words.next_back(), expected.next_back());
+                assert_eq!(field.map(<[u8]>::len), other.map(<[u8]>::len), "{:?}", s);
+                assert!(same(words.as_slice(), expected.as_slice()), "{:?}", s);
+                if field.is_none() {
+                    break;
+                }
+            }
+
+            crate::test::check_size_hint(iter_any(&s, b"XZ", b'Y'));
+        }
+    }
+
+    #[test]
+    fn test_iter_any_escape_delim() {
+        // Every `Y` splits, and so escapes nothing.
+        let s = b"aYXbXYcYYd";
+        let v: Vec<_> = iter_any(s, b"XY", b'Y').collect();
+        assert_eq!(v, &[&b"a"[..], b"b", b"c", b"d"]);
+        let mut v: Vec<_> = iter_any(s, b"XY", b'Y').rev().collect();
+        v.reverse();
+        assert_eq!(v, &[&b"a"[..], b"b", b"c", b"d"]);
+
+        // Same as `Iter` with the escape character as its only delimiter.
+        for s in crate::test::strings(b"aXY", 7) {
+            let words = iter_any(&s, b"Y", b'Y');
+            assert!(words.eq(Iter::from_slice(&s, b'Y', b'Y')), "{:?}", s);
+            assert!(
+                words.rev().eq(Iter::from_slice(&s, b'Y', b'Y').rev()),
+                "{:?}",
+                s
+            );
+        }
+    }
+}
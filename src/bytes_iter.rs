@@ -0,0 +1,106 @@
+use bytes::Bytes;
+
+use crate::Iter;
+
+/// Same as [`iter`](crate::iter), but yields [`Bytes`] which share the buffer of `bytes`.
+///
+/// ```
+/// use bytes::Bytes;
+/// use escaped_delimiter::iter_bytes;
+///
+/// let s = Bytes::from_static(b"a\\ b c");
+/// let s_vec: Vec<_> = iter_bytes(s, b' ', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a\\ b"[..], &b"c"[..]]);
+/// ```
+#[inline]
+pub fn iter_bytes(bytes: Bytes, delim: u8, escape: u8) -> BytesIter {
+    BytesIter::from_bytes(bytes, delim, escape)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytesIter {
+    delim: u8,
+    escape: u8,
+    inner: Bytes,
+}
+
+impl Iterator for BytesIter {
+    type Item = Bytes;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut it = self.as_iter();
+        let field = it.next()?;
+        let field = self.inner.slice_ref(field);
+        self.inner = self.inner.slice_ref(it.as_slice());
+        Some(field)
+    }
+}
+
+impl DoubleEndedIterator for BytesIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let mut it = self.as_iter();
+        let field = it.next_back()?;
+        let field = self.inner.slice_ref(field);
+        self.inner = self.inner.slice_ref(it.as_slice());
+        Some(field)
+    }
+}
+
+impl BytesIter {
+    #[inline]
+    fn as_iter(&self) -> Iter<'_> {
+        Iter::from_slice(&self.inner, self.delim, self.escape)
+    }
+
+    /// Returns the rest of the inner buffer.
+    #[inline]
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.inner
+    }
+
+    /// See [`iter_bytes`].
+    #[inline]
+    pub fn from_bytes(inner: Bytes, delim: u8, escape: u8) -> Self {
+        Self {
+            delim,
+            escape,
+            inner,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bytes_iter() {
+        let inputs: &[&'static [u8]] = &[
+            b"",
+            b"abc",
+            b"abcXdefXXhX jklm",
+            b"XXabXYXcdeXYfYXXYYYXgYYX",
+            b"aY",
+        ];
+
+        for s in inputs {
+            let bytes = Bytes::from_static(s);
+
+            let words: Vec<_> = iter_bytes(bytes.clone(), b'X', b'Y').collect();
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            assert_eq!(words, expected);
+
+            let words: Vec<_> = iter_bytes(bytes, b'X', b'Y').rev().collect();
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').rev().collect();
+            assert_eq!(words, expected);
+        }
+
+        let bytes = Bytes::from(b"abcXdefXXhX jklm".to_vec());
+        let mut words = iter_bytes(bytes.clone(), b'X', b'Y');
+        let first = words.next().unwrap();
+        drop(bytes);
+        assert_eq!(first, &b"abc"[..]);
+        assert_eq!(words.next_back().unwrap(), &b" jklm"[..]);
+        assert_eq!(words.as_bytes(), &b"XdefXXhX"[..]);
+    }
+}
@@ -0,0 +1,192 @@
+/// A table of the escape sequences decoded by [`decode_chars`].
+///
+/// It is also implemented for closures of the same signature as [`EscapeTable::decode`].
+pub trait EscapeTable {
+    /// Decodes the escape sequence at the start of `rest`, the text right after an escape
+    /// character, into a character and the number of bytes of `rest` it takes up.
+    ///
+    /// Returns `None` if `rest` does not start with a sequence of the table, including if it
+    /// only starts with a part of one.
+    fn decode(&self, rest: &str) -> Option<(char, usize)>;
+}
+
+impl<F: Fn(&str) -> Option<(char, usize)>> EscapeTable for F {
+    #[inline]
+    fn decode(&self, rest: &str) -> Option<(char, usize)> {
+        self(rest)
+    }
+}
+
+/// The escape sequences `\n`, `\r`, `\t`, `\0` and `\u{...}` of Rust string literals, with any
+/// escape character in place of `\`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RustEscapes;
+
+impl EscapeTable for RustEscapes {
+    fn decode(&self, rest: &str) -> Option<(char, usize)> {
+        let c = match rest.as_bytes().first()? {
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'0' => '\0',
+            b'u' => return decode_unicode(rest),
+            _ => return None,
+        };
+        Some((c, 1))
+    }
+}
+
+/// Decodes `u{...}` of one to six hex digits.
+fn decode_unicode(rest: &str) -> Option<(char, usize)> {
+    let hex = rest.strip_prefix("u{")?;
+    let end = hex.find('}')?;
+    let digits = &hex[..end];
+    if !(1..=6).contains(&digits.len()) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let c = char::from_u32(u32::from_str_radix(digits, 16).ok()?)?;
+    Some((c, end + 3))
+}
+
+/// Returns an iterator of the characters of a field, such as one of
+/// [`iter_str`](crate::iter_str), with its escape sequences decoded by `table`.
+///
+/// An escaped delimiter or escape character is always decoded as itself, whatever the table says,
+/// so that the field means the same as it does to the byte iterators. An escape character
+/// followed by anything else, which the table does not decode, makes that character literal.
+///
+/// An escape sequence cut off by the end of the field is not one of the table, so its first
+/// character is made literal, and a lone escape character at the end is kept as is, as in
+/// [`unescape`](crate::unescape).
+///
+/// # Panics
+///
+/// Panics if `delim` or `escape` is not ASCII, or if the table returns a length which is not at
+/// a character boundary.
+///
+/// ```
+/// use escaped_delimiter::{decode_chars, iter_str, RustEscapes};
+///
+/// let s = "caf\\u{e9}\\,\\n,\\q\\\\";
+/// let fields: Vec<String> = iter_str(s, b',', b'\\')
+///     .map(|f| decode_chars(f, b',', b'\\', RustEscapes).collect())
+///     .collect();
+/// assert_eq!(fields, &["caf\u{e9},\n", "q\\"]);
+/// ```
+#[inline]
+pub fn decode_chars<T: EscapeTable>(
+    field: &str,
+    delim: u8,
+    escape: u8,
+    table: T,
+) -> DecodedChars<'_, T> {
+    assert!(delim.is_ascii(), "the delimiter is not ASCII");
+    assert!(escape.is_ascii(), "the escape character is not ASCII");
+
+    DecodedChars {
+        delim: delim as char,
+        escape: escape as char,
+        table,
+        rest: field,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedChars<'a, T> {
+    delim: char,
+    escape: char,
+    table: T,
+    rest: &'a str,
+}
+
+impl<'a, T> DecodedChars<'a, T> {
+    /// Returns the rest of the field, not decoded yet.
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        self.rest
+    }
+}
+
+impl<'a, T: EscapeTable> Iterator for DecodedChars<'a, T> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.rest.chars();
+        let c = chars.next()?;
+        if c != self.escape {
+            self.rest = chars.as_str();
+            return Some(c);
+        }
+
+        let after = chars.as_str();
+        let mut chars = after.chars();
+        let Some(next) = chars.next() else {
+            self.rest = after;
+            return Some(c);
+        };
+
+        if next != self.delim && next != self.escape {
+            if let Some((decoded, len)) = self.table.decode(after) {
+                self.rest = &after[len..];
+                return Some(decoded);
+            }
+        }
+
+        self.rest = chars.as_str();
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode(field: &str) -> String {
+        decode_chars(field, b'X', b'Y', RustEscapes).collect()
+    }
+
+    #[test]
+    fn test_decode_chars() {
+        assert_eq!(decode("aYnb"), "a\nb");
+        assert_eq!(decode("YtYrY0"), "\t\r\0");
+        assert_eq!(decode("aYYnb"), "aYnb");
+        assert_eq!(decode("YXYYY\u{3042}"), "XY\u{3042}");
+        assert_eq!(decode("YqYinYU"), "qinU");
+        assert_eq!(decode("Yu{3042}Yu{1f600}"), "\u{3042}\u{1f600}");
+
+        // Not a sequence of the table, or cut off.
+        assert_eq!(
+            decode("Yu{}Yu{110000}Yu{1234567}"),
+            "u{}u{110000}u{1234567}"
+        );
+        assert_eq!(decode("aYu{30"), "au{30");
+        assert_eq!(decode("aYu"), "au");
+        assert_eq!(decode("aY"), "aY");
+        assert_eq!(decode("aYYY"), "aYY");
+        assert_eq!(decode(""), "");
+    }
+
+    #[test]
+    fn test_decode_chars_table() {
+        // The table cannot redefine an escaped delimiter or escape character.
+        let table = |rest: &str| match rest.chars().next()? {
+            'X' | 'Y' => Some(('!', 1)),
+            'e' => Some(('\u{e9}', 1)),
+            'c' if rest.starts_with("cafe") => Some(('\u{2615}', 4)),
+            _ => None,
+        };
+        let s: String = decode_chars("YXYYYeYcafeYcaf", b'X', b'Y', table).collect();
+        assert_eq!(s, "XY\u{e9}\u{2615}caf");
+
+        let mut chars = decode_chars("YeYcafe", b'X', b'Y', table);
+        assert_eq!(chars.next(), Some('\u{e9}'));
+        assert_eq!(chars.as_str(), "Ycafe");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_chars_non_ascii() {
+        decode_chars("a", b'X', 0xe3, RustEscapes);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::Iter;
+
+/// A running checksum of the unescaped fields, used by [`Iter::with_checksum`].
+pub trait FieldHasher {
+    /// Feeds the bytes of an unescaped field.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all the bytes fed so far.
+    fn value(&self) -> u32;
+}
+
+/// The CRC-32 (IEEE 802.3) checksum, which is the default of [`Iter::with_checksum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crc32 {
+    state: u32,
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+impl Crc32 {
+    /// Returns the checksum of no bytes.
+    #[inline]
+    pub fn new() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FieldHasher for Crc32 {
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.state = (self.state >> 8) ^ CRC32_TABLE[((self.state ^ b as u32) & 0xff) as usize];
+        }
+    }
+
+    #[inline]
+    fn value(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the fields and the CRC-32 of all the unescaped fields up to and
+    /// including each, concatenated.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let (_, crc) = iter(b"1234 56\\789", b' ', b'\\').with_checksum().last().unwrap();
+    /// assert_eq!(crc, 0xcbf4_3926);
+    /// ```
+    #[inline]
+    pub fn with_checksum(self) -> Checksummed<'a, Crc32> {
+        self.with_hasher(Crc32::new())
+    }
+
+    /// Same as [`Iter::with_checksum`], but with another checksum.
+    #[inline]
+    pub fn with_hasher<H: FieldHasher>(self, hasher: H) -> Checksummed<'a, H> {
+        Checksummed {
+            hasher,
+            inner: self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checksummed<'a, H> {
+    hasher: H,
+    inner: Iter<'a>,
+}
+
+impl<'a, H: FieldHasher> Iterator for Checksummed<'a, H> {
+    type Item = (&'a [u8], u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        self.hasher.update(&self.inner.unescape(field));
+        Some((field, self.hasher.value()))
+    }
+}
+
+impl<'a, H> Checksummed<'a, H> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    /// Returns the hasher, which has been fed the fields yielded so far.
+    #[inline]
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        let mut crc = Crc32::new();
+        assert_eq!(crc.value(), 0);
+        crc.update(b"123456789");
+        assert_eq!(crc.value(), 0xcbf4_3926);
+
+        let mut crc = Crc32::new();
+        crc.update(b"The quick brown fox ");
+        crc.update(b"jumps over the lazy dog");
+        assert_eq!(crc.value(), 0x414f_a339);
+    }
+
+    #[test]
+    fn test_with_checksum() {
+        let s = b"XaYXbXXcYYXdYeX";
+        let fields = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+
+        let sums: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .with_checksum()
+            .map(|(_, sum)| sum)
+            .collect();
+        assert_eq!(sums.len(), fields.len());
+        for (i, sum) in sums.iter().enumerate() {
+            let mut crc = Crc32::new();
+            crc.update(&fields[..=i].concat());
+            assert_eq!(*sum, crc.value());
+        }
+
+        let mut it = Iter::from_slice(b"XX", b'X', b'Y').with_checksum();
+        assert_eq!(it.next(), None);
+        assert_eq!(it.hasher().value(), 0);
+    }
+
+    #[test]
+    fn test_with_hasher() {
+        struct Len(u32);
+        impl FieldHasher for Len {
+            fn update(&mut self, bytes: &[u8]) {
+                self.0 += bytes.len() as u32;
+            }
+            fn value(&self) -> u32 {
+                self.0
+            }
+        }
+
+        let sums: Vec<_> = Iter::from_slice(b"aYXbXcYYXd", b'X', b'Y')
+            .with_hasher(Len(0))
+            .collect();
+        assert_eq!(sums, &[(&b"aYXb"[..], 3), (&b"cYY"[..], 5), (&b"d"[..], 6)]);
+    }
+}
@@ -0,0 +1,981 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, Read};
+use std::num::NonZeroUsize;
+use std::str::{self, Utf8Error};
+
+use crate::escape::unescape_into;
+use crate::scanner::EscapeTracker;
+use crate::{unescape, Iter};
+
+/// Pushes every unescaped field into `into`.
+///
+/// ```
+/// use std::collections::VecDeque;
+/// use escaped_delimiter::extend_unescaped;
+///
+/// let mut v = VecDeque::new();
+/// extend_unescaped(b"a\\ b c", b' ', b'\\', &mut v);
+/// assert_eq!(v, &[&b"a b"[..], &b"c"[..]]);
+/// ```
+pub fn extend_unescaped<C: Extend<Vec<u8>>>(slice: &[u8], delim: u8, escape: u8, into: &mut C) {
+    into.extend(
+        Iter::from_slice(slice, delim, escape).map(|field| unescape(field, escape).into_owned()),
+    );
+}
+
+/// Collects the unescaped fields which `keep` returns `true` for.
+///
+/// `keep` sees the unescaped field.
+///
+/// ```
+/// use escaped_delimiter::filter_fields;
+///
+/// let v = filter_fields(b"a \\  b", b' ', b'\\', |f| f != b" ");
+/// assert_eq!(v, &[&b"a"[..], &b"b"[..]]);
+/// ```
+pub fn filter_fields<F: Fn(&[u8]) -> bool>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    keep: F,
+) -> Vec<Cow<'_, [u8]>> {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .filter(|field| keep(field))
+        .collect()
+}
+
+/// Collects the unescaped fields into a table of unique values and the index of each field in
+/// the table.
+///
+/// The table keeps the order of the first occurrences.
+///
+/// # Panics
+///
+/// Panics if there are more than `u32::MAX` unique fields.
+///
+/// ```
+/// use escaped_delimiter::collect_interned;
+///
+/// let (indices, table) = collect_interned(b"a b a\\ b a", b' ', b'\\');
+/// assert_eq!(indices, &[0, 1, 2, 0]);
+/// assert_eq!(table, &[&b"a"[..], &b"b"[..], &b"a b"[..]]);
+/// ```
+pub fn collect_interned(slice: &[u8], delim: u8, escape: u8) -> (Vec<u32>, Vec<Vec<u8>>) {
+    let mut indices = Vec::new();
+    let mut table = Vec::new();
+    let mut seen = HashMap::new();
+
+    for field in Iter::from_slice(slice, delim, escape).unescaped() {
+        let index = *seen.entry(field).or_insert_with_key(|field| {
+            let index = u32::try_from(table.len()).expect("too many unique fields");
+            table.push(field.to_vec());
+            index
+        });
+        indices.push(index);
+    }
+
+    (indices, table)
+}
+
+/// Collects the unescaped fields into a map from the index of each field.
+///
+/// The indices count the fields yielded by [`iter`](crate::iter), so the empty fields which it
+/// skips take none.
+///
+/// ```
+/// use escaped_delimiter::to_indexed_map;
+///
+/// let m = to_indexed_map(b"a\\ b  c", b' ', b'\\');
+/// assert_eq!(m.len(), 2);
+/// assert_eq!(m[&0], b"a b");
+/// assert_eq!(m[&1], b"c");
+/// ```
+pub fn to_indexed_map(slice: &[u8], delim: u8, escape: u8) -> BTreeMap<usize, Vec<u8>> {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .map(Cow::into_owned)
+        .enumerate()
+        .collect()
+}
+
+/// Calls `f` with each unescaped field, decoding all of them into `scratch`.
+///
+/// `scratch` is cleared before each field, so that a single allocation is reused for the whole
+/// slice.
+///
+/// ```
+/// use escaped_delimiter::for_each_decoded;
+///
+/// let mut scratch = Vec::new();
+/// let mut v = Vec::new();
+/// for_each_decoded(b"a\\ b c", b' ', b'\\', &mut scratch, |f| v.push(f.len()));
+/// assert_eq!(v, &[3, 1]);
+/// ```
+pub fn for_each_decoded<F: FnMut(&[u8])>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    scratch: &mut Vec<u8>,
+    mut f: F,
+) {
+    for field in Iter::from_slice(slice, delim, escape) {
+        scratch.clear();
+        unescape_into(field, escape, scratch);
+        f(scratch);
+    }
+}
+
+/// Calls `on_field` with each raw field as [`iter`](crate::iter) yields it, and `on_progress`
+/// with the number of bytes scanned so far after every `step` bytes, so that a long field still
+/// reports progress while it is being scanned.
+///
+/// After the last byte, `on_progress` is called with the length of the slice, unless it has just
+/// been.
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use escaped_delimiter::for_each_field_progress;
+///
+/// let (mut progress, mut fields) = (Vec::new(), Vec::new());
+/// let step = NonZeroUsize::new(4).unwrap();
+/// for_each_field_progress(b"a b\\ c de", b' ', b'\\', step, |n| progress.push(n), |f| fields.push(f));
+/// assert_eq!(progress, &[4, 8, 9]);
+/// assert_eq!(fields, &[&b"a"[..], &b"b\\ c"[..], &b"de"[..]]);
+/// ```
+pub fn for_each_field_progress<'a, P, F>(
+    slice: &'a [u8],
+    delim: u8,
+    escape: u8,
+    step: NonZeroUsize,
+    mut on_progress: P,
+    mut on_field: F,
+) where
+    P: FnMut(usize),
+    F: FnMut(&'a [u8]),
+{
+    let mut start = 0;
+    let mut tracker = EscapeTracker::new(escape);
+    for (i, &c) in slice.iter().enumerate() {
+        if tracker.is_unescaped_target(c, c == delim) {
+            if start < i {
+                on_field(&slice[start..i]);
+            }
+            start = i + 1;
+        }
+
+        if (i + 1) % step == 0 {
+            on_progress(i + 1);
+        }
+    }
+
+    if start < slice.len() {
+        on_field(&slice[start..]);
+    }
+    if slice.len() % step != 0 {
+        on_progress(slice.len());
+    }
+}
+
+/// Folds the unescaped fields into an accumulator, like [`Iterator::fold`].
+///
+/// A field with no escape characters is passed as is, and any other is decoded into a buffer
+/// reused for the whole slice, so `f` sees a field only during its call.
+///
+/// ```
+/// use escaped_delimiter::fold_decoded;
+///
+/// let sum = fold_decoded(b"1 2\\3 4", b' ', b'\\', 0, |sum, f| {
+///     sum + std::str::from_utf8(f).unwrap().parse::<u32>().unwrap()
+/// });
+/// assert_eq!(sum, 1 + 23 + 4);
+/// ```
+pub fn fold_decoded<B, F>(slice: &[u8], delim: u8, escape: u8, init: B, mut f: F) -> B
+where
+    F: FnMut(B, &[u8]) -> B,
+{
+    let mut scratch = Vec::new();
+    Iter::from_slice(slice, delim, escape).fold(init, |acc, field| {
+        if field.contains(&escape) {
+            scratch.clear();
+            unescape_into(field, escape, &mut scratch);
+            f(acc, &scratch)
+        } else {
+            f(acc, field)
+        }
+    })
+}
+
+/// Collects what `f` returns for each unescaped field, up to the first `None`, like
+/// [`Iterator::map_while`].
+///
+/// The fields are decoded as in [`fold_decoded`], and none after the one for which `f` returns
+/// `None` is decoded.
+///
+/// ```
+/// use escaped_delimiter::decode_while;
+///
+/// let s = b"Host:\\ a Accept:\\ b -- body\\ text";
+/// let headers = decode_while(s, b' ', b'\\', |f| (f != b"--").then(|| f.to_vec()));
+/// assert_eq!(headers, &[&b"Host: a"[..], &b"Accept: b"[..]]);
+/// ```
+pub fn decode_while<T, F>(slice: &[u8], delim: u8, escape: u8, mut f: F) -> Vec<T>
+where
+    F: FnMut(&[u8]) -> Option<T>,
+{
+    let mut scratch = Vec::new();
+    Iter::from_slice(slice, delim, escape)
+        .map_while(|field| {
+            if field.contains(&escape) {
+                scratch.clear();
+                unescape_into(field, escape, &mut scratch);
+                f(&scratch)
+            } else {
+                f(field)
+            }
+        })
+        .collect()
+}
+
+/// Returns the unescaped fields, without those equal to the one right before.
+///
+/// ```
+/// use escaped_delimiter::dedup_consecutive;
+///
+/// let v = dedup_consecutive(b"a a\\  b a", b' ', b'\\');
+/// assert_eq!(v, &[&b"a"[..], &b"a "[..], &b"b"[..], &b"a"[..]]);
+/// ```
+pub fn dedup_consecutive(slice: &[u8], delim: u8, escape: u8) -> Vec<Cow<'_, [u8]>> {
+    let mut fields: Vec<_> = Iter::from_slice(slice, delim, escape).unescaped().collect();
+    fields.dedup();
+    fields
+}
+
+/// Returns the distinct unescaped fields, in the order of their first occurrences.
+///
+/// Unlike [`dedup_consecutive`], this removes every repeat, not only the adjacent ones. It is the
+/// table of [`collect_interned`] without the indices.
+///
+/// ```
+/// use escaped_delimiter::unique_fields;
+///
+/// let v = unique_fields(b"b a b\\  a b", b' ', b'\\');
+/// assert_eq!(v, &[&b"b"[..], &b"a"[..], &b"b "[..]]);
+/// ```
+pub fn unique_fields(slice: &[u8], delim: u8, escape: u8) -> Vec<Vec<u8>> {
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+    for field in Iter::from_slice(slice, delim, escape).unescaped() {
+        if !seen.contains(&field) {
+            unique.push(field.to_vec());
+            seen.insert(field);
+        }
+    }
+
+    unique
+}
+
+/// Groups the unescaped fields by the key `key` returns for each, wherever they are.
+///
+/// Unlike [`Iter::group_by`], which only groups adjacent fields, this buckets the whole slice.
+/// Each bucket keeps the order of its fields.
+///
+/// ```
+/// use escaped_delimiter::group_by_key;
+///
+/// let groups = group_by_key(b"ab c de\\ f g", b' ', b'\\', |f| f.len());
+/// assert_eq!(groups[&1], &[&b"c"[..], &b"g"[..]]);
+/// assert_eq!(groups[&2], &[&b"ab"[..]]);
+/// assert_eq!(groups[&4], &[&b"de f"[..]]);
+/// ```
+pub fn group_by_key<K, F>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    mut key: F,
+) -> HashMap<K, Vec<Vec<u8>>>
+where
+    K: Eq + Hash,
+    F: FnMut(&[u8]) -> K,
+{
+    let mut groups = HashMap::new();
+    for field in Iter::from_slice(slice, delim, escape).unescaped() {
+        groups
+            .entry(key(&field))
+            .or_insert_with(Vec::new)
+            .push(field.into_owned());
+    }
+
+    groups
+}
+
+/// Same as `iter(slice, delim, escape).collect::<Vec<_>>()`, but counts the fields first so that
+/// the `Vec` is allocated once with the exact capacity.
+///
+/// This scans the slice twice, which the `collect` benchmark shows to take about twice as long
+/// as collecting directly, whatever the field lengths: reallocating is cheaper than splitting
+/// again. Prefer it only when the `Vec` must not be over-allocated.
+///
+/// ```
+/// use escaped_delimiter::collect_fields;
+///
+/// let v = collect_fields(b"a\\ b  c", b' ', b'\\');
+/// assert_eq!(v, &[&b"a\\ b"[..], &b"c"[..]]);
+/// assert_eq!(v.capacity(), 2);
+/// ```
+pub fn collect_fields(slice: &[u8], delim: u8, escape: u8) -> Vec<&[u8]> {
+    let it = Iter::from_slice(slice, delim, escape);
+    let mut fields = Vec::with_capacity(it.count_fields());
+    fields.extend(it);
+    fields
+}
+
+/// Collects the fields with unescaped `trim`s removed from both ends, without those which
+/// become empty.
+///
+/// ```
+/// use escaped_delimiter::trim_and_compact;
+///
+/// let v = trim_and_compact(b"a , , b,\\ ", b',', b'\\', b' ');
+/// assert_eq!(v, &[&b"a"[..], &b"b"[..], &b"\\ "[..]]);
+/// ```
+pub fn trim_and_compact(slice: &[u8], delim: u8, escape: u8, trim: u8) -> Vec<&[u8]> {
+    Iter::from_slice(slice, delim, escape)
+        .trim(trim)
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+/// Rewrites `buf` into its unescaped fields back to back, and returns their lengths.
+///
+/// Unescaping only shrinks a field, so this needs no buffer other than the returned lengths.
+///
+/// ```
+/// use escaped_delimiter::compact_in_place;
+///
+/// let mut buf = b"a\\ b  c".to_vec();
+/// assert_eq!(compact_in_place(&mut buf, b' ', b'\\'), &[3, 1]);
+/// assert_eq!(buf, b"a bc");
+/// ```
+pub fn compact_in_place(buf: &mut Vec<u8>, delim: u8, escape: u8) -> Vec<usize> {
+    let mut lens = Vec::new();
+    let (mut w, mut len) = (0, 0);
+    let mut escaped = false;
+    for r in 0..buf.len() {
+        let c = buf[r];
+        if escaped {
+            escaped = false;
+        } else if c == escape && c != delim {
+            escaped = true;
+            continue;
+        } else if c == delim {
+            if len > 0 {
+                lens.push(len);
+                len = 0;
+            }
+            continue;
+        }
+
+        buf[w] = c;
+        w += 1;
+        len += 1;
+    }
+    if escaped {
+        buf[w] = escape;
+        w += 1;
+        len += 1;
+    }
+    if len > 0 {
+        lens.push(len);
+    }
+
+    buf.truncate(w);
+    lens
+}
+
+impl<'a> Iter<'a> {
+    /// Returns the raw slice before the last field and the unescaped last field.
+    ///
+    /// The head excludes the delimiter right before the last field, and is not split further.
+    /// Returns `None` if there are no fields.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let (head, last) = iter(b"a b\\ c d\\ ", b' ', b'\\').take_last().unwrap();
+    /// assert_eq!(head, b"a b\\ c");
+    /// assert_eq!(last, &b"d "[..]);
+    /// ```
+    pub fn take_last(mut self) -> Option<(&'a [u8], Cow<'a, [u8]>)> {
+        let last = self.next_back()?;
+        let head = match self.inner.split_last() {
+            Some((_, head)) => head,
+            None => self.inner,
+        };
+        Some((head, self.unescape(last)))
+    }
+
+    /// Collects the rest of the fields, unescaped and owned.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"a b\\ c d", b' ', b'\\');
+    /// it.next();
+    /// assert_eq!(it.to_owned_fields(), &[&b"b c"[..], &b"d"[..]]);
+    /// ```
+    pub fn to_owned_fields(self) -> Vec<Vec<u8>> {
+        self.unescaped().map(Cow::into_owned).collect()
+    }
+}
+
+/// Reads `r` to the end and collects its fields, unescaped and owned.
+///
+/// ```
+/// use std::io::Cursor;
+/// use escaped_delimiter::from_reader_all;
+///
+/// let fields = from_reader_all(Cursor::new(b"a\\ b c"), b' ', b'\\').unwrap();
+/// assert_eq!(fields, &[&b"a b"[..], &b"c"[..]]);
+/// ```
+pub fn from_reader_all<R: Read>(mut r: R, delim: u8, escape: u8) -> io::Result<Vec<Vec<u8>>> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    Ok(Iter::from_slice(&buf, delim, escape).to_owned_fields())
+}
+
+/// Collects the unescaped fields of `input`, keeping its ownership.
+///
+/// If `input` is borrowed, so is each field without escape characters to remove, as with
+/// [`Iter::unescaped`]. If it is owned, it is dropped here, so every field is owned.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use escaped_delimiter::fields_from_cow;
+///
+/// let v = fields_from_cow(Cow::Borrowed(b"a\\ b c"), b' ', b'\\');
+/// assert!(matches!(v[..], [Cow::Owned(_), Cow::Borrowed(b"c")]));
+///
+/// let v = fields_from_cow(Cow::Owned(b"a\\ b c".to_vec()), b' ', b'\\');
+/// assert!(matches!(v[..], [Cow::Owned(_), Cow::Owned(_)]));
+/// assert_eq!(v, &[&b"a b"[..], &b"c"[..]]);
+/// ```
+pub fn fields_from_cow(input: Cow<'_, [u8]>, delim: u8, escape: u8) -> Vec<Cow<'_, [u8]>> {
+    match input {
+        Cow::Borrowed(slice) => Iter::from_slice(slice, delim, escape).unescaped().collect(),
+        Cow::Owned(buf) => Iter::from_slice(&buf, delim, escape)
+            .to_owned_fields()
+            .into_iter()
+            .map(Cow::Owned)
+            .collect(),
+    }
+}
+
+/// Returns an iterator of the unescaped fields from the last one, as [`slice::rsplit`] is to
+/// [`slice::split`].
+///
+/// A field borrows `slice` if it has no escape characters to remove.
+///
+/// ```
+/// use escaped_delimiter::rfields_decoded;
+///
+/// let v: Vec<_> = rfields_decoded(b"a\\ b c", b' ', b'\\').collect();
+/// assert_eq!(v, &[&b"c"[..], &b"a b"[..]]);
+/// ```
+#[inline]
+pub fn rfields_decoded(slice: &[u8], delim: u8, escape: u8) -> impl Iterator<Item = Cow<'_, [u8]>> {
+    Iter::from_slice(slice, delim, escape).unescaped().rev()
+}
+
+/// Returns an iterator of the unescaped fields converted to UTF-8 lossily.
+///
+/// Invalid sequences are replaced with U+FFFD. A field borrows `slice` if it has neither escape
+/// characters to remove nor invalid sequences.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use escaped_delimiter::fields_lossy;
+///
+/// let v: Vec<_> = fields_lossy(b"caf\xc3\xa9 a\\ b \xff", b' ', b'\\').collect();
+/// assert_eq!(v, &["caf\u{e9}", "a b", "\u{fffd}"]);
+/// assert!(matches!(v[0], Cow::Borrowed(_)));
+/// ```
+pub fn fields_lossy(slice: &[u8], delim: u8, escape: u8) -> impl Iterator<Item = Cow<'_, str>> {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .map(|field| match field {
+            Cow::Borrowed(field) => String::from_utf8_lossy(field),
+            Cow::Owned(field) => Cow::Owned(
+                String::from_utf8(field)
+                    .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()),
+            ),
+        })
+}
+
+/// Collects the raw fields as `&str`, failing at the first field which is not valid UTF-8.
+///
+/// This is the strict counterpart to [`fields_lossy`]. The fields are not unescaped, so that
+/// they can borrow `slice`; call [`unescape`] on their bytes if needed, which keeps them valid as
+/// long as the escape character is ASCII.
+///
+/// ```
+/// use escaped_delimiter::collect_str;
+///
+/// assert_eq!(collect_str(b"caf\xc3\xa9 a\\ b", b' ', b'\\').unwrap(), &["caf\u{e9}", "a\\ b"]);
+///
+/// let err = collect_str(b"a b\xff c", b' ', b'\\').unwrap_err();
+/// assert_eq!((err.index, err.offset), (1, 2));
+/// assert_eq!(err.error.valid_up_to(), 1);
+/// ```
+pub fn collect_str(slice: &[u8], delim: u8, escape: u8) -> Result<Vec<&str>, FieldUtf8Error> {
+    Iter::from_slice(slice, delim, escape)
+        .enumerate()
+        .map(|(index, field)| {
+            str::from_utf8(field).map_err(|error| FieldUtf8Error {
+                index,
+                offset: field.as_ptr() as usize - slice.as_ptr() as usize,
+                error,
+            })
+        })
+        .collect()
+}
+
+/// The error returned by [`collect_str`].
+///
+/// It converts into the bare [`Utf8Error`], which is relative to the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldUtf8Error {
+    /// The index of the field.
+    pub index: usize,
+    /// The offset of the field from the start of the slice.
+    pub offset: usize,
+    /// What is wrong with the field.
+    pub error: Utf8Error,
+}
+
+impl fmt::Display for FieldUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "field {} at offset {}: {}",
+            self.index, self.offset, self.error
+        )
+    }
+}
+
+impl Error for FieldUtf8Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<FieldUtf8Error> for Utf8Error {
+    #[inline]
+    fn from(e: FieldUtf8Error) -> Self {
+        e.error
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_extend_unescaped() {
+        let s = b"cXaYXbXXYYX";
+        let mut v = vec![b"z".to_vec()];
+        extend_unescaped(s, b'X', b'Y', &mut v);
+        assert_eq!(v, &[&b"z"[..], &b"c"[..], &b"aXb"[..], &b"Y"[..]]);
+
+        let s = b"cXaYXbXcXXYY";
+        let mut set = BTreeSet::new();
+        extend_unescaped(s, b'X', b'Y', &mut set);
+        let set: Vec<_> = set.into_iter().collect();
+        assert_eq!(set, &[&b"Y"[..], &b"aXb"[..], &b"c"[..]]);
+    }
+
+    #[test]
+    fn test_filter_fields() {
+        let s = b"aXYXbXYYXcXaX";
+        let v = filter_fields(s, b'X', b'Y', |f| f != b"a");
+        assert_eq!(v, &[&b"Xb"[..], &b"Y"[..], &b"c"[..]]);
+        let v = filter_fields(s, b'X', b'Y', |f| f == b"Xb" || f == b"Y");
+        assert_eq!(v, &[&b"Xb"[..], &b"Y"[..]]);
+        let v = filter_fields(s, b'X', b'Y', |f| !f.is_empty());
+        assert_eq!(v, &[&b"a"[..], &b"Xb"[..], &b"Y"[..], &b"c"[..], &b"a"[..]]);
+    }
+
+    #[test]
+    fn test_collect_interned() {
+        let s = b"aXbXaXYXaXbXYXaXc";
+        let (indices, table) = collect_interned(s, b'X', b'Y');
+        assert_eq!(indices, &[0, 1, 0, 2, 1, 2, 3]);
+        assert_eq!(table, &[&b"a"[..], &b"b"[..], &b"Xa"[..], &b"c"[..]]);
+
+        let restored: Vec<_> = indices.iter().map(|&i| &table[i as usize][..]).collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+        assert_eq!(expected, restored);
+
+        assert_eq!(collect_interned(b"", b'X', b'Y'), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_to_indexed_map() {
+        let m = to_indexed_map(b"XaYXbXXYYXcY", b'X', b'Y');
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), &[0, 1, 2]);
+        assert_eq!(
+            m.values().collect::<Vec<_>>(),
+            &[&b"aXb"[..], &b"Y"[..], &b"cY"[..]]
+        );
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let m = to_indexed_map(&s, b'X', b'Y');
+            let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y').unescaped().collect();
+            assert_eq!(m.len(), expected.len());
+            for (i, field) in expected.iter().enumerate() {
+                assert_eq!(m[&i], &field[..], "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_owned_fields() {
+        let s = b"XaYXbXXcYYXdYeX";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        let expected: Vec<_> = words.map(|f| unescape(f, b'Y').into_owned()).collect();
+        assert_eq!(words.to_owned_fields(), expected);
+
+        words.next();
+        words.next_back();
+        assert_eq!(words.to_owned_fields(), &expected[1..expected.len() - 1]);
+
+        let words = Iter::with_specials(s, b'X', b'Y', b"");
+        assert_eq!(
+            words.to_owned_fields(),
+            &[&b"aXb"[..], &b"cY"[..], &b"dYe"[..]]
+        );
+    }
+
+    #[test]
+    fn test_from_reader_all() {
+        let s = b"XaYXbXXcYYXdYeX";
+        let fields = from_reader_all(io::Cursor::new(s), b'X', b'Y').unwrap();
+        assert_eq!(fields, &[&b"aXb"[..], b"cY", b"de"]);
+
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::Other.into())
+            }
+        }
+        let r = io::Cursor::new(b"aXb").chain(Failing);
+        assert!(from_reader_all(r, b'X', b'Y').is_err());
+    }
+
+    #[test]
+    fn test_dedup_consecutive() {
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[]),
+            (b"aXaXaXb", &[b"a", b"b"]),
+            (b"aXbXbXYbXc", &[b"a", b"b", b"c"]),
+            (b"aXbXcXc", &[b"a", b"b", b"c"]),
+            (b"aXXaXYaXXa", &[b"a"]),
+            (b"aXbXcXb", &[b"a", b"b", b"c", b"b"]),
+            (b"YXXYXXYYXY", &[b"X", b"Y"]),
+        ];
+
+        for &(s, expected) in cases {
+            assert_eq!(dedup_consecutive(s, b'X', b'Y'), expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_unique_fields() {
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[]),
+            (b"aXbXaXcXbXa", &[b"a", b"b", b"c"]),
+            (b"aXYXaXXaXYYXYXaXa", &[b"a", b"Xa", b"Y"]),
+            (b"YXXYXXYYXY", &[b"X", b"Y"]),
+        ];
+        for &(s, expected) in cases {
+            assert_eq!(unique_fields(s, b'X', b'Y'), expected, "{:?}", s);
+        }
+
+        for s in crate::test::strings(b"aXY", 7) {
+            assert_eq!(
+                unique_fields(&s, b'X', b'Y'),
+                collect_interned(&s, b'X', b'Y').1
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_by_key() {
+        let groups = group_by_key(b"aXbbXYXXcYYXddXeXXfff", b'X', b'Y', |f| f.len());
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&1], &[&b"a"[..], b"X", b"e"]);
+        assert_eq!(groups[&2], &[&b"bb"[..], b"cY", b"dd"]);
+        assert_eq!(groups[&3], &[b"fff"]);
+
+        assert!(group_by_key(b"XX", b'X', b'Y', |_| ()).is_empty());
+    }
+
+    #[test]
+    fn test_collect_fields() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"abc", b"XaYXXbYYXcXYX", b"aXbXcXdXeXfXgXh"];
+        for s in inputs {
+            let fields = collect_fields(s, b'X', b'Y');
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            assert_eq!(fields, expected);
+            assert_eq!(fields.capacity(), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_trim_and_compact() {
+        let s = b"  aXZZZXZ ZXZYZ XYZZXZZbZZYZXZZ";
+        let v = trim_and_compact(s, b'X', b'Y', b'Z');
+        assert_eq!(v, &[&b"  a"[..], b" ", b"YZ ", b"YZ", b"bZZYZ"]);
+
+        assert!(trim_and_compact(b"ZZXXZ", b'X', b'Y', b'Z').is_empty());
+        assert_eq!(trim_and_compact(b"ZYZZ", b'X', b'Y', b'Z'), &[&b"YZ"[..]]);
+    }
+
+    #[test]
+    fn test_compact_in_place() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"XX",
+            b"Y",
+            b"abc",
+            b"XaYXXbYYXcXYX",
+            b"YYYXXYaYbYX",
+            b"aXbY",
+            b"XYXYYYX",
+        ];
+        for s in inputs {
+            let expected = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+
+            let mut buf = s.to_vec();
+            let lens = compact_in_place(&mut buf, b'X', b'Y');
+            let mut rest = &buf[..];
+            let fields: Vec<_> = lens
+                .iter()
+                .map(|&len| {
+                    let (field, tail) = rest.split_at(len);
+                    rest = tail;
+                    field
+                })
+                .collect();
+            assert!(rest.is_empty());
+            assert_eq!(fields, expected, "{:?}", s);
+        }
+
+        let mut buf = b"aXXbX".to_vec();
+        assert_eq!(compact_in_place(&mut buf, b'X', b'X'), &[1, 1]);
+        assert_eq!(buf, b"ab");
+    }
+
+    #[test]
+    fn test_fold_decoded() {
+        let s = b"aYXbXXcYYXdYeXfY";
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+
+        let len = fold_decoded(s, b'X', b'Y', 0, |len, f| len + f.len());
+        assert_eq!(len, expected.iter().map(|f| f.len()).sum::<usize>());
+        assert_eq!(len, 9);
+
+        let concat = fold_decoded(s, b'X', b'Y', Vec::new(), |mut v, f| {
+            v.extend_from_slice(f);
+            v
+        });
+        assert_eq!(concat, expected.concat());
+        assert_eq!(concat, b"aXbcYdefY");
+
+        assert_eq!(fold_decoded(b"XX", b'X', b'Y', 7, |n, _| n + 1), 7);
+    }
+
+    #[test]
+    fn test_decode_while() {
+        let s = b"1X2Y3XYX4XaX5";
+        let parse = |f: &[u8]| str::from_utf8(f).ok()?.parse::<u32>().ok();
+        assert_eq!(decode_while(s, b'X', b'Y', parse), &[1, 23]);
+
+        let mut seen = Vec::new();
+        let v = decode_while(s, b'X', b'Y', |f| {
+            seen.push(f.to_vec());
+            (f != b"a").then_some(f.len())
+        });
+        assert_eq!(v, &[1, 2, 2]);
+        assert_eq!(seen, &[&b"1"[..], b"23", b"X4", b"a"]);
+
+        let all = decode_while(s, b'X', b'Y', |f| Some(f.to_vec()));
+        let expected = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+        assert_eq!(all, expected);
+        assert!(decode_while(b"", b'X', b'Y', |_| Some(())).is_empty());
+    }
+
+    #[test]
+    fn test_for_each_field_progress() {
+        let s = b"aYXbXXcYYXdYYYXXeY";
+        for step in 1..=s.len() + 1 {
+            let step = NonZeroUsize::new(step).unwrap();
+            let (mut progress, mut fields) = (Vec::new(), Vec::new());
+            for_each_field_progress(
+                s,
+                b'X',
+                b'Y',
+                step,
+                |n| progress.push(n),
+                |f| fields.push(f),
+            );
+            assert_eq!(fields, &[&b"aYXb"[..], b"cYY", b"dYYYX", b"eY"]);
+
+            let mut expected: Vec<_> = (step.get()..=s.len()).step_by(step.get()).collect();
+            if s.len() % step != 0 {
+                expected.push(s.len());
+            }
+            assert_eq!(progress, expected, "{}", step);
+        }
+
+        // A single long field reports progress as it goes.
+        let s = vec![b'Y'; 1050];
+        let step = NonZeroUsize::new(100).unwrap();
+        let (progress, mut reported_before) = (Cell::new(0), None);
+        for_each_field_progress(
+            &s,
+            b'X',
+            b'Y',
+            step,
+            |n| progress.set(n),
+            |_| reported_before = Some(progress.get()),
+        );
+        assert_eq!(reported_before, Some(1000));
+        assert_eq!(progress.get(), 1050);
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let mut fields = Vec::new();
+            for_each_field_progress(&s, b'X', b'Y', step, |_| {}, |f| fields.push(f));
+            let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y').collect();
+            assert_eq!(fields, expected, "{:?}", s);
+        }
+
+        let mut progress = Vec::new();
+        for_each_field_progress(b"", b'X', b'Y', step, |n| progress.push(n), |_| {});
+        assert!(progress.is_empty());
+    }
+
+    #[test]
+    fn test_for_each_decoded() {
+        let s: Vec<u8> = (0..100)
+            .flat_map(|i| format!("{}YX{}YYX", i, i).into_bytes())
+            .collect();
+        let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y')
+            .unescaped()
+            .map(Cow::into_owned)
+            .collect();
+
+        let mut scratch = b"garbage".to_vec();
+        let mut decoded = Vec::new();
+        for_each_decoded(&s, b'X', b'Y', &mut scratch, |f| decoded.push(f.to_vec()));
+        assert_eq!(decoded, expected);
+        assert_eq!(decoded[0], b"0X0Y");
+    }
+
+    #[test]
+    fn test_take_last() {
+        let (head, last) = Iter::from_slice(b"aXbYXXcYXX", b'X', b'Y')
+            .take_last()
+            .unwrap();
+        assert_eq!(head, b"aXbYX");
+        assert_eq!(last, &b"cX"[..]);
+
+        let (head, last) = Iter::from_slice(b"XXaYX", b'X', b'Y').take_last().unwrap();
+        assert_eq!(head, b"X");
+        assert_eq!(last, &b"aX"[..]);
+
+        let (head, last) = Iter::from_slice(b"aYb", b'X', b'Y').take_last().unwrap();
+        assert_eq!(head, b"");
+        assert_eq!(last, &b"ab"[..]);
+
+        assert_eq!(Iter::from_slice(b"XX", b'X', b'Y').take_last(), None);
+    }
+
+    #[test]
+    fn test_rfields_decoded() {
+        for s in crate::test::strings(b"aXY", 7) {
+            let mut expected = Iter::from_slice(&s, b'X', b'Y').to_owned_fields();
+            expected.reverse();
+            let v: Vec<_> = rfields_decoded(&s, b'X', b'Y').collect();
+            assert_eq!(v, expected, "{:?}", s);
+        }
+
+        let v: Vec<_> = rfields_decoded(b"aYXbXcXXYYdYX", b'X', b'Y').collect();
+        assert_eq!(v, &[&b"YdX"[..], b"c", b"aXb"]);
+        assert!(matches!(v[1], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_fields_lossy() {
+        let s = "\u{3042}X\u{3044}YX\u{3046}XaYYb".as_bytes();
+        let v: Vec<_> = fields_lossy(s, b'X', b'Y').collect();
+        assert_eq!(v, &["\u{3042}", "\u{3044}X\u{3046}", "aYb"]);
+        assert!(matches!(v[0], Cow::Borrowed(_)));
+        assert!(matches!(v[1], Cow::Owned(_)));
+
+        let s = b"\xe3\x81X\xffYXaX";
+        let v: Vec<_> = fields_lossy(s, b'X', b'Y').collect();
+        assert_eq!(v, &["\u{fffd}", "\u{fffd}Xa"]);
+    }
+
+    #[test]
+    fn test_fields_from_cow() {
+        let s = b"XaYXbXXcYYXdX";
+        let expected = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+
+        let v = fields_from_cow(Cow::Borrowed(s), b'X', b'Y');
+        assert_eq!(v, expected);
+        assert!(matches!(
+            v[..],
+            [Cow::Owned(_), Cow::Owned(_), Cow::Borrowed(b"d")]
+        ));
+
+        let v = fields_from_cow(Cow::Owned(s.to_vec()), b'X', b'Y');
+        assert_eq!(v, expected);
+        assert!(v.iter().all(|f| matches!(f, Cow::Owned(_))));
+
+        assert!(fields_from_cow(Cow::Owned(vec![]), b'X', b'Y').is_empty());
+    }
+
+    #[test]
+    fn test_collect_str() {
+        let s = "\u{3042}X\u{3044}YX\u{3046}XXaYYb".as_bytes();
+        assert_eq!(
+            collect_str(s, b'X', b'Y').unwrap(),
+            &["\u{3042}", "\u{3044}YX\u{3046}", "aYYb"]
+        );
+        assert_eq!(collect_str(b"XX", b'X', b'Y').unwrap(), Vec::<&str>::new());
+
+        // The second field is cut in the middle of a character.
+        let s = b"aX\xe3\x81YXX\xffXb";
+        let err = collect_str(s, b'X', b'Y').unwrap_err();
+        assert_eq!((err.index, err.offset), (1, 2));
+        assert_eq!(err.error.valid_up_to(), 0);
+        assert_eq!(err.error.error_len(), Some(2));
+        assert_eq!(
+            err.to_string(),
+            format!("field 1 at offset 2: {}", err.error)
+        );
+        assert!(err.source().is_some());
+
+        let err: Utf8Error = err.into();
+        assert_eq!((err.valid_up_to(), err.error_len()), (0, Some(2)));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{join, Iter};
+
+/// Same as [`iter`](crate::iter), but the delimiter and the escape character are const generics.
+///
+/// Fixing them at compile time allows the compiler to specialize the scan for them.
+///
+/// ```
+/// use escaped_delimiter::iter_const;
+///
+/// let s = b"a\\ b c";
+/// let s_vec: Vec<_> = iter_const::<b' ', b'\\'>(s).collect();
+/// assert_eq!(s_vec, &[&b"a\\ b"[..], &b"c"[..]]);
+/// ```
+#[inline]
+pub fn iter_const<const DELIM: u8, const ESCAPE: u8>(slice: &[u8]) -> ConstIter<'_, DELIM, ESCAPE> {
+    ConstIter::from_slice(slice)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstIter<'a, const DELIM: u8, const ESCAPE: u8> {
+    inner: &'a [u8],
+}
+
+impl<'a, const DELIM: u8, const ESCAPE: u8> Iterator for ConstIter<'a, DELIM, ESCAPE> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut it = self.as_iter();
+        let word = it.next();
+        self.inner = it.as_slice();
+        word
+    }
+}
+
+impl<'a, const DELIM: u8, const ESCAPE: u8> DoubleEndedIterator for ConstIter<'a, DELIM, ESCAPE> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let mut it = self.as_iter();
+        let word = it.next_back();
+        self.inner = it.as_slice();
+        word
+    }
+}
+
+impl<'a, const DELIM: u8, const ESCAPE: u8> ConstIter<'a, DELIM, ESCAPE> {
+    #[inline]
+    fn as_iter(&self) -> Iter<'a> {
+        Iter::from_slice(self.inner, DELIM, ESCAPE)
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// See [`iter_const`].
+    #[inline]
+    pub fn from_slice(inner: &'a [u8]) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, const DELIM: u8, const ESCAPE: u8> From<ConstIter<'a, DELIM, ESCAPE>> for Iter<'a> {
+    #[inline]
+    fn from(it: ConstIter<'a, DELIM, ESCAPE>) -> Self {
+        it.as_iter()
+    }
+}
+
+/// A format with a fixed delimiter and escape character.
+///
+/// It is zero-sized, so that a format can be defined once as a type alias and reused.
+///
+/// ```
+/// use escaped_delimiter::Delimited;
+///
+/// type Csv = Delimited<b',', b'\\'>;
+///
+/// let fields = Csv::default().decode(b"a\\,b,c");
+/// assert_eq!(fields, &[&b"a,b"[..], &b"c"[..]]);
+/// assert_eq!(Csv::default().encode(&[b"a,b", b"c"]), b"a\\,b,c");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Delimited<const DELIM: u8, const ESCAPE: u8>;
+
+impl<const DELIM: u8, const ESCAPE: u8> Delimited<DELIM, ESCAPE> {
+    /// The delimiter.
+    pub const DELIM: u8 = DELIM;
+    /// The escape character.
+    pub const ESCAPE: u8 = ESCAPE;
+
+    /// Same as [`iter`](crate::iter) with the delimiter and the escape character of the format.
+    #[inline]
+    pub fn iter<'a>(&self, slice: &'a [u8]) -> Iter<'a> {
+        Iter::from_slice(slice, DELIM, ESCAPE)
+    }
+
+    /// Returns the unescaped fields of `slice`.
+    #[inline]
+    pub fn decode(&self, slice: &[u8]) -> Vec<Vec<u8>> {
+        self.iter(slice).to_owned_fields()
+    }
+
+    /// Same as [`join`] with the delimiter and the escape character of the format.
+    #[inline]
+    pub fn encode(&self, fields: &[&[u8]]) -> Vec<u8> {
+        join(fields, DELIM, ESCAPE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_const_iter() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"abc",
+            b"abcXdefXXhX jklm",
+            b"abXYXcdeXYfYXXYYYXgYYX",
+            b"XXabXYXcdeXYfYXXYYYXgYYX",
+            b"YYXa",
+        ];
+
+        for s in inputs {
+            let words: Vec<_> = iter_const::<b'X', b'Y'>(s).collect();
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            assert_eq!(words, expected);
+
+            let words: Vec<_> = iter_const::<b'X', b'Y'>(s).rev().collect();
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').rev().collect();
+            assert_eq!(words, expected);
+        }
+
+        let mut words = iter_const::<b'X', b'Y'>(b"abcXdefXXhX jklm");
+        assert_eq!(words.next(), Some(&b"abc"[..]));
+        assert_eq!(words.next_back(), Some(&b" jklm"[..]));
+        assert_eq!(words.as_slice(), &b"XdefXXhX"[..]);
+    }
+
+    #[test]
+    fn test_delimited() {
+        type Spaces = Delimited<b' ', b'\\'>;
+        type Pipes = Delimited<b'|', b'^'>;
+
+        let s = b"a\\ b|c^|d e";
+        assert_eq!(Spaces::default().decode(s), &[&b"a b|c^|d"[..], &b"e"[..]]);
+        assert_eq!(Pipes::default().decode(s), &[&b"a\\ b"[..], &b"c|d e"[..]]);
+
+        let fields: &[&[u8]] = &[b"a b", b"c|d"];
+        assert_eq!(Spaces::default().encode(fields), b"a\\ b c|d");
+        assert_eq!(Pipes::default().encode(fields), b"a b|c^|d");
+        assert_eq!(Pipes::DELIM, b'|');
+        assert_eq!(Pipes::ESCAPE, b'^');
+    }
+}
@@ -0,0 +1,313 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/// Returns an iterator of the records of a CSV slice as in RFC 4180, each a `Vec` of its fields.
+///
+/// Unlike the other iterators of this crate, there is no escape character. A field may be
+/// enclosed in double quotes, inside of which the delimiter and line breaks are literal and a
+/// doubled quote is a literal quote; the quotes are removed from the yielded fields. Records end
+/// with CRLF, or a bare LF as commonly accepted, and the line break after the last record is
+/// optional. Empty fields are kept, so an empty line is a record of one empty field.
+///
+/// A field borrows the slice unless it has a doubled quote. The iteration stops at the first
+/// error, which is an unclosed quote, a quote in an unquoted field, or anything but a delimiter
+/// or a line break after a closing quote.
+///
+/// # Panics
+///
+/// Panics if the delimiter is a double quote, CR or LF.
+///
+/// ```
+/// use escaped_delimiter::csv_records;
+///
+/// let s = b"\"aaa\",\"b\r\nbb\",\"c\"\"cc\"\r\nzzz,,xxx\r\n";
+/// let records: Vec<_> = csv_records(s, b',').collect::<Result<_, _>>().unwrap();
+/// assert_eq!(records, &[
+///     &[&b"aaa"[..], &b"b\r\nbb"[..], &b"c\"cc"[..]][..],
+///     &[&b"zzz"[..], &b""[..], &b"xxx"[..]][..],
+/// ]);
+/// ```
+pub fn csv_records(slice: &[u8], delim: u8) -> CsvRecords<'_> {
+    assert!(
+        delim != b'"' && delim != b'\r' && delim != b'\n',
+        "the delimiter is a quote or a line break"
+    );
+
+    CsvRecords {
+        delim,
+        offset: 0,
+        failed: false,
+        inner: slice,
+    }
+}
+
+/// An error yielded by [`CsvRecords`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CsvError {
+    /// The offset from the start of the slice.
+    pub offset: usize,
+    /// What is wrong with the field.
+    pub kind: CsvErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsvErrorKind {
+    /// The quote at the offset is never closed.
+    UnclosedQuote,
+    /// The quote at the offset is in a field which does not start with a quote.
+    QuoteInUnquotedField,
+    /// The byte at the offset follows a closing quote, but is neither the delimiter nor a line
+    /// break.
+    AfterClosingQuote,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            CsvErrorKind::UnclosedQuote => write!(f, "unclosed quote at offset {}", self.offset),
+            CsvErrorKind::QuoteInUnquotedField => {
+                write!(f, "quote in an unquoted field at offset {}", self.offset)
+            }
+            CsvErrorKind::AfterClosingQuote => {
+                write!(
+                    f,
+                    "unexpected byte after a closing quote at offset {}",
+                    self.offset
+                )
+            }
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+/// What ends a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum End {
+    Delim,
+    Record,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvRecords<'a> {
+    delim: u8,
+    offset: usize,
+    failed: bool,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for CsvRecords<'a> {
+    type Item = Result<Vec<Cow<'a, [u8]>>, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.inner.is_empty() {
+            return None;
+        }
+
+        let mut record = Vec::new();
+        loop {
+            match self.field() {
+                Ok((field, end)) => {
+                    record.push(field);
+                    if end == End::Record {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl<'a> CsvRecords<'a> {
+    /// Returns the rest of the inner slice, from the start of the next record.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// Consumes the field at the start of the rest of the slice and what ends it.
+    fn field(&mut self) -> Result<(Cow<'a, [u8]>, End), CsvError> {
+        let (s, offset) = (self.inner, self.offset);
+        let error = |i, kind| CsvError {
+            offset: offset + i,
+            kind,
+        };
+
+        if s.first() != Some(&b'"') {
+            let mut i = 0;
+            while i < s.len() && !self.is_end(i) {
+                if s[i] == b'"' {
+                    return Err(error(i, CsvErrorKind::QuoteInUnquotedField));
+                }
+                i += 1;
+            }
+            let end = self.consume_end(i).expect("the field ends at i");
+            return Ok((Cow::Borrowed(&s[..i]), end));
+        }
+
+        let mut owned: Option<Vec<u8>> = None;
+        let mut start = 1;
+        loop {
+            let quote = match s[start..].iter().position(|&c| c == b'"') {
+                Some(j) => start + j,
+                None => return Err(error(0, CsvErrorKind::UnclosedQuote)),
+            };
+
+            if s.get(quote + 1) == Some(&b'"') {
+                // Keeps one of the doubled quotes.
+                owned
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(&s[start..=quote]);
+                start = quote + 2;
+                continue;
+            }
+
+            let field = match owned {
+                Some(mut owned) => {
+                    owned.extend_from_slice(&s[start..quote]);
+                    Cow::Owned(owned)
+                }
+                None => Cow::Borrowed(&s[1..quote]),
+            };
+            let end = self
+                .consume_end(quote + 1)
+                .ok_or_else(|| error(quote + 1, CsvErrorKind::AfterClosingQuote))?;
+            return Ok((field, end));
+        }
+    }
+
+    /// Returns whether the field ends at `i`, with a delimiter or a line break.
+    #[inline]
+    fn is_end(&self, i: usize) -> bool {
+        let s = self.inner;
+        s[i] == self.delim || s[i] == b'\n' || s[i] == b'\r' && s.get(i + 1) == Some(&b'\n')
+    }
+
+    /// Consumes the field ending at `i` together with its delimiter or line break, if any.
+    fn consume_end(&mut self, i: usize) -> Option<End> {
+        let (len, end) = if i == self.inner.len() {
+            (0, End::Record)
+        } else if !self.is_end(i) {
+            return None;
+        } else if self.inner[i] == self.delim {
+            (1, End::Delim)
+        } else if self.inner[i] == b'\r' {
+            (2, End::Record)
+        } else {
+            (1, End::Record)
+        };
+
+        self.inner = &self.inner[i + len..];
+        self.offset += i + len;
+        Some(end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn records(s: &[u8]) -> Result<Vec<Vec<Cow<'_, [u8]>>>, CsvError> {
+        csv_records(s, b',').collect()
+    }
+
+    #[test]
+    fn test_csv_records() {
+        // The examples of section 2 of RFC 4180.
+        let expected: &[&[&[u8]]] = &[&[b"aaa", b"bbb", b"ccc"], &[b"zzz", b"yyy", b"xxx"]];
+        assert_eq!(
+            records(b"aaa,bbb,ccc\r\nzzz,yyy,xxx\r\n").unwrap(),
+            expected
+        );
+        assert_eq!(records(b"aaa,bbb,ccc\r\nzzz,yyy,xxx").unwrap(), expected);
+        assert_eq!(
+            records(b"\"aaa\",\"bbb\",\"ccc\"\r\nzzz,yyy,xxx").unwrap(),
+            expected
+        );
+        let with_header = b"field_name,field_name,field_name\r\naaa,bbb,ccc\r\nzzz,yyy,xxx\r\n";
+        assert_eq!(records(with_header).unwrap()[1..], *expected);
+        assert_eq!(
+            records(b"\"aaa\",\"b\r\nbb\",\"ccc\"\r\nzzz,yyy,xxx").unwrap(),
+            &[
+                &[&b"aaa"[..], b"b\r\nbb", b"ccc"][..],
+                &[b"zzz", b"yyy", b"xxx"]
+            ]
+        );
+        assert_eq!(
+            records(b"\"aaa\",\"b\"\"bb\",\"ccc\"").unwrap(),
+            &[&[&b"aaa"[..], b"b\"bb", b"ccc"]]
+        );
+
+        // Empty fields and lines are kept, but not a line after the last line break.
+        assert_eq!(
+            records(b",a,\n\n\"\",\"\"\"\"\r\n").unwrap(),
+            &[&[&b""[..], b"a", b""][..], &[b""], &[b"", b"\""]]
+        );
+        assert!(records(b"").unwrap().is_empty());
+
+        // A bare CR is content, and so is the delimiter inside the quotes.
+        assert_eq!(
+            records(b"a\rb,\"c,d\"\r").unwrap_err(),
+            CsvError {
+                offset: 9,
+                kind: CsvErrorKind::AfterClosingQuote
+            }
+        );
+        assert_eq!(
+            records(b"a\rb,\"c,d\"").unwrap(),
+            &[&[&b"a\rb"[..], b"c,d"]]
+        );
+
+        let v = records(b"\"a\",\"b\"\"\"").unwrap();
+        assert!(matches!(v[0][..], [Cow::Borrowed(b"a"), Cow::Owned(_)]));
+
+        let v: Vec<_> = csv_records(b"a\tb\t\"c\td\"", b'\t').collect();
+        assert_eq!(
+            v,
+            &[Ok(vec![
+                Cow::from(&b"a"[..]),
+                b"b"[..].into(),
+                b"c\td"[..].into()
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let err = |offset, kind| CsvError { offset, kind };
+        let cases = [
+            (&b"a,\"b"[..], err(2, CsvErrorKind::UnclosedQuote)),
+            (b"a\r\n\"b\"\"\r\n", err(3, CsvErrorKind::UnclosedQuote)),
+            (b"a,b\"c", err(3, CsvErrorKind::QuoteInUnquotedField)),
+            (b"a,b\"", err(3, CsvErrorKind::QuoteInUnquotedField)),
+            (b"\"a\"b,c", err(3, CsvErrorKind::AfterClosingQuote)),
+            (b"\"a\" ,c", err(3, CsvErrorKind::AfterClosingQuote)),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(records(s), Err(expected), "{:?}", s);
+        }
+
+        // The records before the error are still yielded, and nothing after it.
+        let mut it = csv_records(b"a,b\nc\"\nd", b',');
+        assert!(matches!(it.next(), Some(Ok(_))));
+        assert_eq!(
+            it.next(),
+            Some(Err(err(5, CsvErrorKind::QuoteInUnquotedField)))
+        );
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_csv_as_slice() {
+        let mut it = csv_records(b"a,\"b\nc\"\r\nd\n", b',');
+        it.next();
+        assert_eq!(it.as_slice(), b"d\n");
+        it.next();
+        assert_eq!(it.as_slice(), b"");
+    }
+}
@@ -0,0 +1,123 @@
+use crate::Iter;
+
+impl<'a> Iter<'a> {
+    /// Returns a cursor at the first field, which moves field by field in either direction.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut cursor = iter(b"a b\\ c d", b' ', b'\\').cursor();
+    /// assert_eq!(cursor.current(), Some(&b"a"[..]));
+    /// assert_eq!(cursor.advance(), Some(&b"b\\ c"[..]));
+    /// assert_eq!(cursor.current(), Some(&b"b\\ c"[..]));
+    /// assert_eq!(cursor.retreat(), Some(&b"a"[..]));
+    /// assert_eq!(cursor.retreat(), None);
+    /// assert_eq!(cursor.current(), Some(&b"a"[..]));
+    /// ```
+    pub fn cursor(self) -> Cursor<'a> {
+        let mut cursor = Cursor {
+            base: self,
+            start: 0,
+            end: 0,
+            current: None,
+        };
+        cursor.advance();
+        cursor
+    }
+}
+
+/// A position at a field of a slice, returned by [`Iter::cursor`].
+///
+/// Unlike [`Iterator::next`], querying the current field does not move the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor<'a> {
+    base: Iter<'a>,
+    start: usize,
+    end: usize,
+    current: Option<&'a [u8]>,
+}
+
+impl<'a> Cursor<'a> {
+    /// Returns the field at the cursor, which is `None` only if the slice has no fields.
+    #[inline]
+    pub fn current(&self) -> Option<&'a [u8]> {
+        self.current
+    }
+
+    /// Moves the cursor to the next field and returns it.
+    ///
+    /// If the cursor is at the last field, it stays there and `None` is returned.
+    pub fn advance(&mut self) -> Option<&'a [u8]> {
+        let mut after = Iter {
+            inner: &self.base.inner[self.end..],
+            ..self.base
+        };
+        let field = after.next()?;
+
+        self.end = self.base.len() - after.len();
+        self.start = self.end - field.len();
+        self.current = Some(field);
+        Some(field)
+    }
+
+    /// Moves the cursor to the previous field and returns it.
+    ///
+    /// If the cursor is at the first field, it stays there and `None` is returned.
+    pub fn retreat(&mut self) -> Option<&'a [u8]> {
+        let mut before = Iter {
+            inner: &self.base.inner[..self.start],
+            ..self.base
+        };
+        let field = before.next_back()?;
+
+        self.start = before.len();
+        self.end = self.start + field.len();
+        self.current = Some(field);
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let mut cursor = Iter::from_slice(b"XXaXbYXXXcYYXdX", b'X', b'Y').cursor();
+        assert_eq!(cursor.current(), Some(&b"a"[..]));
+        assert_eq!(cursor.retreat(), None);
+        assert_eq!(cursor.current(), Some(&b"a"[..]));
+
+        assert_eq!(cursor.advance(), Some(&b"bYX"[..]));
+        assert_eq!(cursor.advance(), Some(&b"cYY"[..]));
+        assert_eq!(cursor.retreat(), Some(&b"bYX"[..]));
+        assert_eq!(cursor.current(), Some(&b"bYX"[..]));
+        assert_eq!(cursor.advance(), Some(&b"cYY"[..]));
+        assert_eq!(cursor.advance(), Some(&b"d"[..]));
+        assert_eq!(cursor.advance(), None);
+        assert_eq!(cursor.current(), Some(&b"d"[..]));
+
+        let mut back = Vec::new();
+        while let Some(field) = cursor.retreat() {
+            back.push(field);
+        }
+        assert_eq!(back, &[&b"cYY"[..], b"bYX", b"a"]);
+        assert_eq!(cursor.current(), Some(&b"a"[..]));
+    }
+
+    #[test]
+    fn test_cursor_empty() {
+        for s in [&b""[..], b"X", b"XXX"] {
+            let mut cursor = Iter::from_slice(s, b'X', b'Y').cursor();
+            assert_eq!(cursor.current(), None);
+            assert_eq!(cursor.advance(), None);
+            assert_eq!(cursor.retreat(), None);
+            assert_eq!(cursor.current(), None);
+        }
+
+        let mut cursor = Iter::from_slice(b"YX", b'X', b'Y').cursor();
+        assert_eq!(cursor.current(), Some(&b"YX"[..]));
+        assert_eq!(cursor.advance(), None);
+        assert_eq!(cursor.retreat(), None);
+    }
+}
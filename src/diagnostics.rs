@@ -0,0 +1,235 @@
+use crate::Iter;
+
+/// A suspicious escape found by [`Iter::lenient_with_warnings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// The offset of the escape character from the start of the slice.
+    pub offset: usize,
+    /// What is suspicious about the escape character.
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// The slice ends with an escape character which escapes nothing.
+    TrailingEscape,
+    /// An escape character is followed by a byte which does not need escaping.
+    RedundantEscape,
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the same fields, which also pushes a [`Diagnostic`] to `warnings`
+    /// for each suspicious escape in the yielded fields.
+    ///
+    /// Offsets are relative to the rest of the slice at the time of this call.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, Diagnostic, DiagnosticKind};
+    ///
+    /// let mut warnings = Vec::new();
+    /// let s_vec: Vec<_> = iter(b"a\\b c\\", b' ', b'\\')
+    ///     .lenient_with_warnings(&mut warnings)
+    ///     .collect();
+    /// assert_eq!(s_vec, &[&b"a\\b"[..], &b"c\\"[..]]);
+    /// assert_eq!(warnings, &[
+    ///     Diagnostic { offset: 1, kind: DiagnosticKind::RedundantEscape },
+    ///     Diagnostic { offset: 5, kind: DiagnosticKind::TrailingEscape },
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn lenient_with_warnings<'w>(
+        self,
+        warnings: &'w mut Vec<Diagnostic>,
+    ) -> WithWarnings<'a, 'w> {
+        WithWarnings {
+            base: (self.inner.as_ptr() as usize).wrapping_sub(self.base_offset()),
+            inner: self,
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WithWarnings<'a, 'w> {
+    base: usize,
+    inner: Iter<'a>,
+    warnings: &'w mut Vec<Diagnostic>,
+}
+
+impl<'a> Iterator for WithWarnings<'a, '_> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        self.check(field);
+        Some(field)
+    }
+}
+
+impl<'a> DoubleEndedIterator for WithWarnings<'a, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        self.check(field);
+        Some(field)
+    }
+}
+
+impl<'a> WithWarnings<'a, '_> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    fn check(&mut self, field: &[u8]) {
+        let start = (field.as_ptr() as usize).wrapping_sub(self.base);
+
+        let mut escape_at = None;
+        for (i, c) in field.iter().copied().enumerate() {
+            if let Some(j) = escape_at.take() {
+                // Same as the bare escapes of `TryIter::reject_bare_escapes`.
+                if !self.inner.needs_escape(c) {
+                    self.warn(start + j, DiagnosticKind::RedundantEscape);
+                }
+            } else if c == self.inner.escape {
+                escape_at = Some(i);
+            }
+        }
+
+        if let Some(j) = escape_at {
+            self.warn(start + j, DiagnosticKind::TrailingEscape);
+        }
+    }
+
+    #[inline]
+    fn warn(&mut self, offset: usize, kind: DiagnosticKind) {
+        self.warnings.push(Diagnostic { offset, kind });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lenient_with_warnings() {
+        let s = b"aYbXYXcXYYdYeXXfY";
+        let mut warnings = Vec::new();
+        let mut words = Iter::from_slice(s, b'X', b'Y').lenient_with_warnings(&mut warnings);
+        assert_eq!(words.next(), Some(&b"aYb"[..]));
+        assert_eq!(words.next(), Some(&b"YXc"[..]));
+        assert_eq!(words.next(), Some(&b"YYdYe"[..]));
+        assert_eq!(words.next(), Some(&b"fY"[..]));
+        assert_eq!(words.next(), None);
+        assert_eq!(
+            warnings,
+            &[
+                Diagnostic {
+                    offset: 1,
+                    kind: DiagnosticKind::RedundantEscape
+                },
+                Diagnostic {
+                    offset: 11,
+                    kind: DiagnosticKind::RedundantEscape
+                },
+                Diagnostic {
+                    offset: 16,
+                    kind: DiagnosticKind::TrailingEscape
+                },
+            ]
+        );
+
+        let mut warnings = Vec::new();
+        let words: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .lenient_with_warnings(&mut warnings)
+            .rev()
+            .collect();
+        assert_eq!(words.len(), 4);
+        assert_eq!(
+            warnings,
+            &[
+                Diagnostic {
+                    offset: 16,
+                    kind: DiagnosticKind::TrailingEscape
+                },
+                Diagnostic {
+                    offset: 11,
+                    kind: DiagnosticKind::RedundantEscape
+                },
+                Diagnostic {
+                    offset: 1,
+                    kind: DiagnosticKind::RedundantEscape
+                },
+            ]
+        );
+
+        let mut warnings = Vec::new();
+        let words = Iter::with_specials(s, b'X', b'Y', b"e").lenient_with_warnings(&mut warnings);
+        assert_eq!(words.count(), 4);
+        assert_eq!(
+            warnings,
+            &[
+                Diagnostic {
+                    offset: 1,
+                    kind: DiagnosticKind::RedundantEscape
+                },
+                Diagnostic {
+                    offset: 16,
+                    kind: DiagnosticKind::TrailingEscape
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_with_warnings_bare_escapes() {
+        use crate::{ParseErrorKind, Problem};
+
+        // The warnings are the problems of `TryIter::reject_bare_escapes`.
+        for s in crate::test::strings(b"abXY", 6) {
+            for specials in [None, Some(&b"a"[..])] {
+                let words = match specials {
+                    Some(specials) => Iter::with_specials(&s, b'X', b'Y', specials),
+                    None => Iter::from_slice(&s, b'X', b'Y'),
+                };
+
+                let mut warnings = Vec::new();
+                words.lenient_with_warnings(&mut warnings).count();
+
+                let mut it = words.strict().reject_bare_escapes(true).lenient();
+                it.by_ref().count();
+                let problems: Vec<_> = it
+                    .problems()
+                    .iter()
+                    .map(|&Problem { offset, kind, .. }| {
+                        let kind = match kind {
+                            ParseErrorKind::BareEscape => DiagnosticKind::RedundantEscape,
+                            ParseErrorKind::TrailingEscape => DiagnosticKind::TrailingEscape,
+                            kind => unreachable!("{:?}", kind),
+                        };
+                        Diagnostic { offset, kind }
+                    })
+                    .collect();
+                assert_eq!(warnings, problems, "{:?} {:?}", s, specials);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lenient_with_warnings_offsets() {
+        let s = b"aXbYcXd";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        words.next();
+
+        let mut warnings = Vec::new();
+        let words = words.lenient_with_warnings(&mut warnings);
+        assert_eq!(words.count(), 2);
+        assert_eq!(
+            warnings,
+            &[Diagnostic {
+                offset: 2,
+                kind: DiagnosticKind::RedundantEscape
+            }]
+        );
+    }
+}
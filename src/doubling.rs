@@ -0,0 +1,468 @@
+use std::borrow::Cow;
+
+use crate::scanner::is_unescaped;
+
+/// Returns an iterator of fields in which a doubled delimiter is a literal delimiter.
+///
+/// There is no escape character; instead, a run of delimiters is read as pairs of literal
+/// delimiters, and if the run is of odd length, its last delimiter separates the fields. Empty
+/// fields are skipped as in [`iter`](crate::iter).
+///
+/// Since a field boundary is always placed after the pairs, a field cannot start with a literal
+/// delimiter unless it is the first field.
+///
+/// ```
+/// use escaped_delimiter::{iter_doubling, unescape_doubling};
+///
+/// let s = b"a||b|c|||d";
+/// let s_vec: Vec<_> = iter_doubling(s, b'|').collect();
+/// assert_eq!(s_vec, &[&b"a||b"[..], &b"c||"[..], &b"d"[..]]);
+/// assert_eq!(unescape_doubling(s_vec[0], b'|'), &b"a|b"[..]);
+/// ```
+#[inline]
+pub fn iter_doubling(slice: &[u8], delim: u8) -> DoublingIter<'_> {
+    DoublingIter::from_slice(slice, delim)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoublingIter<'a> {
+    delim: u8,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for DoublingIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.find_boundary() {
+                Some(i) => {
+                    let field = &self.inner[..i];
+                    self.inner = &self.inner[i + 1..];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoubleEndedIterator for DoublingIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.rfind_boundary() {
+                Some(i) => {
+                    let field = &self.inner[i + 1..];
+                    self.inner = &self.inner[..i];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoublingIter<'a> {
+    /// Returns the position of the first boundary, i.e. the last delimiter of the first run of
+    /// odd length.
+    fn find_boundary(&self) -> Option<usize> {
+        let mut run = 0;
+        for (i, &c) in self.inner.iter().enumerate() {
+            if c == self.delim {
+                run += 1;
+            } else if run & 1 == 1 {
+                return Some(i - 1);
+            } else {
+                run = 0;
+            }
+        }
+
+        if run & 1 == 1 {
+            Some(self.inner.len() - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position of the last boundary.
+    fn rfind_boundary(&self) -> Option<usize> {
+        let mut run = 0;
+        let mut run_end = 0;
+        for (i, &c) in self.inner.iter().enumerate().rev() {
+            if c == self.delim {
+                if run == 0 {
+                    run_end = i;
+                }
+                run += 1;
+            } else if run & 1 == 1 {
+                return Some(run_end);
+            } else {
+                run = 0;
+            }
+        }
+
+        if run & 1 == 1 {
+            Some(run_end)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// See [`iter_doubling`].
+    #[inline]
+    pub fn from_slice(inner: &'a [u8], delim: u8) -> Self {
+        Self { delim, inner }
+    }
+}
+
+/// Collapses each pair of delimiters in a field yielded by [`iter_doubling`].
+///
+/// ```
+/// use escaped_delimiter::unescape_doubling;
+///
+/// assert_eq!(unescape_doubling(b"a||||b", b'|'), &b"a||b"[..]);
+/// ```
+pub fn unescape_doubling(field: &[u8], delim: u8) -> Cow<'_, [u8]> {
+    let first = match field.iter().position(|&c| c == delim) {
+        Some(i) => i,
+        None => return Cow::Borrowed(field),
+    };
+
+    let mut out = Vec::with_capacity(field.len() - 1);
+    out.extend_from_slice(&field[..first]);
+
+    let mut skip = false;
+    for &c in &field[first..] {
+        if c == delim {
+            if !skip {
+                out.push(c);
+            }
+            skip = !skip;
+        } else {
+            out.push(c);
+            skip = false;
+        }
+    }
+
+    Cow::Owned(out)
+}
+
+/// Returns an iterator of fields in which a delimiter is made literal either by doubling it or
+/// by an escape character before it.
+///
+/// The slice is read from the front: an escape character makes the following byte literal, else
+/// a pair of delimiters is a literal delimiter, and else a delimiter separates the fields. So in
+/// `\,,` the escape character takes the first delimiter, and the second one is left alone. Empty
+/// fields are skipped as in [`iter`](crate::iter).
+///
+/// ```
+/// use escaped_delimiter::{iter_doubling_or_escape, unescape_doubling_or_escape};
+///
+/// let s = b"a,,b\\,c,d\\,,e";
+/// let s_vec: Vec<_> = iter_doubling_or_escape(s, b',', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a,,b\\,c"[..], &b"d\\,"[..], &b"e"[..]]);
+/// assert_eq!(unescape_doubling_or_escape(s_vec[0], b',', b'\\'), &b"a,b,c"[..]);
+/// ```
+#[inline]
+pub fn iter_doubling_or_escape(slice: &[u8], delim: u8, escape: u8) -> DoublingOrEscapeIter<'_> {
+    DoublingOrEscapeIter::from_slice(slice, delim, escape)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoublingOrEscapeIter<'a> {
+    delim: u8,
+    escape: u8,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for DoublingOrEscapeIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.find_boundary() {
+                Some(i) => {
+                    let field = &self.inner[..i];
+                    self.inner = &self.inner[i + 1..];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoubleEndedIterator for DoublingOrEscapeIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.rfind_boundary() {
+                Some(i) => {
+                    let field = &self.inner[i + 1..];
+                    self.inner = &self.inner[..i];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoublingOrEscapeIter<'a> {
+    /// Returns the position of the first boundary.
+    fn find_boundary(&self) -> Option<usize> {
+        let mut i = 0;
+        while let Some(start) = self.inner[i..].iter().position(|&c| c == self.delim) {
+            let start = i + start;
+            let len = self.inner[start..]
+                .iter()
+                .take_while(|&&c| c == self.delim)
+                .count();
+            if self.is_boundary_run(start, len) {
+                return Some(start + len - 1);
+            }
+            i = start + len;
+        }
+
+        None
+    }
+
+    /// Returns the position of the last boundary.
+    fn rfind_boundary(&self) -> Option<usize> {
+        let mut end = self.inner.len();
+        while let Some(last) = self.inner[..end].iter().rposition(|&c| c == self.delim) {
+            let len = self.inner[..=last]
+                .iter()
+                .rev()
+                .take_while(|&&c| c == self.delim)
+                .count();
+            let start = last + 1 - len;
+            if self.is_boundary_run(start, len) {
+                return Some(last);
+            }
+            end = start;
+        }
+
+        None
+    }
+
+    /// Returns whether the run of `len` delimiters at `start` ends with a boundary, which is the
+    /// case iff an odd number of them are left after the escape character, if any, takes the
+    /// first one.
+    ///
+    /// A run which [`next_back`](DoubleEndedIterator::next_back) has cut after its boundary is
+    /// one delimiter shorter, so it no longer ends with a boundary.
+    #[inline]
+    fn is_boundary_run(&self, start: usize, len: usize) -> bool {
+        let escaped = !is_unescaped(self.inner, start, self.escape);
+        (len - escaped as usize) & 1 == 1
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// See [`iter_doubling_or_escape`].
+    #[inline]
+    pub fn from_slice(inner: &'a [u8], delim: u8, escape: u8) -> Self {
+        Self {
+            delim,
+            escape,
+            inner,
+        }
+    }
+}
+
+/// Removes the escape characters and collapses each pair of delimiters in a field yielded by
+/// [`iter_doubling_or_escape`].
+///
+/// A lone escape character at the end is kept as is, as in [`unescape`](crate::unescape).
+///
+/// ```
+/// use escaped_delimiter::unescape_doubling_or_escape;
+///
+/// assert_eq!(unescape_doubling_or_escape(b"a,,b\\,,,\\\\", b',', b'\\'), &b"a,b,,\\"[..]);
+/// ```
+pub fn unescape_doubling_or_escape(field: &[u8], delim: u8, escape: u8) -> Cow<'_, [u8]> {
+    let first = match field.iter().position(|&c| c == delim || c == escape) {
+        Some(i) => i,
+        None => return Cow::Borrowed(field),
+    };
+
+    let mut out = Vec::with_capacity(field.len());
+    out.extend_from_slice(&field[..first]);
+
+    let mut rest = &field[first..];
+    while let Some((&c, tail)) = rest.split_first() {
+        rest = tail;
+        match tail.first() {
+            Some(&next) if c == escape || (c == delim && next == delim) => {
+                out.push(next);
+                rest = &tail[1..];
+            }
+            _ => out.push(c),
+        }
+    }
+
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_doubling() {
+        let s = b"|a||b|c|||d||||e|||||";
+        let mut words = iter_doubling(s, b'|');
+        assert_eq!(words.next(), Some(&b"a||b"[..]));
+        assert_eq!(words.next(), Some(&b"c||"[..]));
+        assert_eq!(words.next(), Some(&b"d||||e||||"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = iter_doubling(s, b'|').rev();
+        assert_eq!(words.next(), Some(&b"d||||e||||"[..]));
+        assert_eq!(words.next(), Some(&b"c||"[..]));
+        assert_eq!(words.next(), Some(&b"a||b"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = iter_doubling(b"||a|", b'|');
+        assert_eq!(words.next(), Some(&b"||a"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = iter_doubling(b"a|b|c", b'|');
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next_back(), Some(&b"c"[..]));
+        assert_eq!(words.next(), Some(&b"b"[..]));
+        assert_eq!(words.next_back(), None);
+    }
+
+    #[test]
+    fn test_doubling_rev_exhaustive() {
+        for len in 0..=10u32 {
+            for mut k in 0..1usize << len {
+                let s: Vec<_> = (0..len)
+                    .map(|_| {
+                        let c = if k & 1 == 0 { b'a' } else { b'|' };
+                        k >>= 1;
+                        c
+                    })
+                    .collect();
+
+                let words: Vec<_> = iter_doubling(&s, b'|').collect();
+                let mut rwords: Vec<_> = iter_doubling(&s, b'|').rev().collect();
+                rwords.reverse();
+                assert_eq!(words, rwords, "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unescape_doubling() {
+        assert!(matches!(
+            unescape_doubling(b"abc", b'|'),
+            Cow::Borrowed(b"abc")
+        ));
+        assert_eq!(unescape_doubling(b"||", b'|'), &b"|"[..]);
+        assert_eq!(unescape_doubling(b"a||b||||", b'|'), &b"a|b||"[..]);
+        assert_eq!(unescape_doubling(b"||a||", b'|'), &b"|a|"[..]);
+    }
+
+    /// Splits the slice by reading it from the front, one token at a time.
+    fn split_doubling_or_escape(s: &[u8]) -> Vec<&[u8]> {
+        let mut fields = Vec::new();
+        let (mut start, mut i) = (0, 0);
+        while i < s.len() {
+            if s[i] == b'Y' || (s[i] == b'X' && s.get(i + 1) == Some(&b'X')) {
+                i += 2;
+            } else if s[i] == b'X' {
+                fields.push(&s[start..i]);
+                start = i + 1;
+                i += 1;
+            } else {
+                i += 1;
+            }
+        }
+        fields.push(&s[start..]);
+        fields.retain(|f| !f.is_empty());
+        fields
+    }
+
+    #[test]
+    fn test_doubling_or_escape() {
+        let s = b"XaXXbYXcXdYXXeYYXXXfYYYXX";
+        let mut words = iter_doubling_or_escape(s, b'X', b'Y');
+        assert_eq!(words.next(), Some(&b"aXXbYXc"[..]));
+        assert_eq!(words.next(), Some(&b"dYX"[..]));
+        assert_eq!(words.next(), Some(&b"eYYXX"[..]));
+        assert_eq!(words.next(), Some(&b"fYYYX"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = iter_doubling_or_escape(s, b'X', b'Y').rev();
+        assert_eq!(words.next(), Some(&b"fYYYX"[..]));
+        assert_eq!(words.next(), Some(&b"eYYXX"[..]));
+        assert_eq!(words.next(), Some(&b"dYX"[..]));
+        assert_eq!(words.next(), Some(&b"aXXbYXc"[..]));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_doubling_or_escape_exhaustive() {
+        for s in crate::test::strings(b"aXY", 9) {
+            let expected = split_doubling_or_escape(&s);
+            let words: Vec<_> = iter_doubling_or_escape(&s, b'X', b'Y').collect();
+            assert_eq!(words, expected, "{:?}", s);
+            let rwords: Vec<_> = iter_doubling_or_escape(&s, b'X', b'Y').rev().collect();
+            assert!(rwords.into_iter().eq(expected.into_iter().rev()), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_unescape_doubling_or_escape() {
+        assert!(matches!(
+            unescape_doubling_or_escape(b"abc", b'X', b'Y'),
+            Cow::Borrowed(b"abc")
+        ));
+        assert_eq!(
+            unescape_doubling_or_escape(b"XXaYXbYY", b'X', b'Y'),
+            &b"XaXbY"[..]
+        );
+        assert_eq!(unescape_doubling_or_escape(b"YXX", b'X', b'Y'), &b"XX"[..]);
+        assert_eq!(
+            unescape_doubling_or_escape(b"XXXYa", b'X', b'Y'),
+            &b"XXa"[..]
+        );
+        assert_eq!(unescape_doubling_or_escape(b"aY", b'X', b'Y'), &b"aY"[..]);
+    }
+}
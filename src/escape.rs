@@ -0,0 +1,1051 @@
+use std::borrow::Cow;
+use std::io::{self, BufRead, Write};
+
+use crate::scanner::{is_unescaped, EscapeTracker};
+use crate::Iter;
+
+/// Removes the escape characters from a field.
+///
+/// Each escape character makes the following byte literal, so a run of escape characters
+/// collapses pairwise. A lone escape character at the end is kept as is.
+///
+/// ```
+/// use escaped_delimiter::unescape;
+///
+/// assert_eq!(unescape(b"abc", b'\\'), &b"abc"[..]);
+/// assert_eq!(unescape(b"a\\ b\\\\", b'\\'), &b"a b\\"[..]);
+/// assert_eq!(unescape(b"a\\", b'\\'), &b"a\\"[..]);
+/// ```
+pub fn unescape(field: &[u8], escape: u8) -> Cow<'_, [u8]> {
+    unescape_with(field, escape, |_| true)
+}
+
+/// Same as [`unescape`], but an escape character followed by a byte which is not special is
+/// kept as is.
+///
+/// The escape character itself must be special.
+pub(crate) fn unescape_with(
+    field: &[u8],
+    escape: u8,
+    is_special: impl Fn(u8) -> bool,
+) -> Cow<'_, [u8]> {
+    let first = match field
+        .windows(2)
+        .position(|w| w[0] == escape && is_special(w[1]))
+    {
+        Some(i) => i,
+        None => return Cow::Borrowed(field),
+    };
+
+    let mut out = Vec::with_capacity(field.len() - 1);
+    out.extend_from_slice(&field[..first]);
+    unescape_into_with(&field[first..], escape, is_special, &mut out);
+
+    Cow::Owned(out)
+}
+
+/// Same as [`unescape`], but appends the unescaped field to `out`.
+#[inline]
+pub(crate) fn unescape_into(field: &[u8], escape: u8, out: &mut Vec<u8>) {
+    unescape_into_with(field, escape, |_| true, out)
+}
+
+/// Same as [`unescape_with`], but appends the unescaped field to `out`.
+pub(crate) fn unescape_into_with(
+    field: &[u8],
+    escape: u8,
+    is_special: impl Fn(u8) -> bool,
+    out: &mut Vec<u8>,
+) {
+    let mut escaped = false;
+    for &c in field {
+        if escaped {
+            if !is_special(c) {
+                out.push(escape);
+            }
+            out.push(c);
+            escaped = false;
+        } else if c == escape {
+            escaped = true;
+        } else {
+            out.push(c);
+        }
+    }
+    if escaped {
+        out.push(escape);
+    }
+}
+
+/// Returns the number of escape characters which [`unescape`] removes from a field.
+#[inline]
+pub(crate) fn count_escapes(field: &[u8], escape: u8) -> usize {
+    count_escapes_with(field, escape, |_| true)
+}
+
+/// Same as `count_escapes`, but counts those which `unescape_with` removes.
+pub(crate) fn count_escapes_with(
+    field: &[u8],
+    escape: u8,
+    is_special: impl Fn(u8) -> bool,
+) -> usize {
+    let mut count = 0;
+    let mut escaped = false;
+    for &c in field {
+        if escaped {
+            if is_special(c) {
+                count += 1;
+            }
+            escaped = false;
+        } else if c == escape {
+            escaped = true;
+        }
+    }
+    count
+}
+
+/// Returns whether the byte at `offset` is an escape character which makes the following byte
+/// literal.
+///
+/// Returns `false` if `offset` is out of bounds.
+///
+/// ```
+/// use escaped_delimiter::is_escaped_at;
+///
+/// let s = b"a\\\\\\ b";
+/// assert!(!is_escaped_at(s, b'\\', 0));
+/// assert!(is_escaped_at(s, b'\\', 1));
+/// assert!(!is_escaped_at(s, b'\\', 2));
+/// assert!(is_escaped_at(s, b'\\', 3));
+/// ```
+pub fn is_escaped_at(slice: &[u8], escape: u8, offset: usize) -> bool {
+    if offset + 1 >= slice.len() || slice[offset] != escape {
+        return false;
+    }
+
+    let run = slice[..offset]
+        .iter()
+        .rev()
+        .take_while(|&&c| c == escape)
+        .count();
+    run & 1 == 0
+}
+
+/// Returns whether the slice ends with an odd number of consecutive escape characters, that is,
+/// whether the byte right after it is escaped.
+///
+/// When a slice is processed in shards, this tells each shard whether its first byte is
+/// escaped. If a shard consists only of escape characters, its parity must be combined with that
+/// of the shard before, with `^`.
+///
+/// ```
+/// use escaped_delimiter::trailing_escape_parity;
+///
+/// assert!(!trailing_escape_parity(b"a\\\\", b'\\'));
+/// assert!(trailing_escape_parity(b"a\\\\\\", b'\\'));
+/// ```
+pub fn trailing_escape_parity(slice: &[u8], escape: u8) -> bool {
+    !is_unescaped(slice, slice.len(), escape)
+}
+
+/// Escapes every delimiter and escape character in a field.
+///
+/// This is the inverse of [`unescape`].
+///
+/// ```
+/// use escaped_delimiter::escape_field;
+///
+/// assert_eq!(escape_field(b"abc", b' ', b'\\'), &b"abc"[..]);
+/// assert_eq!(escape_field(b"a b\\", b' ', b'\\'), &b"a\\ b\\\\"[..]);
+/// ```
+pub fn escape_field(field: &[u8], delim: u8, escape: u8) -> Cow<'_, [u8]> {
+    let is_special = |c: u8| c == delim || c == escape;
+
+    let first = match field.iter().position(|&c| is_special(c)) {
+        Some(i) => i,
+        None => return Cow::Borrowed(field),
+    };
+
+    let mut out = Vec::with_capacity(field.len() + 1);
+    out.extend_from_slice(&field[..first]);
+    for &c in &field[first..] {
+        if is_special(c) {
+            out.push(escape);
+        }
+        out.push(c);
+    }
+
+    Cow::Owned(out)
+}
+
+/// Returns the length of [`escape_field`] of the field, without escaping it.
+///
+/// ```
+/// use escaped_delimiter::escaped_len;
+///
+/// assert_eq!(escaped_len(b"abc", b' ', b'\\'), 3);
+/// assert_eq!(escaped_len(b"a b\\", b' ', b'\\'), 6);
+/// ```
+pub fn escaped_len(field: &[u8], delim: u8, escape: u8) -> usize {
+    field.len() + field.iter().filter(|&&c| c == delim || c == escape).count()
+}
+
+/// Escapes each field and joins them with the delimiter.
+///
+/// Splitting the result with [`iter`](crate::iter) and unescaping each field gives the
+/// original fields back, except for empty ones. Splitting it with
+/// [`Iter::keep_empty`](crate::Iter::keep_empty) instead gives back any fields exactly, empty
+/// ones included, as long as there is at least one: no fields and a single empty field are
+/// both joined into an empty slice, which reads back as a single empty field.
+///
+/// ```
+/// use escaped_delimiter::{iter, join, unescape};
+///
+/// let s = join(&[b"a b", b"c"], b' ', b'\\');
+/// assert_eq!(s, b"a\\ b c");
+///
+/// for fields in [&[&b""[..]][..], &[b"", b""], &[b"", b"a b", b""]] {
+///     let s = join(fields, b' ', b'\\');
+///     let v: Vec<_> = iter(&s, b' ', b'\\').keep_empty().map(|f| unescape(f, b'\\')).collect();
+///     assert_eq!(v, fields);
+/// }
+/// ```
+pub fn join(fields: &[&[u8]], delim: u8, escape: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(delim);
+        }
+        out.extend_from_slice(&escape_field(field, delim, escape));
+    }
+    out
+}
+
+/// Returns whether joining the fields with [`join`] and splitting the result with
+/// [`Iter::keep_empty`](crate::Iter::keep_empty) gives back the same unescaped fields, to check
+/// a choice of `delim` and `escape` against representative data.
+///
+/// Empty fields are kept, but no fields at all never round-trip: they are joined into an empty
+/// slice, which reads back as a single empty field.
+///
+/// ```
+/// use escaped_delimiter::round_trips;
+///
+/// let fields: &[&[u8]] = &[b"a b", b"", b"c\\"];
+/// assert!(round_trips(fields, b' ', b'\\'));
+/// assert!(!round_trips(fields, b' ', b' '));
+/// ```
+pub fn round_trips(fields: &[&[u8]], delim: u8, escape: u8) -> bool {
+    let joined = join(fields, delim, escape);
+    let split = Iter::from_slice(&joined, delim, escape).keep_empty();
+    split
+        .map(|field| unescape(field, escape))
+        .eq(fields.iter().copied())
+}
+
+/// Same as [`join`], but joins the escaped fields with `separator` instead of the delimiter.
+///
+/// The fields are still escaped against `delim` and `escape`, so the result splits back with
+/// [`iter`](crate::iter) only if `separator` is `[delim]`. Otherwise, if `separator` ends with
+/// `delim` and contains neither another delimiter nor the escape character, every occurrence of
+/// it in the result is one of the separators, and [`iter_seq`](crate::iter_seq) with `separator`
+/// as the delimiter splits the result back. Any other separator may run into the escaped fields.
+///
+/// ```
+/// use escaped_delimiter::{iter_seq, join_with, unescape};
+///
+/// let s = join_with(&[b"a b,", b"c"], b' ', b'\\', b", ");
+/// assert_eq!(s, b"a\\ b,, c");
+///
+/// let v: Vec<_> = iter_seq(&s, b", ", b'\\').map(|f| unescape(f, b'\\')).collect();
+/// assert_eq!(v, &[&b"a b,"[..], &b"c"[..]]);
+/// ```
+pub fn join_with(fields: &[&[u8]], delim: u8, escape: u8, separator: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.extend_from_slice(separator);
+        }
+        out.extend_from_slice(&escape_field(field, delim, escape));
+    }
+    out
+}
+
+/// Same as [`join`], but inserts an escape character only where it is needed, for output edited
+/// by hand.
+///
+/// Every delimiter is still escaped. An escape character is left alone if it is the only one
+/// in a row and is followed by a byte of the same field other than the delimiter; any other is
+/// escaped. The result then splits back into the fields with
+/// [`Iter::with_specials`](crate::Iter::with_specials) and no specials, where an escape
+/// character before any other byte is content, but not with [`iter`](crate::iter), which
+/// removes every escape character.
+///
+/// ```
+/// use escaped_delimiter::{join_minimal, Iter};
+///
+/// let s = join_minimal(&[b"C:\\dir a", b"b\\"], b' ', b'\\');
+/// assert_eq!(s, b"C:\\dir\\ a b\\\\");
+///
+/// let v: Vec<_> = Iter::with_specials(&s, b' ', b'\\', b"").unescaped().collect();
+/// assert_eq!(v, &[&b"C:\\dir a"[..], &b"b\\"[..]]);
+/// ```
+pub fn join_minimal(fields: &[&[u8]], delim: u8, escape: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(delim);
+        }
+
+        for (j, &c) in field.iter().enumerate() {
+            let lone = |k: usize| -> bool {
+                let before = k.checked_sub(1).map(|k| field[k]);
+                let after = field.get(k + 1).copied();
+                before != Some(escape) && after.is_some_and(|c| c != delim && c != escape)
+            };
+            if c == delim || (c == escape && !lone(j)) {
+                out.push(escape);
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Same as [`join`], but borrows a lone field which needs no escaping instead of copying it.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use escaped_delimiter::join_cow;
+///
+/// assert!(matches!(join_cow(&[b"ab"], b' ', b'\\'), Cow::Borrowed(b"ab")));
+/// assert_eq!(join_cow(&[b"a b", b"c"], b' ', b'\\'), &b"a\\ b c"[..]);
+/// ```
+pub fn join_cow<'a>(fields: &[&'a [u8]], delim: u8, escape: u8) -> Cow<'a, [u8]> {
+    match fields {
+        [] => Cow::Borrowed(&[]),
+        [field] => escape_field(field, delim, escape),
+        _ => Cow::Owned(join(fields, delim, escape)),
+    }
+}
+
+/// Same as [`join`], but writes to `w` without building the whole output.
+///
+/// ```
+/// use escaped_delimiter::write_joined;
+///
+/// let mut out = Vec::new();
+/// write_joined(&mut out, &[b"a b", b"c"], b' ', b'\\').unwrap();
+/// assert_eq!(out, b"a\\ b c");
+/// ```
+pub fn write_joined<W: Write>(
+    w: &mut W,
+    fields: &[&[u8]],
+    delim: u8,
+    escape: u8,
+) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            w.write_all(&[delim])?;
+        }
+        w.write_all(&escape_field(field, delim, escape))?;
+    }
+    Ok(())
+}
+
+/// Returns an iterator which escapes each field, borrowing those with nothing to escape.
+///
+/// This is the lazy counterpart of [`join`], for callers which write their own separators.
+///
+/// ```
+/// use escaped_delimiter::escape_all;
+///
+/// let v: Vec<_> = escape_all([&b"a b"[..], b"c"], b' ', b'\\').collect();
+/// assert_eq!(v, &[&b"a\\ b"[..], &b"c"[..]]);
+/// ```
+pub fn escape_all<'a>(
+    fields: impl IntoIterator<Item = &'a [u8]>,
+    delim: u8,
+    escape: u8,
+) -> impl Iterator<Item = Cow<'a, [u8]>> {
+    fields
+        .into_iter()
+        .map(move |field| escape_field(field, delim, escape))
+}
+
+/// Returns an iterator of the raw fields, each with the offset of every escape character in it
+/// which escapes a byte, and that byte.
+///
+/// A lone escape character at the end escapes nothing, so it is not listed.
+///
+/// ```
+/// use escaped_delimiter::fields_with_escape_pairs;
+///
+/// let v: Vec<_> = fields_with_escape_pairs(b"a\\ b\\\\ c\\", b' ', b'\\').collect();
+/// assert_eq!(v, &[
+///     (&b"a\\ b\\\\"[..], vec![(1, b' '), (4, b'\\')]),
+///     (&b"c\\"[..], vec![]),
+/// ]);
+/// ```
+pub fn fields_with_escape_pairs(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+) -> impl Iterator<Item = (&[u8], Vec<(usize, u8)>)> {
+    Iter::from_slice(slice, delim, escape).map(move |field| (field, escape_pairs(field, escape)))
+}
+
+/// Returns an iterator of the fields and the offsets of the escaped delimiters in each, from
+/// the start of `slice`.
+///
+/// The offsets are those of the delimiters themselves, not of the escape characters before them.
+///
+/// ```
+/// use escaped_delimiter::fields_with_escaped_delim_offsets;
+///
+/// let v: Vec<_> = fields_with_escaped_delim_offsets(b"a b\\ c\\\\ d\\ ", b' ', b'\\').collect();
+/// assert_eq!(v, &[
+///     (&b"a"[..], vec![]),
+///     (&b"b\\ c\\\\"[..], vec![4]),
+///     (&b"d\\ "[..], vec![11]),
+/// ]);
+/// ```
+pub fn fields_with_escaped_delim_offsets(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+) -> impl Iterator<Item = (&[u8], Vec<usize>)> {
+    Iter::from_slice(slice, delim, escape).map(move |field| {
+        let base = field.as_ptr() as usize - slice.as_ptr() as usize;
+        let offsets = escape_pairs(field, escape)
+            .into_iter()
+            .filter(|&(_, c)| c == delim)
+            .map(|(i, _)| base + i + 1)
+            .collect();
+        (field, offsets)
+    })
+}
+
+fn escape_pairs(field: &[u8], escape: u8) -> Vec<(usize, u8)> {
+    let mut pairs = Vec::new();
+    let mut escaped = false;
+    for (i, &c) in field.iter().enumerate() {
+        if escaped {
+            pairs.push((i - 1, c));
+            escaped = false;
+        } else if c == escape {
+            escaped = true;
+        }
+    }
+    pairs
+}
+
+/// Rewrites the slice into its canonical form.
+///
+/// Each field is unescaped and escaped again, so that redundant escape characters and
+/// consecutive delimiters are removed. Two slices which split into the same unescaped fields
+/// are normalized to the same bytes.
+///
+/// ```
+/// use escaped_delimiter::normalize;
+///
+/// assert_eq!(normalize(b"  \\a b\\ \\\\  ", b' ', b'\\'), b"a b\\ \\\\");
+/// ```
+pub fn normalize(slice: &[u8], delim: u8, escape: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(slice.len());
+    for (i, field) in Iter::from_slice(slice, delim, escape).enumerate() {
+        if i > 0 {
+            out.push(delim);
+        }
+        out.extend_from_slice(&escape_field(&unescape(field, escape), delim, escape));
+    }
+    out
+}
+
+/// Replaces each unescaped field by what `per_field` returns for it, escaped and joined with
+/// the delimiter as in [`join`].
+///
+/// The result splits back into the new fields, except for those `per_field` makes empty.
+///
+/// ```
+/// use escaped_delimiter::transform;
+///
+/// let s = transform(b"a\\ b c", b' ', b'\\', |f| f.to_ascii_uppercase());
+/// assert_eq!(s, b"A\\ B C");
+/// ```
+pub fn transform<F>(slice: &[u8], delim: u8, escape: u8, mut per_field: F) -> Vec<u8>
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    let mut out = Vec::with_capacity(slice.len());
+    for (i, field) in Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .enumerate()
+    {
+        if i > 0 {
+            out.push(delim);
+        }
+        out.extend_from_slice(&escape_field(&per_field(&field), delim, escape));
+    }
+    out
+}
+
+/// Same as [`transform`], but reads the fields from `r` and writes the result to `w`, without
+/// holding more than a field at a time.
+///
+/// A field may span any number of refills of `r`, even between an escape character and the
+/// byte it escapes.
+///
+/// ```
+/// use escaped_delimiter::transform_stream;
+///
+/// let mut out = Vec::new();
+/// transform_stream(&b"a\\ b c"[..], &mut out, b' ', b'\\', |f| f.to_ascii_uppercase()).unwrap();
+/// assert_eq!(out, b"A\\ B C");
+/// ```
+pub fn transform_stream<R, W, F>(
+    mut r: R,
+    mut w: W,
+    delim: u8,
+    escape: u8,
+    mut per_field: F,
+) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    let mut field = Vec::new();
+    let mut decoded = Vec::new();
+    let mut tracker = EscapeTracker::new(escape);
+    let mut first = true;
+
+    let mut flush = |field: &mut Vec<u8>, w: &mut W| -> io::Result<()> {
+        if field.is_empty() {
+            return Ok(());
+        }
+        decoded.clear();
+        unescape_into(field, escape, &mut decoded);
+        field.clear();
+        if !first {
+            w.write_all(&[delim])?;
+        }
+        first = false;
+        w.write_all(&escape_field(&per_field(&decoded), delim, escape))
+    };
+
+    loop {
+        let buf = r.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for &c in buf {
+            if tracker.is_unescaped_target(c, c == delim) {
+                flush(&mut field, &mut w)?;
+                continue;
+            }
+            field.push(c);
+        }
+        let len = buf.len();
+        r.consume(len);
+    }
+    flush(&mut field, &mut w)
+}
+
+/// Rewrites every unescaped `from_delim` into `to_delim`, so that the slice splits on `to_delim`
+/// into the same unescaped fields as it did on `from_delim`.
+///
+/// Every delimiter is rewritten, including the consecutive ones. Any other byte is kept as is,
+/// except that an escape character is inserted before each unescaped `to_delim` in the content.
+/// An escaped `from_delim` keeps its escape character, which is redundant but harmless. If
+/// `from_delim` is also the escape character, it always splits, as in [`iter`](crate::iter).
+///
+/// ```
+/// use escaped_delimiter::replace_field_delim;
+///
+/// let s = replace_field_delim(b"a,b\\,c\\\\,d\te", b',', b'\\', b'\t');
+/// assert_eq!(s, b"a\tb\\,c\\\\\td\\\te");
+/// ```
+pub fn replace_field_delim(slice: &[u8], from_delim: u8, escape: u8, to_delim: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(slice.len());
+    let mut escaped = false;
+    for &c in slice {
+        if escaped {
+            escaped = false;
+            out.push(c);
+        } else if c == escape && c != from_delim {
+            escaped = true;
+            out.push(c);
+        } else if c == from_delim {
+            out.push(to_delim);
+        } else if c == to_delim {
+            out.extend_from_slice(&[escape, c]);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Rewrites the slice from `old_escape` to `new_escape`, so that it splits on `delim` into the
+/// same unescaped fields.
+///
+/// The delimiters are kept as they are, and each field is replaced with its unescaped content
+/// escaped again by [`escape_field`] with `new_escape`. So an escape character which escapes
+/// nothing is removed, and `new_escape` in the content is escaped.
+///
+/// ```
+/// use escaped_delimiter::change_escape;
+///
+/// let s = change_escape(b"a\\ b^  c\\\\", b' ', b'\\', b'^');
+/// assert_eq!(s, b"a^ b^^  c\\");
+/// ```
+pub fn change_escape(slice: &[u8], delim: u8, old_escape: u8, new_escape: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(slice.len());
+    let push_content = |out: &mut Vec<u8>, c: u8| {
+        if c == delim || c == new_escape {
+            out.push(new_escape);
+        }
+        out.push(c);
+    };
+
+    let mut escaped = false;
+    for &c in slice {
+        if escaped {
+            escaped = false;
+            push_content(&mut out, c);
+        } else if c == old_escape && c != delim {
+            escaped = true;
+        } else if c == delim {
+            out.push(delim);
+        } else {
+            push_content(&mut out, c);
+        }
+    }
+    if escaped {
+        push_content(&mut out, old_escape);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unescape() {
+        assert!(matches!(unescape(b"", b'Y'), Cow::Borrowed(b"")));
+        assert!(matches!(unescape(b"abc", b'Y'), Cow::Borrowed(b"abc")));
+        assert!(matches!(unescape(b"abcY", b'Y'), Cow::Borrowed(b"abcY")));
+
+        assert_eq!(unescape(b"YX", b'Y'), &b"X"[..]);
+        assert_eq!(unescape(b"YY", b'Y'), &b"Y"[..]);
+        assert_eq!(unescape(b"YYY", b'Y'), &b"YY"[..]);
+        assert_eq!(unescape(b"YYYX", b'Y'), &b"YX"[..]);
+        assert_eq!(unescape(b"aYXbYYc", b'Y'), &b"aXbYc"[..]);
+        assert_eq!(unescape(b"Ya", b'Y'), &b"a"[..]);
+    }
+
+    #[test]
+    fn test_count_escapes() {
+        let fields: &[&[u8]] = &[
+            b"", b"abc", b"Y", b"aY", b"YY", b"YYY", b"YXbYYc", b"YaYbYc",
+        ];
+        for field in fields {
+            let n = count_escapes(field, b'Y');
+            assert_eq!(field.len() - n, unescape(field, b'Y').len());
+        }
+    }
+
+    #[test]
+    fn test_is_escaped_at() {
+        let s = b"aYYYYYb";
+        let escaped: Vec<_> = (0..s.len() + 1)
+            .map(|i| is_escaped_at(s, b'Y', i))
+            .collect();
+        assert_eq!(
+            escaped,
+            &[false, true, false, true, false, true, false, false]
+        );
+
+        let s = b"YYYYXYYY";
+        let escaped: Vec<_> = (0..s.len()).map(|i| is_escaped_at(s, b'Y', i)).collect();
+        assert_eq!(
+            escaped,
+            &[true, false, true, false, false, true, false, false]
+        );
+
+        assert!(!is_escaped_at(b"", b'Y', 0));
+        assert!(!is_escaped_at(b"Y", b'Y', 0));
+    }
+
+    #[test]
+    fn test_trailing_escape_parity() {
+        for n in 0..8 {
+            let s = [&b"aYXb"[..], &vec![b'Y'; n]].concat();
+            assert_eq!(trailing_escape_parity(&s, b'Y'), n % 2 == 1);
+            assert_eq!(trailing_escape_parity(&s[4..], b'Y'), n % 2 == 1);
+        }
+        assert!(!trailing_escape_parity(b"YYYa", b'Y'));
+
+        // At any offset, the parity tells whether the byte there is escaped in the whole slice.
+        let s = b"aYYYXbYXYYcYYYYYX";
+        for mid in 0..s.len() {
+            let carried = trailing_escape_parity(&s[..mid], b'Y');
+            assert_eq!(carried, mid > 0 && is_escaped_at(s, b'Y', mid - 1));
+        }
+    }
+
+    #[test]
+    fn test_escape_field() {
+        assert!(matches!(escape_field(b"", b'X', b'Y'), Cow::Borrowed(b"")));
+        assert!(matches!(
+            escape_field(b"abc", b'X', b'Y'),
+            Cow::Borrowed(b"abc")
+        ));
+
+        assert_eq!(escape_field(b"X", b'X', b'Y'), &b"YX"[..]);
+        assert_eq!(escape_field(b"Y", b'X', b'Y'), &b"YY"[..]);
+        assert_eq!(escape_field(b"aXbYc", b'X', b'Y'), &b"aYXbYYc"[..]);
+
+        for field in [&b"aXbYc"[..], b"YYX", b"XY", b"Y"] {
+            assert_eq!(unescape(&escape_field(field, b'X', b'Y'), b'Y'), field);
+        }
+    }
+
+    #[test]
+    fn test_escaped_len() {
+        for field in crate::test::strings(b"aXY", 6) {
+            let len = escape_field(&field, b'X', b'Y').len();
+            assert_eq!(escaped_len(&field, b'X', b'Y'), len, "{:?}", field);
+        }
+        assert_eq!(escaped_len(b"XX", b'X', b'X'), 4);
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join(&[], b'X', b'Y'), b"");
+        assert_eq!(join(&[b"a"], b'X', b'Y'), b"a");
+        assert_eq!(join(&[b"aXb", b"Y", b"c"], b'X', b'Y'), b"aYXbXYYXc");
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let strings: Vec<_> = crate::test::strings(b"aXYZ", 3).collect();
+        for a in &strings {
+            for b in &strings {
+                let fields = [&a[..], b];
+                assert!(round_trips(&fields, b'X', b'Y'), "{:?}", fields);
+                assert!(round_trips(&fields, b'Z', b'X'), "{:?}", fields);
+
+                let plain = !fields.concat().contains(&b'X');
+                assert_eq!(round_trips(&fields, b'X', b'X'), plain, "{:?}", fields);
+            }
+        }
+
+        assert!(round_trips(&[b""], b'X', b'Y'));
+        assert!(!round_trips(&[], b'X', b'Y'));
+    }
+
+    #[test]
+    fn test_join_minimal() {
+        assert_eq!(join_minimal(&[], b'X', b'Y'), b"");
+        assert_eq!(join_minimal(&[b"aYb", b"cX"], b'X', b'Y'), b"aYbXcYX");
+        assert_eq!(
+            join_minimal(&[b"aYYb", b"YXc", b"dY"], b'X', b'Y'),
+            b"aYYYYbXYYYXcXdYY"
+        );
+
+        let decode = |s: &[u8]| -> Vec<Vec<u8>> {
+            let it = crate::Iter::with_specials(s, b'X', b'Y', b"").keep_empty();
+            it.map(|f| unescape_with(f, b'Y', |c| c == b'X' || c == b'Y').into_owned())
+                .collect()
+        };
+
+        let strings: Vec<_> = crate::test::strings(b"aXY", 4).collect();
+        let mut fewer = 0;
+        for a in &strings {
+            for b in &strings {
+                let fields = [&a[..], b];
+                let joined = join_minimal(&fields, b'X', b'Y');
+                assert_eq!(decode(&joined), fields, "{:?}", fields);
+
+                let escapes = |s: &[u8]| s.len() - fields.concat().len();
+                let full = join(&fields, b'X', b'Y');
+                assert!(escapes(&joined) <= escapes(&full), "{:?}", fields);
+                fewer += usize::from(escapes(&joined) < escapes(&full));
+            }
+        }
+        assert!(fewer > 0);
+    }
+
+    #[test]
+    fn test_join_with() {
+        assert_eq!(join_with(&[], b'X', b'Y', b"--"), b"");
+        assert_eq!(join_with(&[b"aXb"], b'X', b'Y', b"--"), b"aYXb");
+        assert_eq!(
+            join_with(&[b"aXb", b"Y", b"c"], b'X', b'Y', b"--"),
+            b"aYXb--YY--c"
+        );
+        assert_eq!(join_with(&[b"a", b"b"], b'X', b'Y', b""), b"ab");
+
+        let strings: Vec<_> = crate::test::strings(b"aXY", 3).collect();
+        for a in &strings {
+            for b in &strings {
+                let fields = [&a[..], b, a];
+                assert_eq!(
+                    join_with(&fields, b'X', b'Y', b"X"),
+                    join(&fields, b'X', b'Y')
+                );
+
+                for separator in [&b"aX"[..], b"aaX"] {
+                    let joined = join_with(&fields, b'X', b'Y', separator);
+                    let v: Vec<_> = crate::iter_seq(&joined, separator, b'Y')
+                        .map(|f| unescape(f, b'Y'))
+                        .collect();
+                    let expected: Vec<_> = fields.into_iter().filter(|f| !f.is_empty()).collect();
+                    assert_eq!(v, expected, "{:?} {:?}", fields, separator);
+                }
+            }
+        }
+
+        // A separator starting with the delimiter runs into an escaped one.
+        let joined = join_with(&[b"aX", b"b"], b'X', b'Y', b"XX");
+        assert_eq!(joined, b"aYXXXb");
+        assert_eq!(crate::iter_seq(&joined, b"XX", b'Y').count(), 1);
+    }
+
+    #[test]
+    fn test_join_cow() {
+        let inputs: &[&[&[u8]]] = &[&[], &[b""], &[b"a"], &[b"aXb"], &[b"a", b"b"], &[b"", b""]];
+        for fields in inputs {
+            let joined = join_cow(fields, b'X', b'Y');
+            assert_eq!(joined, join(fields, b'X', b'Y'));
+            let clean = fields.len() <= 1 && !fields.concat().contains(&b'X');
+            assert_eq!(matches!(joined, Cow::Borrowed(_)), clean, "{:?}", fields);
+        }
+
+        let field = b"abc";
+        match join_cow(&[field], b'X', b'Y') {
+            Cow::Borrowed(s) => assert_eq!(s.as_ptr(), field.as_ptr()),
+            Cow::Owned(_) => panic!("allocated"),
+        }
+    }
+
+    #[test]
+    fn test_write_joined() {
+        let inputs: &[&[&[u8]]] = &[
+            &[],
+            &[b""],
+            &[b"a"],
+            &[b"aXb", b"", b"Y", b"c"],
+            &[b"X", b"X"],
+        ];
+        for fields in inputs {
+            let mut out = Vec::new();
+            write_joined(&mut out, fields, b'X', b'Y').unwrap();
+            assert_eq!(out, join(fields, b'X', b'Y'));
+        }
+
+        let mut buf = [0; 4];
+        let err = write_joined(&mut &mut buf[..], &[b"aXb", b"c"], b'X', b'Y').unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn test_escape_all() {
+        let fields: &[&[u8]] = &[b"aXb", b"", b"Y", b"c", b"XYX"];
+        let escaped: Vec<_> = escape_all(fields.iter().copied(), b'X', b'Y').collect();
+        assert!(matches!(escaped[3], Cow::Borrowed(b"c")));
+        assert_eq!(escaped.join(&b'X'), join(fields, b'X', b'Y'));
+
+        assert_eq!(escape_all([], b'X', b'Y').count(), 0);
+    }
+
+    #[test]
+    fn test_fields_with_escaped_delim_offsets() {
+        let s = b"XaXbYXcYYXdYXeYYYXfXYgY";
+        let v: Vec<_> = fields_with_escaped_delim_offsets(s, b'X', b'Y').collect();
+        assert_eq!(
+            v,
+            &[
+                (&b"a"[..], vec![]),
+                (&b"bYXcYY"[..], vec![5]),
+                (&b"dYXeYYYXf"[..], vec![12, 17]),
+                (&b"YgY"[..], vec![]),
+            ]
+        );
+
+        for s in crate::test::strings(b"aXY", 7) {
+            for (field, offsets) in fields_with_escaped_delim_offsets(&s, b'X', b'Y') {
+                let roles = Iter::from_slice(&s, b'X', b'Y').byte_roles();
+                let base = field.as_ptr() as usize - s.as_ptr() as usize;
+                let expected = roles
+                    .filter(|&(i, _, role)| {
+                        (base..base + field.len()).contains(&i)
+                            && role == crate::ByteRole::EscapedDelimiter
+                    })
+                    .map(|(i, _, _)| i);
+                assert!(expected.eq(offsets), "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields_with_escape_pairs() {
+        let s = b"aYXbYXXYYYXcYYXYdY";
+        let v: Vec<_> = fields_with_escape_pairs(s, b'X', b'Y').collect();
+        assert_eq!(
+            v,
+            &[
+                (&b"aYXbYX"[..], vec![(1, b'X'), (4, b'X')]),
+                (&b"YYYXcYY"[..], vec![(0, b'Y'), (2, b'X'), (5, b'Y')]),
+                (&b"YdY"[..], vec![(0, b'd')]),
+            ]
+        );
+
+        // Removing each escape character listed gives the unescaped field.
+        for (field, pairs) in v {
+            let mut unescaped = field.to_vec();
+            for &(i, c) in pairs.iter().rev() {
+                assert_eq!(unescaped.remove(i), b'Y');
+                assert_eq!(unescaped[i], c);
+            }
+            assert_eq!(unescape(field, b'Y'), &unescaped[..]);
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let s = b"XaYXbXXcYYXdYeX";
+        let upper = transform(s, b'X', b'Y', |f| f.to_ascii_uppercase());
+        assert_eq!(upper, b"AYXBXCYYXDE");
+
+        let fields: Vec<_> = Iter::from_slice(&upper, b'X', b'Y').unescaped().collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .unescaped()
+            .map(|f| f.to_ascii_uppercase())
+            .collect();
+        assert_eq!(fields, expected);
+
+        // A field may be made to need escaping, and the identity is `normalize`.
+        assert_eq!(
+            transform(b"aXb", b'X', b'Y', |f| [f, b"X"].concat()),
+            b"aYXXbYX"
+        );
+        assert_eq!(
+            transform(s, b'X', b'Y', <[u8]>::to_vec),
+            normalize(s, b'X', b'Y')
+        );
+        assert_eq!(transform(b"", b'X', b'Y', |_| unreachable!()), b"");
+    }
+
+    #[test]
+    fn test_transform_stream() {
+        // Reads at most `n` bytes at a time.
+        struct Fragmented<'a>(&'a [u8], usize);
+        impl io::Read for Fragmented<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.1.min(buf.len()).min(self.0.len());
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let upper = |f: &[u8]| [f, b"X"].concat().to_ascii_uppercase();
+        for s in crate::test::strings(b"aXY", 7) {
+            let expected = transform(&s, b'X', b'Y', upper);
+            for n in 1..4 {
+                let r = io::BufReader::with_capacity(n, Fragmented(&s, n));
+                let mut out = Vec::new();
+                transform_stream(r, &mut out, b'X', b'Y', upper).unwrap();
+                assert_eq!(out, expected, "{:?} {}", s, n);
+            }
+
+            let mut out = Vec::new();
+            transform_stream(&s[..], &mut out, b'X', b'X', upper).unwrap();
+            assert_eq!(out, transform(&s, b'X', b'X', upper), "{:?}", s);
+        }
+
+        let mut buf = [0; 4];
+        let err = transform_stream(&b"aXbXc"[..], &mut buf[..], b'X', b'Y', upper).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(b"", b'X', b'Y'), b"");
+        assert_eq!(normalize(b"XXaXXbX", b'X', b'Y'), b"aXb");
+        assert_eq!(normalize(b"YaYXbXYYXY", b'X', b'Y'), b"aYXbXYYXYY");
+
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"abc",
+            b"XYXaYYYXXbYX",
+            b"YaYbYcY",
+            b"XXYYYYXXY",
+            b"abXYXcdeXYfYXXYYYXgYYX",
+        ];
+        for s in inputs {
+            let once = normalize(s, b'X', b'Y');
+            assert_eq!(normalize(&once, b'X', b'Y'), once);
+
+            let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+                .map(|f| unescape(f, b'Y').into_owned())
+                .collect();
+            let normalized: Vec<_> = Iter::from_slice(&once, b'X', b'Y')
+                .map(|f| unescape(f, b'Y').into_owned())
+                .collect();
+            assert_eq!(fields, normalized);
+        }
+    }
+
+    #[test]
+    fn test_change_escape() {
+        let s = b"aXbYXcZdXXYYXeZYZfY";
+        let changed = change_escape(s, b'X', b'Y', b'Z');
+        assert_eq!(changed, b"aXbZXcZZdXXYXeZZZZfY");
+
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+        let fields: Vec<_> = Iter::from_slice(&changed, b'X', b'Z').unescaped().collect();
+        assert_eq!(fields, expected);
+
+        // Without redundant escape characters, it round-trips byte for byte.
+        let back = change_escape(&changed, b'X', b'Z', b'Y');
+        assert_eq!(back, b"aXbYXcZdXXYYXeZZfYY");
+        assert_eq!(change_escape(&back, b'X', b'Y', b'Z'), changed);
+
+        let s = br"a\ b^c \\ d\";
+        let changed = change_escape(s, b' ', b'\\', b'^');
+        assert_eq!(changed, br"a^ b^^c \ d\");
+        assert_eq!(
+            change_escape(&changed, b' ', b'^', b'\\'),
+            br"a\ b^c \\ d\\"
+        );
+
+        assert_eq!(change_escape(b"", b'X', b'Y', b'Z'), b"");
+    }
+
+    #[test]
+    fn test_replace_field_delim() {
+        let s = b"aXbYXcZdXXYYXeYZY";
+        let replaced = replace_field_delim(s, b'X', b'Y', b'Z');
+        assert_eq!(replaced, b"aZbYXcYZdZZYYZeYZY");
+
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+        let fields: Vec<_> = Iter::from_slice(&replaced, b'Z', b'Y')
+            .unescaped()
+            .collect();
+        assert_eq!(fields, expected);
+
+        assert_eq!(replace_field_delim(b"", b'X', b'Y', b'Z'), b"");
+
+        // A delimiter which is also the escape character is rewritten every time.
+        let s = b"aXbXXc";
+        let replaced = replace_field_delim(s, b'X', b'X', b',');
+        assert_eq!(replaced, b"a,b,,c");
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'X').collect();
+        assert!(Iter::from_slice(&replaced, b',', b'X').eq(expected));
+    }
+}
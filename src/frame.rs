@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::fmt;
+
+const PREFIX_LEN: usize = 4;
+
+/// Writes each field as its length in big-endian `u32` followed by its bytes, without escaping.
+///
+/// # Panics
+///
+/// Panics if a field is longer than `u32::MAX` bytes.
+///
+/// ```
+/// use escaped_delimiter::{frame_fields, unframe_fields};
+///
+/// let framed = frame_fields(&[b"a b", b""]);
+/// assert_eq!(framed, b"\0\0\0\x03a b\0\0\0\0");
+/// assert_eq!(unframe_fields(&framed).unwrap(), &[&b"a b"[..], &b""[..]]);
+/// ```
+pub fn frame_fields(fields: &[&[u8]]) -> Vec<u8> {
+    let len = fields.iter().map(|f| PREFIX_LEN + f.len()).sum();
+    let mut out = Vec::with_capacity(len);
+    for field in fields {
+        let prefix = u32::try_from(field.len()).expect("the field is too long to frame");
+        out.extend_from_slice(&prefix.to_be_bytes());
+        out.extend_from_slice(field);
+    }
+    out
+}
+
+/// Reads the fields written by [`frame_fields`].
+pub fn unframe_fields(slice: &[u8]) -> Result<Vec<&[u8]>, FrameError> {
+    let mut fields = Vec::new();
+    let mut offset = 0;
+    while offset < slice.len() {
+        let truncated = FrameError { offset };
+
+        let prefix = slice.get(offset..offset + PREFIX_LEN).ok_or(truncated)?;
+        let len = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+        let start = offset + PREFIX_LEN;
+        let field = start
+            .checked_add(len)
+            .and_then(|end| slice.get(start..end))
+            .ok_or(truncated)?;
+
+        fields.push(field);
+        offset = start + len;
+    }
+
+    Ok(fields)
+}
+
+/// The error returned by [`unframe_fields`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameError {
+    /// The offset of the truncated frame.
+    pub offset: usize,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame at offset {} is truncated", self.offset)
+    }
+}
+
+impl Error for FrameError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_fields() {
+        let large = vec![b'X'; 70000];
+        let fields: &[&[u8]] = &[b"", b"aXbYc", &large, b"", b"d"];
+        let framed = frame_fields(fields);
+        assert_eq!(framed.len(), 4 * 5 + 5 + 70000 + 1);
+        assert_eq!(unframe_fields(&framed).unwrap(), fields);
+
+        assert_eq!(frame_fields(&[]), b"");
+        assert!(unframe_fields(b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unframe_fields_truncated() {
+        let framed = frame_fields(&[b"ab", b"cd"]);
+        for len in [1, 5, 7, 11] {
+            let err = unframe_fields(&framed[..len]).unwrap_err();
+            let offset = if len < 6 { 0 } else { 6 };
+            assert_eq!(err, FrameError { offset });
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::iter::Rev;
+
+use crate::Iter;
+
+/// Same as [`Iter::keep_empty`], but a trailing delimiter terminates the last field instead of
+/// starting an empty one, like [`str::split_terminator`].
+///
+/// An empty slice yields no fields.
+///
+/// ```
+/// use escaped_delimiter::split_terminator;
+///
+/// let s_vec: Vec<_> = split_terminator(b"a,,b\\,,", b',', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a"[..], &b""[..], &b"b\\,"[..]]);
+/// ```
+pub fn split_terminator(slice: &[u8], delim: u8, escape: u8) -> KeepEmpty<'_> {
+    let it = Iter::from_slice(slice, delim, escape);
+    let inner = match slice.len().checked_sub(1) {
+        Some(last) if it.scanner().is_boundary(slice, last) => &slice[..last],
+        _ => slice,
+    };
+
+    KeepEmpty {
+        inner: Iter { inner, ..it },
+        finished: slice.is_empty(),
+    }
+}
+
+/// Same as [`split_terminator`], but from the end of the slice.
+///
+/// ```
+/// use escaped_delimiter::rsplit_terminator;
+///
+/// let s_vec: Vec<_> = rsplit_terminator(b"a,,b\\,,", b',', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"b\\,"[..], &b""[..], &b"a"[..]]);
+/// ```
+#[inline]
+pub fn rsplit_terminator(slice: &[u8], delim: u8, escape: u8) -> Rev<KeepEmpty<'_>> {
+    split_terminator(slice, delim, escape).rev()
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator which does not collapse consecutive delimiters, like
+    /// [`slice::split`].
+    ///
+    /// Every unescaped delimiter separates two fields, so leading, trailing, and consecutive
+    /// delimiters yield empty fields, and an empty slice yields one empty field.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s_vec: Vec<_> = iter(b"a  b\\ c ", b' ', b'\\').keep_empty().collect();
+    /// assert_eq!(s_vec, &[&b"a"[..], &b""[..], &b"b\\ c"[..], &b""[..]]);
+    /// ```
+    #[inline]
+    pub fn keep_empty(self) -> KeepEmpty<'a> {
+        KeepEmpty {
+            inner: self,
+            finished: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepEmpty<'a> {
+    inner: Iter<'a>,
+    finished: bool,
+}
+
+impl<'a> Iterator for KeepEmpty<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.inner.inner;
+        match self.inner.find_delim() {
+            Some(i) => {
+                self.inner.inner = &slice[i + 1..];
+                Some(&slice[..i])
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for KeepEmpty<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.inner.inner;
+        match self.inner.rfind_delim() {
+            Some(i) => {
+                self.inner.inner = &slice[..i];
+                Some(&slice[i + 1..])
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+}
+
+impl<'a> KeepEmpty<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        if self.finished {
+            &[]
+        } else {
+            self.inner.as_slice()
+        }
+    }
+
+    /// Returns the number of unescaped delimiters in the rest of the slice.
+    ///
+    /// Unless the iterator is exhausted, `count_fields() == count_delimiters() + 1`.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let it = iter(b"a,,b,", b',', b'\\').keep_empty();
+    /// assert_eq!(it.count_delimiters(), 3);
+    /// assert_eq!(it.count_fields(), 4);
+    /// ```
+    #[inline]
+    pub fn count_delimiters(&self) -> usize {
+        if self.finished {
+            0
+        } else {
+            self.inner.count_delimiters()
+        }
+    }
+
+    /// Returns the number of fields left, without consuming the iterator.
+    #[inline]
+    pub fn count_fields(&self) -> usize {
+        (*self).count()
+    }
+
+    /// Same as [`Iter::to_owned_fields`], but with the empty fields.
+    pub fn to_owned_fields(self) -> Vec<Vec<u8>> {
+        let it = self.inner;
+        self.map(|field| it.unescape(field).into_owned()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keep_empty() {
+        let s = b"XaXXbYXcYYXX";
+        let expected = [
+            &b""[..],
+            &b"a"[..],
+            &b""[..],
+            &b"bYXcYY"[..],
+            &b""[..],
+            &b""[..],
+        ];
+        let words: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().collect();
+        assert_eq!(words, expected);
+        let mut words: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().rev().collect();
+        words.reverse();
+        assert_eq!(words, expected);
+
+        let mut words = Iter::from_slice(b"", b'X', b'Y').keep_empty();
+        assert_eq!(words.next_back(), Some(&b""[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = Iter::from_slice(b"aXb", b'X', b'Y').keep_empty();
+        assert_eq!(words.next_back(), Some(&b"b"[..]));
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next_back(), None);
+        assert_eq!(words.as_slice(), b"");
+    }
+
+    #[test]
+    fn test_keep_empty_split() {
+        // Without escape characters, it is the same as `slice::split`.
+        for s in [&b""[..], b"X", b"XX", b"aXXb", b"XaX", b"abc"] {
+            let expected: Vec<_> = s.split(|&c| c == b'X').collect();
+            let words: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().collect();
+            assert_eq!(words, expected);
+            let words: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().rev().collect();
+            assert!(words.into_iter().eq(expected.into_iter().rev()));
+        }
+    }
+
+    #[test]
+    fn test_keep_empty_rev_exhaustive() {
+        for s in crate::test::strings(b"aXY", 8) {
+            let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y').keep_empty().collect();
+            let mut words: Vec<_> = Iter::from_slice(&s, b'X', b'Y')
+                .keep_empty()
+                .rev()
+                .collect();
+            words.reverse();
+            assert_eq!(words, expected, "{:?}", s);
+
+            // Each bit of `pattern` chooses `next` (0) or `next_back` (1).
+            for pattern in 0..16u32 {
+                let mut words = Iter::from_slice(&s, b'X', b'Y').keep_empty();
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                for i in 0.. {
+                    let word = if pattern >> (i % 4) & 1 == 0 {
+                        words.next().map(|w| front.push(w))
+                    } else {
+                        words.next_back().map(|w| back.push(w))
+                    };
+                    if word.is_none() {
+                        break;
+                    }
+                }
+
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, expected, "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_terminator() {
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[]),
+            (b"X", &[b""]),
+            (b"XX", &[b"", b""]),
+            (b"aXb", &[b"a", b"b"]),
+            (b"aXbX", &[b"a", b"b"]),
+            (b"aXbXX", &[b"a", b"b", b""]),
+            (b"aXbYX", &[b"a", b"bYX"]),
+            (b"aXbYXX", &[b"a", b"bYX"]),
+            (b"aXbYYX", &[b"a", b"bYY"]),
+            (b"XaXXb", &[b"", b"a", b"", b"b"]),
+        ];
+
+        for &(s, expected) in cases {
+            let words: Vec<_> = split_terminator(s, b'X', b'Y').collect();
+            assert_eq!(words, expected, "{:?}", s);
+
+            let mut words: Vec<_> = rsplit_terminator(s, b'X', b'Y').collect();
+            words.reverse();
+            assert_eq!(words, expected, "{:?}", s);
+        }
+
+        // Without escape characters, it is the same as `str::split_terminator`.
+        for s in ["", "X", "XX", "aXXb", "XaX", "abc", "aXbXX"] {
+            let expected: Vec<_> = s.split_terminator('X').map(str::as_bytes).collect();
+            let words: Vec<_> = split_terminator(s.as_bytes(), b'X', b'Y').collect();
+            assert_eq!(words, expected);
+            let expected: Vec<_> = s.rsplit_terminator('X').map(str::as_bytes).collect();
+            let words: Vec<_> = rsplit_terminator(s.as_bytes(), b'X', b'Y').collect();
+            assert_eq!(words, expected);
+        }
+    }
+
+    #[test]
+    fn test_count_keep_empty() {
+        let mut words = Iter::from_slice(b"aXXbYXcX", b'X', b'Y').keep_empty();
+        assert_eq!(words.count_delimiters(), 3);
+        assert_eq!(words.count_fields(), 4);
+
+        words.next();
+        assert_eq!(words.count_delimiters(), 2);
+        assert_eq!(words.count_fields(), 3);
+
+        let mut words = Iter::from_slice(b"", b'X', b'Y').keep_empty();
+        assert_eq!(words.count_delimiters(), 0);
+        assert_eq!(words.count_fields(), 1);
+        words.next();
+        assert_eq!(words.count_delimiters(), 0);
+        assert_eq!(words.count_fields(), 0);
+    }
+
+    #[test]
+    fn test_keep_empty_to_owned_fields() {
+        let words = Iter::from_slice(b"XaYXbXXcYYXdYeX", b'X', b'Y').keep_empty();
+        assert_eq!(
+            words.to_owned_fields(),
+            &[
+                &b""[..],
+                &b"aXb"[..],
+                &b""[..],
+                &b"cY"[..],
+                &b"de"[..],
+                &b""[..]
+            ]
+        );
+    }
+}
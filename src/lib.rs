@@ -21,24 +21,197 @@
 //! let s = b"a\\ b\\\\ c\\\\\\ d\\\\\\\\ e";
 //! let s_vec: Vec<_> = iter(s, b' ', b'\\').collect();
 //! assert_eq!(s_vec, &[&b"a\\ b\\\\"[..], &b"c\\\\\\ d\\\\\\\\"[..], &b"e"[..]]);
+//!
+//! // An escaped delimiter at the start is content, not a leading separator
+//! let s = b"\\ a b";
+//! let s_vec: Vec<_> = iter(s, b' ', b'\\').collect();
+//! assert_eq!(s_vec, &[&b"\\ a"[..], &b"b"[..]]);
+//!
+//! // Unescape the fields, borrowing those with no escape characters
+//! let s = b"a\\ b\\\\ c\\\\\\ d\\\\\\\\ e";
+//! let s_vec: Vec<_> = iter(s, b' ', b'\\').unescaped().collect();
+//! assert_eq!(s_vec, &[&b"a b\\"[..], &b"c\\ d\\\\"[..], &b"e"[..]]);
 //! ```
 
+use std::borrow::Cow;
+use std::ffi::{CStr, OsStr};
+use std::fmt;
+use std::iter::FusedIterator;
 use std::num::NonZeroUsize;
 
+mod adapters;
+mod any;
+#[cfg(feature = "bytes")]
+mod bytes_iter;
+mod chars;
+mod checksum;
+mod collect;
+mod const_iter;
+mod csv;
+mod cursor;
+mod diagnostics;
+mod doubling;
+mod escape;
+mod frame;
+mod keep_empty;
+mod lines;
+mod located;
+mod nested;
+mod options;
+mod owned;
+mod pairs;
+mod parse;
+mod parser;
+mod peekable;
+mod prefixed;
+mod quoted;
+mod ranges;
+mod roles;
+mod scanner;
+mod search;
+mod segments;
+mod seq;
+mod sink;
+#[cfg(feature = "smallvec")]
+mod small;
+mod sniff;
+mod split;
+mod state;
+mod stats;
+mod str_iter;
+#[cfg(feature = "futures")]
+mod stream;
+mod try_iter;
+mod windowed;
+mod zip;
+
+pub use adapters::{
+    EnumerateFromEnd, EscapeCounts, FieldChunks, FieldPairs, GroupBy, LimitBytes, MapFields,
+    RFieldChunks, Trim, Unescaped,
+};
+pub use any::{iter_any, AnyIter};
+#[cfg(feature = "bytes")]
+pub use bytes_iter::{iter_bytes, BytesIter};
+pub use chars::{decode_chars, DecodedChars, EscapeTable, RustEscapes};
+pub use checksum::{Checksummed, Crc32, FieldHasher};
+pub use collect::{
+    collect_fields, collect_interned, collect_str, compact_in_place, decode_while,
+    dedup_consecutive, extend_unescaped, fields_from_cow, fields_lossy, filter_fields,
+    fold_decoded, for_each_decoded, for_each_field_progress, from_reader_all, group_by_key,
+    rfields_decoded, to_indexed_map, trim_and_compact, unique_fields, FieldUtf8Error,
+};
+pub use const_iter::{iter_const, ConstIter, Delimited};
+pub use csv::{csv_records, CsvError, CsvErrorKind, CsvRecords};
+pub use cursor::Cursor;
+pub use diagnostics::{Diagnostic, DiagnosticKind, WithWarnings};
+pub use doubling::{
+    iter_doubling, iter_doubling_or_escape, unescape_doubling, unescape_doubling_or_escape,
+    DoublingIter, DoublingOrEscapeIter,
+};
+pub use escape::{
+    change_escape, escape_all, escape_field, escaped_len, fields_with_escape_pairs,
+    fields_with_escaped_delim_offsets, is_escaped_at, join, join_cow, join_minimal, join_with,
+    normalize, replace_field_delim, round_trips, trailing_escape_parity, transform,
+    transform_stream, unescape, write_joined,
+};
+pub use frame::{frame_fields, unframe_fields, FrameError};
+pub use keep_empty::{rsplit_terminator, split_terminator, KeepEmpty};
+pub use lines::{lines, Lines};
+pub use located::{Located, Location};
+pub use nested::{split_nested, Nested};
+pub use options::{CommentScope, NullableFields, SplitOptions, SplitOptionsIter};
+pub use owned::OwnedSplit;
+pub use pairs::{pairs, to_map, to_vec_pairs, try_to_map, MissingKvDelim, Pairs};
+pub use parse::{
+    parse_bounded, parse_fields, parse_numbers, FieldCountError, FieldsError, FromFields,
+};
+pub use parser::Parser;
+pub use peekable::PeekableIter;
+pub use prefixed::{
+    split_inclusive_left, split_keep_delimiter_prefix, DelimiterPrefixed, InclusiveLeft,
+};
+pub use quoted::{iter_quoted, Quoted};
+pub use ranges::{field_ranges, FieldRanges};
+pub use roles::{debug_roles, ByteRole};
+pub use scanner::EscapeRun;
+pub use search::{
+    count_field_matches, ends_with_field, field_at_offset, fields_matching_glob, find_field,
+    find_sorted_field, had_trailing_delimiter, has_delimiter, last_field, position_of_decoded,
+    rposition_of_decoded, starts_with_field, strip_field_prefix, strip_field_suffix,
+};
+pub use segments::{raw_segments, RawSegments, Segment};
+pub use seq::{iter_seq, SeqIter};
+pub use sink::FieldSink;
+#[cfg(feature = "smallvec")]
+pub use small::collect_small;
+pub use sniff::{auto_delim, sniff_delimiter};
+pub use split::{
+    cmd_tokenize, fields_until_bytes, rsplit_once_decoded, split_at_nth_delimiter, split_at_offset,
+    split_escaped, split_limited, split_whitespace_escaped, SplitEscaped, SplitWhitespaceEscaped,
+    WithTerminators,
+};
+pub use state::IterState;
+pub use stats::{decoded_len, max_decoded_field_len, max_field_len, stats, tally, SplitStats};
+pub use str_iter::{iter_str, str_iter, StrDelimiter, StrIter};
+#[cfg(feature = "futures")]
+pub use stream::{stream, EscapedStream};
+pub use try_iter::{
+    first_invalid_field, ErrorPolicy, Lenient, ParseError, ParseErrorKind, Problem, TryIter,
+};
+pub use windowed::{for_each_field_windowed, Continuation};
+pub use zip::zip_fields;
+
+use escape::unescape_with;
+use scanner::{Direction, Scanner};
+
 pub fn iter(slice: &[u8], delim: u8, escape: u8) -> Iter<'_> {
     Iter::from_slice(slice, delim, escape)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An iterator of the fields of a slice, returned by [`iter`].
+///
+/// `{:?}` shows the raw state, while `{:#?}` shows the unescaped fields which are left, decoded
+/// lossily as UTF-8.
+///
+/// ```
+/// use escaped_delimiter::iter;
+///
+/// let it = iter(b"a\\ b c", b' ', b'\\');
+/// assert_eq!(format!("{:#?}", it), "[\n    \"a b\",\n    \"c\",\n]");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Iter<'a> {
     delim: u8,
     escape: u8,
+    specials: Option<&'a [u8]>,
+    run: EscapeRun,
+    /// The address which offsets are counted from, if set by [`Iter::from_slice_with_base`].
+    origin: Option<usize>,
     inner: &'a [u8],
 }
 
+impl fmt::Debug for Iter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            let fields =
+                self.map(|field| String::from_utf8_lossy(&self.unescape(field)).into_owned());
+            f.debug_list().entries(fields).finish()
+        } else {
+            f.debug_struct("Iter")
+                .field("delim", &self.delim)
+                .field("escape", &self.escape)
+                .field("specials", &self.specials)
+                .field("run", &self.run)
+                .field("inner", &self.inner)
+                .finish()
+        }
+    }
+}
+
 impl<'a> Iterator for Iter<'a> {
     type Item = &'a [u8];
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let pos = self.find_bow();
         self.inner = &self.inner[pos..];
@@ -49,9 +222,21 @@ impl<'a> Iterator for Iter<'a> {
 
         Some(inner)
     }
+
+    /// Fields are not empty and are separated by at least one byte, and a byte other than the
+    /// delimiter at either end is in a field.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let lower = match (self.inner.first(), self.inner.last()) {
+            (Some(&first), Some(&last)) => (first != self.delim || last != self.delim) as usize,
+            _ => 0,
+        };
+        (lower, Some(self.len().div_ceil(2)))
+    }
 }
 
 impl<'a> DoubleEndedIterator for Iter<'a> {
+    #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         let pos = self.rfind_eow()?.get();
         self.inner = &self.inner[..pos];
@@ -64,6 +249,8 @@ impl<'a> DoubleEndedIterator for Iter<'a> {
     }
 }
 
+impl FusedIterator for Iter<'_> {}
+
 impl Iter<'_> {
     #[inline]
     fn len(self) -> usize {
@@ -75,107 +262,71 @@ impl Iter<'_> {
         self.inner.is_empty()
     }
 
-    fn enumerate(&self) -> impl DoubleEndedIterator<Item = (usize, u8)> + '_ {
-        self.inner.iter().copied().enumerate()
+    /// Returns the offset of the rest of the slice from where [`Iter::from_slice_with_base`]
+    /// counts, or 0 without a base.
+    #[inline]
+    fn base_offset(&self) -> usize {
+        self.origin.map_or(0, |origin| {
+            (self.inner.as_ptr() as usize).wrapping_sub(origin)
+        })
     }
 
     #[inline]
-    fn renumerate(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
-        self.enumerate().rev()
+    fn scanner(&self) -> Scanner {
+        Scanner::new(self.delim, self.escape).with_run(self.run)
     }
 
+    // `self.inner` always starts at the beginning of the slice or at an unescaped delimiter,
+    // so no leading delimiter can be escaped: an escaped one is preceded by an ESCAPE, which
+    // stops the skip.
+    #[inline]
     fn find_bow(&self) -> usize {
-        let mut it = self.enumerate().skip_while(|&(_, c)| c == self.delim);
-
-        if let Some((i, _)) = it.next() {
-            i
-        } else {
-            self.len()
-        }
+        self.scanner()
+            .skip_boundaries(self.inner, Direction::Forward)
     }
 
+    /// Returns the end of the field at the start of the rest of the slice.
+    ///
+    /// Must only be called once the leading delimiters are skipped by `find_bow`, since the
+    /// position returned could be 0 otherwise.
+    #[inline]
     fn find_eow(&self) -> Option<NonZeroUsize> {
         if self.is_empty() {
             return None;
         }
 
-        let mut prev_char = 0u8;
-        for (i, c) in self.enumerate() {
-            if c == self.delim && prev_char != self.escape {
-                // SAFETY: self.inner[0] != DELIM
-                return unsafe { Some(NonZeroUsize::new_unchecked(i)) };
-            }
-
-            prev_char = if c == self.escape && prev_char == self.escape {
-                0
-            } else {
-                c
-            };
-        }
-
-        // SAFETY: self.inner.len() > 0
-        unsafe { Some(NonZeroUsize::new_unchecked(self.len())) }
+        let pos = self.find_delim().unwrap_or(self.len());
+        debug_assert_ne!(pos, 0, "find_eow before find_bow");
+        // SAFETY: self.inner[0] is not an unescaped delimiter after `find_bow`, which uses the
+        // same scanner as `find_delim`, and self.inner.len() > 0
+        unsafe { Some(NonZeroUsize::new_unchecked(pos)) }
     }
 
+    #[inline]
     fn rfind_eow(&self) -> Option<NonZeroUsize> {
-        let mut it = self.renumerate().skip_while(|&(_, c)| c == self.delim);
-
-        if let Some((i, c)) = it.next() {
-            if c == self.escape {
-                // [^ESCAPE] ESCAPE ESCAPE* ESCAPE \t*
-                //             ^              ^
-                //             j              i
-                let last = match it.filter(|&(_, c)| c == self.escape).last() {
-                    Some((j, _)) if !iso_parity(i, j) => i + 1,
-                    _ => i + 2,
-                };
-                // SAFETY: i + 1 > 0
-                unsafe { Some(NonZeroUsize::new_unchecked(last)) }
-            } else {
-                // SAFETY: i + 1 > 0
-                unsafe { Some(NonZeroUsize::new_unchecked(i + 1)) }
-            }
-        } else {
-            None
-        }
+        let trailing = self
+            .scanner()
+            .skip_boundaries(self.inner, Direction::Backward);
+        NonZeroUsize::new(self.len() - trailing)
     }
 
+    #[inline]
     fn rfind_bow(&self) -> usize {
-        let mut delim = 0;
-        let mut delim_found = false;
-        let mut broken = false;
-
-        for (i, c) in self.renumerate() {
-            if delim_found && c != self.escape {
-                // [^ESCAPE] ESCAPE* DELIM
-                //     ^               ^
-                //     i             delim
-                if iso_parity(i, delim) {
-                    // # of ESCAPE's is odd
-                    delim_found = false;
-                } else {
-                    // # of ESCAPE's is even
-                    broken = true;
-                    break;
-                }
-            }
-            if c == self.delim {
-                delim_found = true;
-                delim = i;
-            }
-        }
+        self.rfind_delim().map_or(0, |i| i + 1)
+    }
 
-        if delim_found && (broken || iso_parity(delim, 0)) {
-            delim + 1
-        } else {
-            0
-        }
+    /// Returns the position of the first unescaped delimiter, without skipping leading ones.
+    #[inline]
+    fn find_delim(&self) -> Option<usize> {
+        self.scanner().find_boundary(self.inner, Direction::Forward)
     }
-}
 
-#[inline]
-fn iso_parity(i: usize, j: usize) -> bool {
-    (i & 1) == (j & 1)
+    /// Returns the position of the last unescaped delimiter.
+    #[inline]
+    fn rfind_delim(&self) -> Option<usize> {
+        self.scanner()
+            .find_boundary(self.inner, Direction::Backward)
+    }
 }
 
 impl<'a> Iter<'a> {
@@ -199,13 +350,271 @@ impl<'a> Iter<'a> {
         self.inner
     }
 
+    /// Same as [`Iter::as_slice`], but consumes the iterator, to hand the rest of the slice over
+    /// to the next stage.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"GET /a\\ b HTTP/1.1", b' ', b'\\');
+    /// it.next();
+    /// assert_eq!(it.into_inner(), &b" /a\\ b HTTP/1.1"[..]);
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// Returns `true` if the next field from the front and the next field from the back do not
+    /// overlap.
+    ///
+    /// They are either the same field or separated by at least one delimiter. Since `next` and
+    /// `next_back` only cut the slice at unescaped delimiters, this always holds, however the two
+    /// ends are interleaved.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s = b"a b\\\\\\ c d";
+    /// let mut it = iter(s, b' ', b'\\');
+    /// assert!(it.remaining_is_valid());
+    /// it.next();
+    /// it.next_back();
+    /// assert!(it.remaining_is_valid());
+    /// assert_eq!(it.next_back(), Some(&b"b\\\\\\ c"[..]));
+    /// assert_eq!(it.next(), None);
+    /// assert!(it.remaining_is_valid());
+    /// ```
+    pub fn remaining_is_valid(&self) -> bool {
+        let offset = |word: &[u8]| word.as_ptr() as usize - self.inner.as_ptr() as usize;
+        let (mut front, mut back) = (*self, *self);
+
+        match (front.next(), back.next_back()) {
+            (None, None) => true,
+            (Some(front), Some(back)) => {
+                let (front_start, back_start) = (offset(front), offset(back));
+                if front_start == back_start {
+                    front.len() == back.len()
+                } else {
+                    front_start + front.len() < back_start
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Splits the iterator into one over the first `n` fields and one over the rest.
+    ///
+    /// The delimiter right after the `n`-th field belongs to neither of them.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let s = b"a b\\ c d";
+    /// let (head, tail) = iter(s, b' ', b'\\').split_at_field(2);
+    /// assert_eq!(head.as_slice(), &b"a b\\ c"[..]);
+    /// assert_eq!(tail.as_slice(), &b"d"[..]);
+    /// ```
+    pub fn split_at_field(self, n: usize) -> (Self, Self) {
+        if n == 0 {
+            return (Self { inner: &[], ..self }, self);
+        }
+
+        let mut tail = self;
+        for _ in 0..n {
+            if tail.next().is_none() {
+                break;
+            }
+        }
+
+        let mid = self.len() - tail.len();
+        let head = Self {
+            inner: &self.inner[..mid],
+            ..self
+        };
+        tail.inner = tail.inner.get(1..).unwrap_or_default();
+
+        (head, tail)
+    }
+
     /// See the examples at the top of this doc page.
+    ///
+    /// If `delim` and `escape` are the same byte, nothing is escaped and every occurrence of it
+    /// is a delimiter.
+    ///
+    /// ```
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let s_vec: Vec<_> = Iter::from_slice(b"a  b ", b' ', b' ').collect();
+    /// assert_eq!(s_vec, &[&b"a"[..], &b"b"[..]]);
+    /// ```
     #[inline]
     pub fn from_slice(inner: &'a [u8], delim: u8, escape: u8) -> Self {
         Self {
             inner,
             delim,
             escape,
+            specials: None,
+            run: EscapeRun::Parity,
+            origin: None,
+        }
+    }
+
+    /// Same as [`Iter::from_slice`], but the offsets reported by [`field_ranges`],
+    /// [`located`], [`strict`], [`lenient_with_warnings`] and [`byte_roles`] are counted from
+    /// `base_offset` bytes before the start of `inner`, as if it were split within the buffer
+    /// it was cut from.
+    ///
+    /// Unlike otherwise, the offsets are then also the same whichever fields have been consumed.
+    ///
+    /// [`field_ranges`]: Iter::field_ranges
+    /// [`located`]: Iter::located
+    /// [`strict`]: Iter::strict
+    /// [`lenient_with_warnings`]: Iter::lenient_with_warnings
+    /// [`byte_roles`]: Iter::byte_roles
+    ///
+    /// ```
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let doc = b"key=a b\\ c d";
+    /// let mut it = Iter::from_slice_with_base(&doc[4..], 4, b' ', b'\\');
+    /// it.next();
+    /// let v: Vec<_> = it.field_ranges().collect();
+    /// assert_eq!(v, &[6..10, 11..12]);
+    /// assert_eq!(&doc[6..10], b"b\\ c");
+    /// ```
+    #[inline]
+    pub fn from_slice_with_base(
+        inner: &'a [u8],
+        base_offset: usize,
+        delim: u8,
+        escape: u8,
+    ) -> Self {
+        Self {
+            origin: Some((inner.as_ptr() as usize).wrapping_sub(base_offset)),
+            ..Self::from_slice(inner, delim, escape)
+        }
+    }
+
+    /// Same as [`Iter::from_slice`], but the escape character only escapes the delimiter,
+    /// itself, and the bytes in `specials`.
+    ///
+    /// An escape character followed by any other byte is content, which
+    /// [`unescaped`](Iter::unescaped) keeps as is.
+    ///
+    /// ```
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let s = b"a\\# \\b\\  c";
+    /// let s_vec: Vec<_> = Iter::with_specials(s, b' ', b'\\', b"#").unescaped().collect();
+    /// assert_eq!(s_vec, &[&b"a#"[..], &b"\\b "[..], &b"c"[..]]);
+    /// ```
+    #[inline]
+    pub fn with_specials(inner: &'a [u8], delim: u8, escape: u8, specials: &'a [u8]) -> Self {
+        Self {
+            inner,
+            delim,
+            escape,
+            specials: Some(specials),
+            run: EscapeRun::Parity,
+            origin: None,
+        }
+    }
+
+    /// Same as [`Iter::from_slice`], but splits the bytes of a C string, without the
+    /// terminating NUL.
+    ///
+    /// ```
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let s = c"a\\ b c";
+    /// let s_vec: Vec<_> = Iter::from_cstr(s, b' ', b'\\').collect();
+    /// assert_eq!(s_vec, &[&b"a\\ b"[..], &b"c"[..]]);
+    /// ```
+    #[inline]
+    pub fn from_cstr(cstr: &'a CStr, delim: u8, escape: u8) -> Self {
+        Self::from_slice(cstr.to_bytes(), delim, escape)
+    }
+
+    /// Same as [`Iter::from_slice`], but splits the bytes of an OS string, such as a `PATH`.
+    ///
+    /// The bytes are those of [`OsStr::as_encoded_bytes`]. On Unix, they are the bytes of the
+    /// string as is. Elsewhere, they are an unspecified superset of UTF-8, in which an ASCII
+    /// byte is always that character, so an ASCII delimiter and escape character split the
+    /// string at the same places.
+    ///
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let s = OsStr::new("/usr/bin:/opt/a\\:b");
+    /// let s_vec: Vec<_> = Iter::from_os_str(s, b':', b'\\').collect();
+    /// assert_eq!(s_vec, &[&b"/usr/bin"[..], &b"/opt/a\\:b"[..]]);
+    /// ```
+    #[inline]
+    pub fn from_os_str(os_str: &'a OsStr, delim: u8, escape: u8) -> Self {
+        Self::from_slice(os_str.as_encoded_bytes(), delim, escape)
+    }
+
+    /// Sets how a run of escape characters before a delimiter is interpreted. Defaults to
+    /// [`EscapeRun::Parity`].
+    #[inline]
+    pub fn escape_run(self, run: EscapeRun) -> Self {
+        Self { run, ..self }
+    }
+
+    /// Returns whether the escape character makes `c` literal.
+    #[inline]
+    fn is_special(&self, c: u8) -> bool {
+        c == self.delim || c == self.escape || self.specials.is_none_or(|s| s.contains(&c))
+    }
+
+    /// Returns whether `c` may be escaped without the escape being redundant: the delimiter, the
+    /// escape character, and the bytes given to [`Iter::with_specials`].
+    #[inline]
+    fn needs_escape(&self, c: u8) -> bool {
+        c == self.delim || c == self.escape || self.specials.is_some_and(|s| s.contains(&c))
+    }
+
+    /// Same as `next`, but also returns the number of escape characters which unescaping the
+    /// field removes, counted in the scan which finds the end of the field.
+    #[inline]
+    fn next_counting(&mut self) -> Option<(&'a [u8], usize)> {
+        let pos = self.find_bow();
+        self.inner = &self.inner[pos..];
+        if self.is_empty() {
+            return None;
+        }
+
+        let (pos, count) =
+            self.scanner()
+                .find_boundary_counting(self.inner, Direction::Forward, |c| self.is_special(c));
+        let (field, rest) = self.inner.split_at(pos.unwrap_or(self.len()));
+        self.inner = rest;
+        Some((field, count))
+    }
+
+    /// Same as `next_back`, but also returns the number of escape characters which unescaping
+    /// the field removes, counted in the scan which finds the start of the field.
+    #[inline]
+    fn next_back_counting(&mut self) -> Option<(&'a [u8], usize)> {
+        let pos = self.rfind_eow()?.get();
+        self.inner = &self.inner[..pos];
+
+        let (pos, count) =
+            self.scanner()
+                .find_boundary_counting(self.inner, Direction::Backward, |c| self.is_special(c));
+        let (rest, field) = self.inner.split_at(pos.map_or(0, |i| i + 1));
+        self.inner = rest;
+        Some((field, count))
+    }
+
+    #[inline]
+    fn unescape(&self, field: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.specials.is_some() {
+            unescape_with(field, self.escape, |c| self.is_special(c))
+        } else {
+            unescape(field, self.escape)
         }
     }
 }
@@ -214,6 +623,9 @@ impl<'a> Iter<'a> {
 mod test {
     use super::*;
 
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
     #[test]
     fn test_words() {
         let s = b"";
@@ -296,5 +708,537 @@ mod test {
         assert_eq!(words.next_back(), Some(&b"h"[..]));
         assert_eq!(words.next(), None);
         assert_eq!(words.next_back(), None);
+
+        let s = b"aXbYXcXd";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.next_back(), Some(&b"d"[..]));
+        assert!(words.remaining_is_valid());
+        assert_eq!(words.next_back(), Some(&b"bYXc"[..]));
+        assert!(words.remaining_is_valid());
+        assert_eq!(words.next(), None);
+        assert_eq!(words.next_back(), None);
+
+        let s = b"aYYYXXYXbYY";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        assert_eq!(words.next_back(), Some(&b"YXbYY"[..]));
+        assert_eq!(words.next(), Some(&b"aYYYX"[..]));
+        assert!(words.remaining_is_valid());
+        assert_eq!(words.next_back(), None);
+        assert_eq!(words.next(), None);
+
+        let s = b"aY";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        assert_eq!(words.next_back(), Some(&b"aY"[..]));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_leading_escaped_delim() {
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (b"YX", &[b"YX"]),
+            (b"YXa", &[b"YXa"]),
+            (b"YXXa", &[b"YX", b"a"]),
+            (b"XYXa", &[b"YXa"]),
+            (b"XXYXXa", &[b"YX", b"a"]),
+            (b"YYXa", &[b"YY", b"a"]),
+            (b"YYYXa", &[b"YYYXa"]),
+            (b"aXYXb", &[b"a", b"YXb"]),
+            (b"aXYXXYXb", &[b"a", b"YX", b"YXb"]),
+        ];
+
+        for &(s, expected) in cases {
+            let words: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            assert_eq!(words, expected);
+
+            let mut words: Vec<_> = Iter::from_slice(s, b'X', b'Y').rev().collect();
+            words.reverse();
+            assert_eq!(words, expected);
+
+            // An escape character of 0 is no different.
+            let zero = |s: &[u8]| -> Vec<u8> {
+                s.iter().map(|&c| if c == b'Y' { 0 } else { c }).collect()
+            };
+            let s = zero(s);
+            let expected: Vec<_> = expected.iter().map(|f| zero(f)).collect();
+            let words: Vec<_> = Iter::from_slice(&s, b'X', 0).collect();
+            assert_eq!(words, expected);
+            let unescaped: Vec<_> = Iter::from_slice(&s, b'X', 0).unescaped().collect();
+            let expected: Vec<_> = expected.iter().map(|f| unescape(f, 0)).collect();
+            assert_eq!(unescaped, expected);
+        }
+    }
+
+    #[test]
+    fn test_doc_examples_unescape() {
+        // The examples of the crate documentation, with the logical content of each field.
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (
+                b"The quick brown fox",
+                &[b"The", b"quick", b"brown", b"fox"],
+            ),
+            (
+                b"a\\ b\\\\ c\\\\\\ d\\\\\\\\ e",
+                &[b"a b\\", b"c\\ d\\\\", b"e"],
+            ),
+            (b"\\ a b", &[b" a", b"b"]),
+        ];
+        for &(s, expected) in cases {
+            let it = Iter::from_slice(s, b' ', b'\\');
+            let fields: Vec<_> = it.map(|f| unescape(f, b'\\')).collect();
+            assert_eq!(fields, expected, "{:?}", s);
+            assert!(it.unescaped().eq(fields.iter().cloned()));
+            assert!(it.unescaped().rev().eq(fields.iter().rev().cloned()));
+
+            // Escaping the content again gives fields which split the same way.
+            let joined = join(expected, b' ', b'\\');
+            assert!(Iter::from_slice(&joined, b' ', b'\\')
+                .unescaped()
+                .eq(expected.iter().map(|&f| Cow::Borrowed(f))));
+        }
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let s = b"XaYXbXXcYYXdX";
+        let mut it = Iter::from_slice(s, b'X', b'Y');
+        assert_eq!(it.into_inner(), s);
+        for expected in [&b"XXcYYXdX"[..], b"XdX", b"X"] {
+            it.next();
+            assert_eq!(it.into_inner(), expected);
+        }
+
+        let mut it = Iter::from_slice(s, b'X', b'Y');
+        it.next_back();
+        assert_eq!(it.into_inner(), b"XaYXbXXcYYX");
+    }
+
+    #[test]
+    fn test_single_byte() {
+        for run in [EscapeRun::Parity, EscapeRun::Sticky] {
+            for (s, expected) in [(b"X", None), (b"Y", Some(b"Y")), (b"a", Some(b"a"))] {
+                let s: &[u8] = s;
+                let expected: Vec<&[u8]> = expected.iter().map(|f| &f[..]).collect();
+                let it = Iter::from_slice(s, b'X', b'Y').escape_run(run);
+                assert_eq!(it.collect::<Vec<_>>(), expected, "{:?}", s);
+                assert_eq!(it.rev().collect::<Vec<_>>(), expected, "{:?}", s);
+                assert_eq!(
+                    it.unescaped().rev().collect::<Vec<_>>(),
+                    expected,
+                    "{:?}",
+                    s
+                );
+                assert_eq!(it.keep_empty().count(), if s == b"X" { 2 } else { 1 });
+
+                let it = Iter::with_specials(s, b'X', b'Y', b"a").escape_run(run);
+                assert_eq!(it.collect::<Vec<_>>(), expected, "{:?}", s);
+                assert_eq!(it.rev().collect::<Vec<_>>(), expected, "{:?}", s);
+
+                // A field next to the byte, from either end, is still split off.
+                for t in [[s, b"Xb"].concat(), [&b"bX"[..], s].concat()] {
+                    let it = Iter::from_slice(&t, b'X', b'Y').escape_run(run);
+                    let words: Vec<_> = it.collect();
+                    let mut rwords: Vec<_> = it.rev().collect();
+                    rwords.reverse();
+                    assert_eq!(words, rwords, "{:?}", t);
+                }
+            }
+
+            // When the delimiter is the escape character, it is only ever a delimiter.
+            let mut it = Iter::from_slice(b"X", b'X', b'X').escape_run(run);
+            assert_eq!(it.next_back(), None);
+            assert_eq!(it.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let escaping: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[]),
+            (b"X", &[]),
+            (b"Y", &[b"Y"]),
+            (b"XXXX", &[]),
+            (b"YYYY", &[b"YYYY"]),
+            (b"YYYYY", &[b"YYYYY"]),
+            (b"YYYX", &[b"YYYX"]),
+            (b"YYYYX", &[b"YYYY"]),
+            (b"XYYYX", &[b"YYYX"]),
+        ];
+        let same: &[(&[u8], &[&[u8]])] = &[
+            (b"X", &[]),
+            (b"XX", &[]),
+            (b"XXX", &[]),
+            (b"aXXbX", &[b"a", b"b"]),
+            (b"XaXbXXc", &[b"a", b"b", b"c"]),
+        ];
+
+        let cases = escaping
+            .iter()
+            .map(|&(s, expected)| (s, b'Y', expected))
+            .chain(same.iter().map(|&(s, expected)| (s, b'X', expected)));
+        for (s, escape, expected) in cases {
+            let delim = b'X';
+            let words: Vec<_> = Iter::from_slice(s, delim, escape).collect();
+            assert_eq!(words, expected, "{:?}", s);
+
+            let mut words: Vec<_> = Iter::from_slice(s, delim, escape).rev().collect();
+            words.reverse();
+            assert_eq!(words, expected, "{:?}", s);
+
+            let words: Vec<_> = Iter::from_slice(s, delim, escape).unescaped().collect();
+            let unescaped: Vec<_> = expected.iter().map(|w| unescape(w, escape)).collect();
+            assert_eq!(words, unescaped, "{:?}", s);
+        }
+
+        // An escape character equal to the delimiter never escapes, whatever the order.
+        for s in strings(b"aX", 8) {
+            let expected: Vec<_> = s.split(|&c| c == b'X').filter(|w| !w.is_empty()).collect();
+            let words: Vec<_> = Iter::from_slice(&s, b'X', b'X').collect();
+            assert_eq!(words, expected);
+
+            let mut words: Vec<_> = Iter::from_slice(&s, b'X', b'X').rev().collect();
+            words.reverse();
+            assert_eq!(words, expected);
+        }
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut it = Iter::from_slice(b"aXbYXYcXd", b'X', b'Y');
+        it.next();
+        assert_eq!(
+            format!("{:?}", it),
+            "Iter { delim: 88, escape: 89, specials: None, run: Parity, inner: [88, 98, 89, 88, 89, 99, 88, 100] }"
+        );
+        assert_eq!(format!("{:#?}", it), "[\n    \"bXc\",\n    \"d\",\n]");
+
+        let it = Iter::from_slice(b"XX", b'X', b'Y');
+        assert_eq!(format!("{:#?}", it), "[]");
+        let it = Iter::from_slice(b"a\xffb", b'X', b'Y');
+        assert_eq!(format!("{:#?}", it), "[\n    \"a\u{fffd}b\",\n]");
+    }
+
+    #[test]
+    fn test_escape_run() {
+        let parity: &[(&[u8], &[&[u8]])] = &[
+            (b"aYYX b", &[b"aYY", b" b"]),
+            (b"aYYYX b", &[b"aYYYX b"]),
+            (b"aYX b", &[b"aYX b"]),
+            (b"aX b", &[b"a", b" b"]),
+            (b"XYYXaYYYYXXb", &[b"YY", b"aYYYY", b"b"]),
+        ];
+        let sticky: &[(&[u8], &[&[u8]])] = &[
+            (b"aYYX b", &[b"aYYX b"]),
+            (b"aYYYX b", &[b"aYYYX b"]),
+            (b"aYX b", &[b"aYX b"]),
+            (b"aX b", &[b"a", b" b"]),
+            (b"XYYXaYYYYXXb", &[b"YYXaYYYYX", b"b"]),
+        ];
+
+        for (run, cases) in [(EscapeRun::Parity, parity), (EscapeRun::Sticky, sticky)] {
+            for &(s, expected) in cases {
+                let it = Iter::from_slice(s, b'X', b'Y').escape_run(run);
+                assert_eq!(it.collect::<Vec<_>>(), expected, "{:?}", s);
+
+                let mut words: Vec<_> = it.rev().collect();
+                words.reverse();
+                assert_eq!(words, expected, "{:?}", s);
+            }
+        }
+
+        // With sticky runs, a delimiter splits iff it is not right after an escape character.
+        for s in crate::test::strings(b"aXY", 7) {
+            let mut expected = vec![vec![]];
+            for (i, &c) in s.iter().enumerate() {
+                if c == b'X' && (i == 0 || s[i - 1] != b'Y') {
+                    expected.push(vec![]);
+                } else {
+                    expected.last_mut().unwrap().push(c);
+                }
+            }
+            expected.retain(|f| !f.is_empty());
+
+            let it = Iter::from_slice(&s, b'X', b'Y').escape_run(EscapeRun::Sticky);
+            assert_eq!(it.collect::<Vec<_>>(), expected, "{:?}", s);
+            let mut words: Vec<_> = it.rev().collect();
+            words.reverse();
+            assert_eq!(words, expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_from_cstr() {
+        let s = c"XaYXbXXcYYXdY";
+        let words: Vec<_> = Iter::from_cstr(s, b'X', b'Y').collect();
+        assert_eq!(words, &[&b"aYXb"[..], b"cYY", b"dY"]);
+        let words: Vec<_> = Iter::from_cstr(s, b'X', b'Y').unescaped().collect();
+        assert_eq!(words, &[&b"aXb"[..], b"cY", b"dY"]);
+
+        assert_eq!(Iter::from_cstr(c"", b'X', b'Y').next(), None);
+    }
+
+    #[test]
+    fn test_from_slice_with_base() {
+        let doc = b"ignoredXaYXbXcYYXdYaXeY";
+        let (base, s) = (7, &doc[7..]);
+        let ranges_of = |it: Iter| -> Vec<_> { it.field_ranges().collect() };
+        let mut it = Iter::from_slice_with_base(s, base, b'X', b'Y');
+        let mut relative = Iter::from_slice(s, b'X', b'Y');
+        loop {
+            // Unlike those of `relative`, the offsets stay relative to `doc`.
+            let consumed = s.len() - relative.len();
+            let ranges: Vec<_> = relative
+                .field_ranges()
+                .map(|r| r.start + base + consumed..r.end + base + consumed)
+                .collect();
+            assert_eq!(ranges_of(it), ranges);
+            assert!(ranges_of(it).into_iter().map(|r| &doc[r]).eq(it));
+
+            let located: Vec<_> = it.located().map(|(l, _)| l.offset).collect();
+            assert_eq!(located, ranges.iter().map(|r| r.start).collect::<Vec<_>>());
+            let roles: Vec<_> = it.byte_roles().map(|(i, c, _)| (i, c)).collect();
+            assert!(roles.iter().all(|&(i, c)| doc[i] == c));
+            assert_eq!(
+                roles.first().map(|r| r.0),
+                Some(base + consumed).filter(|_| !it.is_empty())
+            );
+
+            let errors: Vec<_> = it
+                .strict()
+                .filter_map(Result::err)
+                .map(|e| e.offset)
+                .collect();
+            let trailing = ranges.last().map(|r| r.end - 1);
+            assert_eq!(errors, trailing.into_iter().collect::<Vec<_>>());
+            let mut warnings = Vec::new();
+            it.lenient_with_warnings(&mut warnings).for_each(drop);
+            assert!(
+                warnings.iter().all(|w| doc[w.offset] == b'Y'),
+                "{:?}",
+                warnings
+            );
+            assert_eq!(ranges_of(it.to_owned_remaining().iter()), ranges);
+
+            relative.next();
+            if it.next().is_none() {
+                break;
+            }
+        }
+
+        // Without a base, the offsets are relative to the rest of the slice.
+        let mut it = Iter::from_slice(s, b'X', b'Y');
+        it.next();
+        assert_eq!(ranges_of(it), &[1..4, 5..8, 9..11]);
+    }
+
+    #[test]
+    fn test_from_os_str() {
+        let s = OsStr::new("XaYXbXXcYYX\u{e9}Y");
+        let words: Vec<_> = Iter::from_os_str(s, b'X', b'Y').collect();
+        assert_eq!(words, &[&b"aYXb"[..], b"cYY", "\u{e9}Y".as_bytes()]);
+
+        let path = std::path::Path::new("/binX/opt/aYXbX");
+        let words: Vec<_> = Iter::from_os_str(path.as_os_str(), b'X', b'Y').collect();
+        assert_eq!(words, &[&b"/bin"[..], b"/opt/aYXb"]);
+
+        assert_eq!(Iter::from_os_str(OsStr::new(""), b'X', b'Y').next(), None);
+    }
+
+    #[test]
+    fn test_with_specials() {
+        let s = b"aYZbXYcXYXdXYYeZ";
+        let mut words = Iter::with_specials(s, b'X', b'Y', b"Z");
+        assert_eq!(words.next(), Some(&b"aYZb"[..]));
+        assert_eq!(words.next(), Some(&b"Yc"[..]));
+        assert_eq!(words.next(), Some(&b"YXd"[..]));
+        assert_eq!(words.next(), Some(&b"YYeZ"[..]));
+        assert_eq!(words.next(), None);
+
+        let mut words = Iter::with_specials(s, b'X', b'Y', b"Z").unescaped();
+        assert_eq!(words.next().as_deref(), Some(&b"aZb"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"Yc"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"Xd"[..]));
+        assert_eq!(words.next().as_deref(), Some(&b"YeZ"[..]));
+        assert_eq!(words.next(), None);
+
+        let words: Vec<_> = Iter::with_specials(s, b'X', b'Y', b"Z").rev().collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').rev().collect();
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_split_at_field() {
+        let words = Iter::from_slice(b"aXXbYXcXd", b'X', b'Y');
+        let (head, tail) = words.split_at_field(1);
+        assert_eq!(head.as_slice(), &b"a"[..]);
+        assert_eq!(tail.as_slice(), &b"XbYXcXd"[..]);
+
+        let (head, tail) = words.split_at_field(0);
+        assert_eq!(head.as_slice(), &b""[..]);
+        assert_eq!(tail, words);
+
+        let (head, tail) = words.split_at_field(5);
+        assert_eq!(head, words);
+        assert_eq!(tail.as_slice(), &b""[..]);
+
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"abc",
+            b"XXabXYXcdeXYfYXXYYYXgYYX",
+            b"abcXdefXXhX jklmX",
+        ];
+        for s in inputs {
+            let mut words = Iter::from_slice(s, b'X', b'Y');
+            words.next();
+            let expected: Vec<_> = words.collect();
+
+            for n in 0..=expected.len() + 1 {
+                let (head, tail) = words.split_at_field(n);
+                let head: Vec<_> = head.collect();
+                let tail: Vec<_> = tail.collect();
+                assert_eq!(head.len(), n.min(expected.len()));
+                assert_eq!([head, tail].concat(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_words_mixed_exhaustive() {
+        // Each bit of `pattern` chooses `next` (0) or `next_back` (1).
+        for s in strings(b"aXY", 8) {
+            let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y').collect();
+
+            for pattern in 0..16u32 {
+                let mut words = Iter::from_slice(&s, b'X', b'Y');
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                for i in 0.. {
+                    assert!(words.remaining_is_valid());
+                    let word = if pattern >> (i % 4) & 1 == 0 {
+                        words.next().map(|w| front.push(w))
+                    } else {
+                        words.next_back().map(|w| back.push(w))
+                    };
+                    if word.is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(words.next(), None);
+                assert_eq!(words.next_back(), None);
+
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, expected, "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_words_rev_exhaustive() {
+        for s in strings(b"aXY", 10) {
+            let words: Vec<_> = Iter::from_slice(&s, b'X', b'Y').collect();
+            let rwords: Vec<_> = Iter::from_slice(&s, b'X', b'Y').rev().collect();
+            assert!(words.into_iter().rev().eq(rwords), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_size_hint() {
+        for s in strings(b"aXY", 8) {
+            check_size_hint(Iter::from_slice(&s, b'X', b'Y'));
+            check_size_hint(Iter::from_slice(&s, b'X', b'Y').rev());
+        }
+
+        let it = Iter::from_slice(b"XaXbX", b'X', b'Y');
+        assert_eq!(it.size_hint(), (0, Some(3)));
+        let it = Iter::from_slice(b"aXbXc", b'X', b'Y');
+        assert_eq!(it.size_hint(), (1, Some(3)));
+    }
+
+    /// Lists of at least one field, any of which may be empty, made mostly of `delim` and
+    /// `escape`.
+    fn arb_fields() -> impl Strategy<Value = (Vec<Vec<u8>>, u8, u8)> {
+        (any::<u8>(), any::<u8>())
+            .prop_filter("delim == escape", |(d, e)| d != e)
+            .prop_flat_map(|(delim, escape)| {
+                let byte = prop_oneof![Just(delim), Just(escape), any::<u8>()];
+                (vec(vec(byte, 0..4), 1..8), Just(delim), Just(escape))
+            })
+    }
+
+    /// Slices which mostly consist of `delim` and `escape`, so that long runs are common.
+    fn arb_slice() -> impl Strategy<Value = (Vec<u8>, u8, u8)> {
+        (any::<u8>(), any::<u8>())
+            .prop_filter("delim == escape", |(d, e)| d != e)
+            .prop_flat_map(|(delim, escape)| {
+                let byte = prop_oneof![Just(delim), Just(escape), any::<u8>()];
+                (vec(byte, 0..32), Just(delim), Just(escape))
+            })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1024))]
+
+        #[test]
+        fn prop_rev((s, delim, escape) in arb_slice()) {
+            let words: Vec<_> = Iter::from_slice(&s, delim, escape).collect();
+            let mut rwords: Vec<_> = Iter::from_slice(&s, delim, escape).rev().collect();
+            rwords.reverse();
+            prop_assert_eq!(words, rwords);
+        }
+
+        #[test]
+        fn prop_join((s, delim, escape) in arb_slice()) {
+            let fields: Vec<_> = Iter::from_slice(&s, delim, escape).unescaped().collect();
+            let fields_ref: Vec<_> = fields.iter().map(|f| &f[..]).collect();
+            let joined = join(&fields_ref, delim, escape);
+            let rejoined: Vec<_> = Iter::from_slice(&joined, delim, escape).unescaped().collect();
+            prop_assert_eq!(fields, rejoined);
+        }
+
+        #[test]
+        fn prop_join_keep_empty((fields, delim, escape) in arb_fields()) {
+            let fields_ref: Vec<_> = fields.iter().map(|f| &f[..]).collect();
+            let joined = join(&fields_ref, delim, escape);
+            let it = Iter::from_slice(&joined, delim, escape).keep_empty();
+            let split: Vec<_> = it.map(|f| unescape(f, escape)).collect();
+            prop_assert_eq!(split, fields);
+        }
+
+        #[test]
+        fn prop_count((s, delim, escape) in arb_slice()) {
+            let it = Iter::from_slice(&s, delim, escape);
+            prop_assert_eq!(it.count(), it.collect::<Vec<_>>().len());
+        }
+    }
+
+    /// Checks that `it` yields no more than its hints say, then nothing after `None`.
+    pub(crate) fn check_size_hint<I: FusedIterator + Clone>(mut it: I) {
+        loop {
+            let (lower, upper) = it.size_hint();
+            let count = it.clone().count();
+            assert!(lower <= count);
+            assert!(upper.is_some_and(|upper| count <= upper));
+            if it.next().is_none() {
+                break;
+            }
+        }
+        assert!(it.next().is_none());
+        assert_eq!(it.size_hint().0, 0);
+    }
+
+    /// Returns every slice of at most `max_len` bytes of `alphabet`.
+    pub(crate) fn strings(alphabet: &[u8], max_len: u32) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let n = alphabet.len();
+        (0..=max_len).flat_map(move |len| {
+            (0..n.pow(len)).map(move |mut k| {
+                (0..len)
+                    .map(|_| {
+                        let c = alphabet[k % n];
+                        k /= n;
+                        c
+                    })
+                    .collect()
+            })
+        })
     }
 }
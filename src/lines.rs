@@ -0,0 +1,271 @@
+use std::borrow::Cow;
+
+use crate::scanner::is_unescaped;
+use crate::{split_terminator, KeepEmpty};
+
+/// Returns an iterator of the lines of the slice, split on unescaped `\n`.
+///
+/// As in [`str::lines`], a trailing `\n` does not start an empty line, but unlike it, `\r` is
+/// kept unless [`Lines::normalize_crlf`] is set. An escaped `\n` is part of the line; see
+/// [`Lines::join_continuations`] to remove it.
+///
+/// ```
+/// use escaped_delimiter::lines;
+///
+/// let s_vec: Vec<_> = lines(b"a\nb\\\nc\n\nd\n", b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a"[..], &b"b\\\nc"[..], &b""[..], &b"d"[..]]);
+/// ```
+#[inline]
+pub fn lines(slice: &[u8], escape: u8) -> Lines<'_> {
+    Lines {
+        escape,
+        join_continuations: false,
+        normalize_crlf: false,
+        slice,
+        inner: split_terminator(slice, b'\n', escape),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lines<'a> {
+    escape: u8,
+    join_continuations: bool,
+    normalize_crlf: bool,
+    /// The whole slice, to be split again by [`Lines::continuation`].
+    slice: &'a [u8],
+    inner: KeepEmpty<'a>,
+}
+
+impl<'a> Lines<'a> {
+    /// Sets whether an escaped `\n` continues the line, so that the escape character and the `\n`
+    /// are removed and the physical lines are joined into one.
+    ///
+    /// No other escape character is removed.
+    ///
+    /// ```
+    /// use escaped_delimiter::lines;
+    ///
+    /// let s_vec: Vec<_> = lines(b"a \\\nb\\ \\\nc\nd", b'\\').join_continuations(true).collect();
+    /// assert_eq!(s_vec, &[&b"a b\\ c"[..], &b"d"[..]]);
+    /// ```
+    #[inline]
+    pub fn join_continuations(self, join_continuations: bool) -> Self {
+        Self {
+            join_continuations,
+            ..self
+        }
+    }
+
+    /// Sets the byte which escapes `\n`, and `\r` for [`Lines::normalize_crlf`], instead of the
+    /// escape character, so that the lines may be continued with another byte than the one
+    /// escaping the fields in them.
+    ///
+    /// The escape character then has no effect on the lines; it is left to split each line into
+    /// fields. The continuation byte also escapes itself, so that a line can end with it.
+    ///
+    /// The slice is split anew from its start, so this is to be set before taking any line.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, lines};
+    ///
+    /// let s = b"a,b^\\\nc\\\\\nd^\n";
+    /// let v: Vec<_> = lines(s, b'^').continuation(b'\\').join_continuations(true).collect();
+    /// assert_eq!(v, &[&b"a,b^c\\\\"[..], &b"d^"[..]]);
+    /// assert_eq!(iter(&v[0], b',', b'^').unescaped().nth(1).unwrap(), &b"bc\\\\"[..]);
+    /// ```
+    #[inline]
+    pub fn continuation(self, continuation: u8) -> Self {
+        Self {
+            escape: continuation,
+            inner: split_terminator(self.slice, b'\n', continuation),
+            ..self
+        }
+    }
+
+    /// Sets whether a single unescaped `\r` at the end of each line is removed, so that `\r\n`
+    /// and `\n` end the lines alike.
+    ///
+    /// An escaped `\r` is kept with its escape character.
+    ///
+    /// ```
+    /// use escaped_delimiter::lines;
+    ///
+    /// let s_vec: Vec<_> = lines(b"a\r\nb\nc\\\r\nd\r", b'\\').normalize_crlf(true).collect();
+    /// assert_eq!(s_vec, &[&b"a"[..], &b"b"[..], &b"c\\\r"[..], &b"d"[..]]);
+    /// ```
+    #[inline]
+    pub fn normalize_crlf(self, normalize_crlf: bool) -> Self {
+        Self {
+            normalize_crlf,
+            ..self
+        }
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    fn line(&self, mut line: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.normalize_crlf {
+            if let Some((b'\r', rest)) = line.split_last() {
+                if is_unescaped(line, rest.len(), self.escape) {
+                    line = rest;
+                }
+            }
+        }
+
+        if self.join_continuations {
+            strip_continuations(line, self.escape)
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Cow<'a, [u8]>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next()?;
+        Some(self.line(line))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Lines<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next_back()?;
+        Some(self.line(line))
+    }
+}
+
+/// Removes each escaped `\n` together with its escape character.
+fn strip_continuations(line: &[u8], escape: u8) -> Cow<'_, [u8]> {
+    if escape == b'\n' {
+        return Cow::Borrowed(line);
+    }
+
+    let mut out: Option<Vec<u8>> = None;
+    let mut start = 0;
+    for i in (0..line.len()).filter(|&i| line[i] == b'\n') {
+        if !is_unescaped(line, i, escape) {
+            out.get_or_insert_with(Vec::new)
+                .extend_from_slice(&line[start..i - 1]);
+            start = i + 1;
+        }
+    }
+
+    match out {
+        Some(mut out) => {
+            out.extend_from_slice(&line[start..]);
+            Cow::Owned(out)
+        }
+        None => Cow::Borrowed(line),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let s = b"aYX\nbY\nc\n\nYY\nd\nY";
+        let expected = [&b"aYX"[..], b"bY\nc", b"", b"YY", b"d", b"Y"];
+        let v: Vec<_> = lines(s, b'Y').collect();
+        assert_eq!(v, expected);
+        let v: Vec<_> = lines(s, b'Y').rev().collect();
+        assert!(v.into_iter().eq(expected.into_iter().rev()));
+
+        assert_eq!(lines(b"", b'Y').next(), None);
+        assert_eq!(lines(b"\n", b'Y').collect::<Vec<_>>(), &[&b""[..]]);
+    }
+
+    #[test]
+    fn test_join_continuations() {
+        let s = b"first Y\nsecondYY Y\nthirdYX\nnext\nYYY\nY";
+        let v: Vec<_> = lines(s, b'Y').join_continuations(true).collect();
+        assert_eq!(v, &[&b"first secondYY thirdYX"[..], b"next", b"YYY"]);
+        assert!(matches!(v[1], Cow::Borrowed(_)));
+
+        let mut rv: Vec<_> = lines(s, b'Y').join_continuations(true).rev().collect();
+        rv.reverse();
+        assert_eq!(rv, v);
+
+        // A trailing continuation leaves the line open.
+        let v: Vec<_> = lines(b"aY\n", b'Y').join_continuations(true).collect();
+        assert_eq!(v, &[&b"a"[..]]);
+        let v: Vec<_> = lines(b"aYYY", b'Y').join_continuations(true).collect();
+        assert_eq!(v, &[&b"aYYY"[..]]);
+    }
+
+    #[test]
+    fn test_continuation() {
+        let s = b"aY,b,\\\ncYY\nd,Y\ne\\\\\nfY\\\n";
+        let v: Vec<_> = lines(s, b'Y')
+            .continuation(b'\\')
+            .join_continuations(true)
+            .collect();
+        assert_eq!(v, &[&b"aY,b,cYY"[..], b"d,Y", b"e\\\\", b"fY"]);
+        let mut rv: Vec<_> = lines(s, b'Y')
+            .continuation(b'\\')
+            .join_continuations(true)
+            .rev()
+            .collect();
+        rv.reverse();
+        assert_eq!(rv, v);
+
+        // The fields are split and unescaped with the escape character only.
+        let fields: Vec<Vec<_>> = v
+            .iter()
+            .map(|line| crate::Iter::from_slice(line, b',', b'Y').to_owned_fields())
+            .collect();
+        assert_eq!(
+            fields,
+            &[
+                &[&b"a,b"[..], b"cY"][..],
+                &[b"d", b"Y"],
+                &[b"e\\\\"],
+                &[b"fY"],
+            ]
+        );
+
+        // Without it, the escape character continues the lines instead.
+        let v: Vec<_> = lines(s, b'Y').join_continuations(true).collect();
+        assert_eq!(v, &[&b"aY,b,\\"[..], b"cYY", b"d,e\\\\", b"fY\\"]);
+    }
+
+    #[test]
+    fn test_normalize_crlf() {
+        let s = b"a\r\nb\nc\r\r\n\r\n\nYY\r\ndY\r\neYYY\r\nf\r";
+        let expected = [
+            &b"a"[..],
+            b"b",
+            b"c\r",
+            b"",
+            b"",
+            b"YY",
+            b"dY\r",
+            b"eYYY\r",
+            b"f",
+        ];
+        let v: Vec<_> = lines(s, b'Y').normalize_crlf(true).collect();
+        assert_eq!(v, expected);
+        let v: Vec<_> = lines(s, b'Y').normalize_crlf(true).rev().collect();
+        assert!(v.into_iter().eq(expected.into_iter().rev()));
+
+        // Off by default.
+        let v: Vec<_> = lines(b"a\r\nb", b'Y').collect();
+        assert_eq!(v, &[&b"a\r"[..], b"b"]);
+
+        // With continuations, the `\r` is removed only at the end of the joined line.
+        let v: Vec<_> = lines(b"aY\nb\r\nc\r", b'Y')
+            .normalize_crlf(true)
+            .join_continuations(true)
+            .collect();
+        assert_eq!(v, &[&b"ab"[..], b"c"]);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::Iter;
+
+/// Where a field starts, yielded by [`Iter::located`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    /// The line number, counted from 1.
+    pub line: usize,
+    /// The column in bytes, counted from 1.
+    pub col: usize,
+    /// The offset from the start of the slice, relative to the rest of the slice at the time of
+    /// [`Iter::located`], or to the base of [`Iter::from_slice_with_base`].
+    pub offset: usize,
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the fields and where each starts.
+    ///
+    /// Every `\n` starts a new line, whether it is escaped or not, and whatever the delimiter
+    /// is. Lines and columns are relative to the rest of the slice at the time of this call.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, Location};
+    ///
+    /// let v: Vec<_> = iter(b"a b\nc\\\nd e", b' ', b'\\').located().collect();
+    /// assert_eq!(v, &[
+    ///     (Location { line: 1, col: 1, offset: 0 }, &b"a"[..]),
+    ///     (Location { line: 1, col: 3, offset: 2 }, &b"b\nc\\\nd"[..]),
+    ///     (Location { line: 3, col: 3, offset: 9 }, &b"e"[..]),
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn located(self) -> Located<'a> {
+        Located {
+            base: self.base_offset(),
+            slice: self.inner,
+            last: Location {
+                line: 1,
+                col: 1,
+                offset: 0,
+            },
+            inner: self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Located<'a> {
+    base: usize,
+    slice: &'a [u8],
+    last: Location,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for Located<'a> {
+    type Item = (Location, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        let offset = field.as_ptr() as usize - self.slice.as_ptr() as usize;
+
+        // Only the bytes since the last field are counted.
+        let Location { mut line, col, .. } = self.last;
+        let scanned = &self.slice[self.last.offset..offset];
+        let col = match scanned.iter().rposition(|&c| c == b'\n') {
+            Some(i) => {
+                line += scanned.iter().filter(|&&c| c == b'\n').count();
+                scanned.len() - i
+            }
+            None => col + scanned.len(),
+        };
+
+        self.last = Location { line, col, offset };
+        let location = Location {
+            offset: self.base + offset,
+            ..self.last
+        };
+        Some((location, field))
+    }
+}
+
+impl<'a> Located<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_located() {
+        let s = b"\nXaXbc\nY\nXXd\n\nYXeXf\n\n";
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .located()
+            .map(|(loc, field)| ((loc.line, loc.col, loc.offset), field))
+            .collect();
+        assert_eq!(
+            v,
+            &[
+                ((1, 1, 0), &b"\n"[..]),
+                ((2, 2, 2), b"a"),
+                ((2, 4, 4), b"bc\nY\n"),
+                ((4, 3, 11), b"d\n\nYXe"),
+                ((6, 5, 18), b"f\n\n"),
+            ]
+        );
+
+        // Every location is the same as counted from the start.
+        for (loc, _) in Iter::from_slice(s, b'X', b'Y').located() {
+            let head = &s[..loc.offset];
+            let line_start = head.iter().rposition(|&c| c == b'\n').map_or(0, |i| i + 1);
+            assert_eq!(loc.line, 1 + head.iter().filter(|&&c| c == b'\n').count());
+            assert_eq!(loc.col, 1 + loc.offset - line_start);
+        }
+
+        let mut it = Iter::from_slice(b"aXb", b'X', b'Y');
+        it.next();
+        let mut it = it.located();
+        assert_eq!(
+            it.next(),
+            Some((
+                Location {
+                    line: 1,
+                    col: 2,
+                    offset: 1
+                },
+                &b"b"[..]
+            ))
+        );
+        assert_eq!(it.next(), None);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::scanner::find_unescaped;
+
+/// Returns an iterator of the fields separated by the unescaped delimiters which are outside of
+/// any brackets, as in the arguments `f(a, g(b, c)), d`.
+///
+/// An escape character makes a bracket literal as well as a delimiter. Empty fields are skipped
+/// as in [`iter`](crate::iter). The iterator is not double-ended, since unbalanced brackets read
+/// differently from the back.
+///
+/// A closing bracket with no opening one is literal. An opening bracket which is never closed
+/// makes the rest of the slice a single field, after which [`Nested::depth`] is not zero.
+///
+/// # Panics
+///
+/// Panics if `open` and `close` are equal, or if either is the delimiter or the escape character.
+///
+/// ```
+/// use escaped_delimiter::split_nested;
+///
+/// let s_vec: Vec<_> = split_nested(b"f(a,g(b,c)),d\\(,e", b',', b'\\', b'(', b')').collect();
+/// assert_eq!(s_vec, &[&b"f(a,g(b,c))"[..], &b"d\\("[..], &b"e"[..]]);
+/// ```
+pub fn split_nested(slice: &[u8], delim: u8, escape: u8, open: u8, close: u8) -> Nested<'_> {
+    assert_ne!(open, close, "the brackets are equal");
+    for bracket in [open, close] {
+        assert!(
+            bracket != delim && bracket != escape,
+            "a bracket is the delimiter or the escape character"
+        );
+    }
+
+    Nested {
+        delim,
+        escape,
+        open,
+        close,
+        depth: 0,
+        inner: slice,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nested<'a> {
+    delim: u8,
+    escape: u8,
+    open: u8,
+    close: u8,
+    depth: usize,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for Nested<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.find_boundary() {
+                Some(i) => {
+                    let field = &self.inner[..i];
+                    self.inner = &self.inner[i + 1..];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> Nested<'a> {
+    /// Returns the position of the first unescaped delimiter outside of the brackets, leaving the
+    /// depth at that point, or at the end of the slice if there is none.
+    fn find_boundary(&mut self) -> Option<usize> {
+        self.depth = 0;
+        let mut start = 0;
+        loop {
+            // None of the bytes searched for is the escape character, so the search can go on
+            // right after one of them on its own.
+            let i = start
+                + find_unescaped(&self.inner[start..], self.escape, |c| {
+                    c == self.delim || c == self.open || c == self.close
+                })?;
+            let c = self.inner[i];
+            if c == self.delim {
+                if self.depth == 0 {
+                    return Some(i);
+                }
+            } else if c == self.open {
+                self.depth += 1;
+            } else {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            start = i + 1;
+        }
+    }
+
+    /// Returns the number of brackets still open at the end of the last field scanned, which is
+    /// not zero only if the slice ends within brackets.
+    ///
+    /// ```
+    /// use escaped_delimiter::split_nested;
+    ///
+    /// let mut it = split_nested(b"a,(b,c", b',', b'\\', b'(', b')');
+    /// assert_eq!(it.next(), Some(&b"a"[..]));
+    /// assert_eq!(it.depth(), 0);
+    /// assert_eq!(it.next(), Some(&b"(b,c"[..]));
+    /// assert_eq!(it.depth(), 1);
+    /// ```
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split(s: &[u8]) -> Vec<&[u8]> {
+        split_nested(s, b'X', b'Y', b'[', b']').collect()
+    }
+
+    #[test]
+    fn test_split_nested() {
+        assert_eq!(
+            split(b"f[aXg[bXc]]XdXXe"),
+            &[&b"f[aXg[bXc]]"[..], b"d", b"e"]
+        );
+        assert_eq!(split(b"[aXb]X[cX[dX]e]"), &[&b"[aXb]"[..], b"[cX[dX]e]"]);
+
+        // Escaped brackets do not nest, and escaped delimiters do not split at any depth.
+        assert_eq!(split(b"aY[XbYXc"), &[&b"aY["[..], b"bYXc"]);
+        assert_eq!(split(b"[aY]XbYXc]Xd"), &[&b"[aY]XbYXc]"[..], b"d"]);
+        assert_eq!(split(b"aYY[XbY]]Xc"), &[&b"aYY[XbY]]"[..], b"c"]);
+
+        // A stray closing bracket is literal.
+        assert_eq!(split(b"a]XbX]]"), &[&b"a]"[..], b"b", b"]]"]);
+
+        // Without brackets, the fields are those of `iter`.
+        for s in crate::test::strings(b"aXY", 8) {
+            let expected: Vec<_> = crate::Iter::from_slice(&s, b'X', b'Y').collect();
+            assert_eq!(split(&s), expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_split_nested_unbalanced() {
+        let mut it = split_nested(b"aX[bX[cX]dXe", b'X', b'Y', b'[', b']');
+        assert_eq!(it.next(), Some(&b"a"[..]));
+        assert_eq!(it.depth(), 0);
+        assert_eq!(it.next(), Some(&b"[bX[cX]dXe"[..]));
+        assert_eq!(it.depth(), 1);
+        assert_eq!(it.as_slice(), b"");
+        assert_eq!(it.next(), None);
+
+        let mut it = split_nested(b"aX]X[b]", b'X', b'Y', b'[', b']');
+        assert_eq!(it.by_ref().count(), 3);
+        assert_eq!(it.depth(), 0);
+    }
+}
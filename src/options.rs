@@ -0,0 +1,490 @@
+use std::borrow::Cow;
+
+use crate::scanner::{Direction, Scanner};
+use crate::{unescape, Iter, KeepEmpty};
+
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// How far an unescaped comment byte of [`SplitOptions::comment`] extends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CommentScope {
+    /// The rest of the field is ignored.
+    #[default]
+    Field,
+    /// The rest of the slice, which is usually a line, is ignored.
+    Input,
+}
+
+/// A set of options to split a slice on.
+///
+/// ```
+/// use escaped_delimiter::{CommentScope, SplitOptions};
+///
+/// let opts = SplitOptions::new(b' ', b'\\').comment(b'#');
+/// let s_vec: Vec<_> = opts.iter(b"a#b \\#c d").collect();
+/// assert_eq!(s_vec, &[&b"a"[..], &b"\\#c"[..], &b"d"[..]]);
+///
+/// let opts = opts.comment_scope(CommentScope::Input);
+/// let s_vec: Vec<_> = opts.iter(b"a#b \\#c d").collect();
+/// assert_eq!(s_vec, &[&b"a"[..]]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SplitOptions {
+    delim: u8,
+    escape: u8,
+    keep_empty: bool,
+    drop_trailing_empty: bool,
+    comment: Option<u8>,
+    comment_scope: CommentScope,
+    skip_bom: bool,
+    null_sentinel: Option<u8>,
+}
+
+impl SplitOptions {
+    /// Returns the options equivalent to [`iter`](crate::iter).
+    #[inline]
+    pub fn new(delim: u8, escape: u8) -> Self {
+        Self {
+            delim,
+            escape,
+            keep_empty: false,
+            drop_trailing_empty: false,
+            comment: None,
+            comment_scope: CommentScope::Field,
+            skip_bom: false,
+            null_sentinel: None,
+        }
+    }
+
+    /// Sets whether empty fields are yielded as in [`Iter::keep_empty`].
+    #[inline]
+    pub fn keep_empty(self, keep_empty: bool) -> Self {
+        Self { keep_empty, ..self }
+    }
+
+    /// Sets whether the empty fields at the end are dropped, while those before any other field
+    /// are still yielded with [`SplitOptions::keep_empty`], as for the ragged rows of a
+    /// spreadsheet.
+    ///
+    /// A field emptied by a comment counts as empty, and if every field is empty, none is
+    /// yielded. Without [`SplitOptions::keep_empty`], this has no effect.
+    ///
+    /// ```
+    /// use escaped_delimiter::SplitOptions;
+    ///
+    /// let opts = SplitOptions::new(b',', b'\\').keep_empty(true).drop_trailing_empty(true);
+    /// let s_vec: Vec<_> = opts.iter(b",a,,b\\,,,").collect();
+    /// assert_eq!(s_vec, &[&b""[..], &b"a"[..], &b""[..], &b"b\\,"[..]]);
+    /// ```
+    #[inline]
+    pub fn drop_trailing_empty(self, drop_trailing_empty: bool) -> Self {
+        Self {
+            drop_trailing_empty,
+            ..self
+        }
+    }
+
+    /// Makes an unescaped `comment` byte start a comment, which is not part of any field.
+    ///
+    /// An escaped comment byte is content. A field which is empty after removing a comment is
+    /// treated as any other empty field.
+    #[inline]
+    pub fn comment(self, comment: u8) -> Self {
+        Self {
+            comment: Some(comment),
+            ..self
+        }
+    }
+
+    /// Sets how far a comment extends. Defaults to [`CommentScope::Field`].
+    #[inline]
+    pub fn comment_scope(self, comment_scope: CommentScope) -> Self {
+        Self {
+            comment_scope,
+            ..self
+        }
+    }
+
+    /// Sets whether a UTF-8 BOM (`EF BB BF`) at the start of the slice is skipped.
+    ///
+    /// Only a leading BOM is skipped; one anywhere else is content. The fields still borrow
+    /// from the original slice, so their offsets in it are unchanged.
+    ///
+    /// ```
+    /// use escaped_delimiter::SplitOptions;
+    ///
+    /// let opts = SplitOptions::new(b' ', b'\\').skip_bom(true);
+    /// let s_vec: Vec<_> = opts.iter(b"\xef\xbb\xbfa b").collect();
+    /// assert_eq!(s_vec, &[&b"a"[..], &b"b"[..]]);
+    /// ```
+    #[inline]
+    pub fn skip_bom(self, skip_bom: bool) -> Self {
+        Self { skip_bom, ..self }
+    }
+
+    /// Makes a field which consists only of an unescaped `sentinel` byte a null field, which
+    /// [`SplitOptionsIter::fields_nullable`] yields as `None`.
+    ///
+    /// An escaped sentinel, or one with other bytes in the field, is content.
+    #[inline]
+    pub fn null_sentinel(self, sentinel: u8) -> Self {
+        Self {
+            null_sentinel: Some(sentinel),
+            ..self
+        }
+    }
+
+    /// Returns an iterator of the fields of `slice`.
+    pub fn iter(self, slice: &[u8]) -> SplitOptionsIter<'_> {
+        let slice = match slice.strip_prefix(BOM) {
+            Some(rest) if self.skip_bom => rest,
+            _ => slice,
+        };
+        let slice = match (self.comment, self.comment_scope) {
+            (Some(comment), CommentScope::Input) => self.strip_comment(slice, comment),
+            _ => slice,
+        };
+
+        let inner = Iter::from_slice(slice, self.delim, self.escape);
+        let fields = if self.keep_empty {
+            let mut fields = inner.keep_empty();
+            if self.drop_trailing_empty {
+                self.drop_trailing(&mut fields);
+            }
+            Fields::KeepEmpty(fields)
+        } else {
+            Fields::Collapse(inner)
+        };
+
+        SplitOptionsIter { opts: self, fields }
+    }
+
+    /// Returns a function splitting a slice with these options, to be passed to `map` for
+    /// example.
+    ///
+    /// It is `Copy` and only calls [`SplitOptions::iter`].
+    ///
+    /// ```
+    /// use escaped_delimiter::SplitOptions;
+    ///
+    /// let splitter = SplitOptions::new(b',', b'\\').keep_empty(true).splitter();
+    /// let lines: [&[u8]; 2] = [b"a,\\,b", b",c"];
+    /// let rows: Vec<Vec<_>> = lines.into_iter().map(splitter).map(Iterator::collect).collect();
+    /// assert_eq!(rows, &[&[&b"a"[..], &b"\\,b"[..]][..], &[&b""[..], &b"c"[..]]]);
+    /// ```
+    #[inline]
+    pub fn splitter(self) -> impl Fn(&[u8]) -> SplitOptionsIter<'_> + Copy {
+        move |slice| self.iter(slice)
+    }
+
+    /// Consumes the empty fields at the back of `fields`.
+    fn drop_trailing(&self, fields: &mut KeepEmpty<'_>) {
+        let mut rest = *fields;
+        while rest
+            .next_back()
+            .is_some_and(|field| self.field(field).is_empty())
+        {
+            *fields = rest;
+        }
+    }
+
+    /// Truncates `slice` at the first comment byte which is not escaped.
+    ///
+    /// `slice` must start at the beginning of a field.
+    fn strip_comment<'a>(&self, slice: &'a [u8], comment: u8) -> &'a [u8] {
+        let scanner = Scanner::new(comment, self.escape);
+        match scanner.find_boundary(slice, Direction::Forward) {
+            Some(i) => &slice[..i],
+            None => slice,
+        }
+    }
+
+    fn field<'a>(&self, field: &'a [u8]) -> &'a [u8] {
+        match (self.comment, self.comment_scope) {
+            (Some(comment), CommentScope::Field) => self.strip_comment(field, comment),
+            _ => field,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fields<'a> {
+    Collapse(Iter<'a>),
+    KeepEmpty(KeepEmpty<'a>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitOptionsIter<'a> {
+    opts: SplitOptions,
+    fields: Fields<'a>,
+}
+
+impl<'a> Iterator for SplitOptionsIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let field = match &mut self.fields {
+                Fields::Collapse(it) => it.next()?,
+                Fields::KeepEmpty(it) => it.next()?,
+            };
+
+            let field = self.opts.field(field);
+            if self.opts.keep_empty || !field.is_empty() {
+                return Some(field);
+            }
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for SplitOptionsIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let field = match &mut self.fields {
+                Fields::Collapse(it) => it.next_back()?,
+                Fields::KeepEmpty(it) => it.next_back()?,
+            };
+
+            let field = self.opts.field(field);
+            if self.opts.keep_empty || !field.is_empty() {
+                return Some(field);
+            }
+        }
+    }
+}
+
+impl<'a> SplitOptionsIter<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        match &self.fields {
+            Fields::Collapse(it) => it.as_slice(),
+            Fields::KeepEmpty(it) => it.as_slice(),
+        }
+    }
+
+    /// Returns an iterator of the unescaped fields, with `None` for the null fields of
+    /// [`SplitOptions::null_sentinel`].
+    ///
+    /// ```
+    /// use escaped_delimiter::SplitOptions;
+    ///
+    /// let opts = SplitOptions::new(b',', b'\\').keep_empty(true).null_sentinel(b'-');
+    /// let s_vec: Vec<_> = opts.iter(b"a,-,\\-,").fields_nullable().collect();
+    /// assert_eq!(s_vec, &[Some(b"a".into()), None, Some(b"-".into()), Some(b"".into())]);
+    /// ```
+    #[inline]
+    pub fn fields_nullable(self) -> NullableFields<'a> {
+        NullableFields { inner: self }
+    }
+
+    #[inline]
+    fn nullable(&self, field: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        match self.opts.null_sentinel {
+            Some(sentinel) if field == [sentinel] => None,
+            _ => Some(unescape(field, self.opts.escape)),
+        }
+    }
+
+    /// Same as [`Iter::to_owned_fields`], but with the options.
+    pub fn to_owned_fields(self) -> Vec<Vec<u8>> {
+        let escape = self.opts.escape;
+        self.map(|field| unescape(field, escape).into_owned())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullableFields<'a> {
+    inner: SplitOptionsIter<'a>,
+}
+
+impl<'a> Iterator for NullableFields<'a> {
+    type Item = Option<Cow<'a, [u8]>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        Some(self.inner.nullable(field))
+    }
+}
+
+impl<'a> DoubleEndedIterator for NullableFields<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        Some(self.inner.nullable(field))
+    }
+}
+
+impl<'a> NullableFields<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_options_default() {
+        let s = b"XXabXYXcXXdYYXX";
+        let words: Vec<_> = SplitOptions::new(b'X', b'Y').iter(s).collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+        assert_eq!(words, expected);
+
+        let words: Vec<_> = SplitOptions::new(b'X', b'Y')
+            .keep_empty(true)
+            .iter(s)
+            .rev()
+            .collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().rev().collect();
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_skip_bom() {
+        let offsets = |opts: SplitOptions, s: &[u8]| -> Vec<_> {
+            opts.iter(s)
+                .map(|w| (w.as_ptr() as usize - s.as_ptr() as usize, w.len()))
+                .collect()
+        };
+
+        let opts = SplitOptions::new(b'X', b'Y').skip_bom(true);
+        assert_eq!(offsets(opts, b"\xef\xbb\xbfaXbc"), &[(3, 1), (5, 2)]);
+        assert_eq!(offsets(opts, b"\xef\xbb\xbfXaX"), &[(4, 1)]);
+        assert_eq!(offsets(opts, b"aXbc"), &[(0, 1), (2, 2)]);
+        assert_eq!(offsets(opts, b"\xef\xbb\xbf"), &[]);
+        assert_eq!(offsets(opts.keep_empty(true), b"\xef\xbb\xbf"), &[(3, 0)]);
+
+        // Neither an escaped nor a non-leading BOM is skipped.
+        assert_eq!(offsets(opts, b"Y\xef\xbb\xbfa"), &[(0, 5)]);
+        assert_eq!(offsets(opts, b"aX\xef\xbb\xbfb"), &[(0, 1), (2, 4)]);
+        assert_eq!(offsets(opts, b"\xef\xbb\xbf\xef\xbb\xbfa"), &[(3, 4)]);
+
+        let opts = opts.skip_bom(false);
+        assert_eq!(offsets(opts, b"\xef\xbb\xbfaXb"), &[(0, 4), (5, 1)]);
+    }
+
+    #[test]
+    fn test_null_sentinel() {
+        let s = b"ZXaZXYZXZZXYYZXZYXZXZ";
+        let opts = SplitOptions::new(b'X', b'Y').null_sentinel(b'Z');
+        let fields: Vec<_> = opts.iter(s).fields_nullable().collect();
+        let expected: &[Option<&[u8]>] = &[
+            None,
+            Some(b"aZ"),
+            Some(b"Z"),
+            Some(b"ZZ"),
+            Some(b"YZ"),
+            Some(b"ZXZ"),
+            None,
+        ];
+        assert_eq!(fields.len(), expected.len());
+        for (field, expected) in fields.iter().zip(expected) {
+            assert_eq!(field.as_deref(), *expected);
+        }
+
+        let mut rfields: Vec<_> = opts.iter(s).fields_nullable().rev().collect();
+        rfields.reverse();
+        assert_eq!(rfields, fields);
+
+        // Without a sentinel, no field is null.
+        let fields = SplitOptions::new(b'X', b'Y').iter(s).fields_nullable();
+        assert!(fields.clone().all(|f| f.is_some()));
+        assert_eq!(fields.count(), expected.len());
+    }
+
+    #[test]
+    fn test_comment() {
+        let s = b"aZbXYZcXZdXeYYZfXg";
+        let opts = SplitOptions::new(b'X', b'Y').comment(b'Z');
+        let words: Vec<_> = opts.iter(s).collect();
+        assert_eq!(words, &[&b"a"[..], &b"YZc"[..], &b"eYY"[..], &b"g"[..]]);
+        let mut rwords: Vec<_> = opts.iter(s).rev().collect();
+        rwords.reverse();
+        assert_eq!(words, rwords);
+
+        let words: Vec<_> = opts.keep_empty(true).iter(s).collect();
+        assert_eq!(
+            words,
+            &[&b"a"[..], &b"YZc"[..], &b""[..], &b"eYY"[..], &b"g"[..]]
+        );
+
+        let opts = opts.comment_scope(CommentScope::Input);
+        let words: Vec<_> = opts.iter(s).collect();
+        assert_eq!(words, &[&b"a"[..]]);
+        let words: Vec<_> = opts.iter(b"YZaXbZcXd").collect();
+        assert_eq!(words, &[&b"YZa"[..], &b"b"[..]]);
+        let words: Vec<_> = opts.keep_empty(true).iter(b"Za").collect();
+        assert_eq!(words, &[&b""[..]]);
+
+        let words = opts.keep_empty(true).iter(b"aYZbXYXZc");
+        assert_eq!(words.to_owned_fields(), &[&b"aZb"[..], &b"X"[..]]);
+    }
+
+    #[test]
+    fn test_drop_trailing_empty() {
+        let opts = SplitOptions::new(b',', b'Y').keep_empty(true);
+        let dropping = opts.drop_trailing_empty(true);
+        let words: Vec<_> = dropping.iter(b"a,,b,,").collect();
+        assert_eq!(words, &[&b"a"[..], b"", b"b"]);
+        let words: Vec<_> = dropping.iter(b"a,,b,,").rev().collect();
+        assert_eq!(words, &[&b"b"[..], b"", b"a"]);
+        assert_eq!(dropping.iter(b"a,,b,,").as_slice(), b"a,,b");
+
+        for s in crate::test::strings(b"a,Y", 7) {
+            let mut expected: Vec<_> = opts.iter(&s).collect();
+            while expected.last().is_some_and(|f| f.is_empty()) {
+                expected.pop();
+            }
+            assert_eq!(dropping.iter(&s).collect::<Vec<_>>(), expected, "{:?}", s);
+            let mut rwords: Vec<_> = dropping.iter(&s).rev().collect();
+            rwords.reverse();
+            assert_eq!(rwords, expected, "{:?}", s);
+        }
+
+        // A field emptied by a comment is dropped as well.
+        let words: Vec<_> = dropping.comment(b'#').iter(b",a,#b,").collect();
+        assert_eq!(words, &[&b""[..], b"a"]);
+
+        // Without keep_empty, nothing changes.
+        let words: Vec<_> = SplitOptions::new(b',', b'Y')
+            .drop_trailing_empty(true)
+            .iter(b",a,,b,")
+            .collect();
+        assert_eq!(words, &[&b"a"[..], b"b"]);
+    }
+
+    #[test]
+    fn test_splitter() {
+        let opts = SplitOptions::new(b',', b'Y')
+            .keep_empty(true)
+            .drop_trailing_empty(true)
+            .comment(b'#')
+            .skip_bom(true);
+        let splitter = opts.splitter();
+        let lines: Vec<&[u8]> = vec![b"\xef\xbb\xbfa,,b,", b"#a,Y#b,,", b"", b",aY,b#c,d,,"];
+        let rows: Vec<Vec<_>> = lines
+            .iter()
+            .copied()
+            .map(splitter)
+            .map(Iterator::collect)
+            .collect();
+        assert_eq!(
+            rows,
+            &[
+                &[&b"a"[..], b"", b"b"][..],
+                &[b"", b"Y#b"],
+                &[],
+                &[b"", b"aY,b", b"d"],
+            ]
+        );
+        for line in lines {
+            assert!(splitter(line).eq(opts.iter(line)));
+        }
+    }
+}
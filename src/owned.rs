@@ -0,0 +1,95 @@
+use crate::{EscapeRun, Iter};
+
+impl Iter<'_> {
+    /// Copies the rest of the slice into a buffer of its own, so that the remaining fields can
+    /// be split elsewhere, for example on another thread.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"a b\\ c d", b' ', b'\\');
+    /// assert_eq!(it.next(), Some(&b"a"[..]));
+    ///
+    /// let rest = it.to_owned_remaining();
+    /// let fields = std::thread::spawn(move || rest.iter().map(<[u8]>::to_vec).collect::<Vec<_>>());
+    /// assert_eq!(fields.join().unwrap(), &[&b"b\\ c"[..], &b"d"[..]]);
+    /// ```
+    pub fn to_owned_remaining(&self) -> OwnedSplit {
+        OwnedSplit {
+            delim: self.delim,
+            escape: self.escape,
+            specials: self.specials.map(<[u8]>::to_vec),
+            run: self.run,
+            base: self.origin.map(|_| self.base_offset()),
+            buf: self.inner.to_vec(),
+        }
+    }
+}
+
+/// An owned slice together with how to split it, returned by [`Iter::to_owned_remaining`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSplit {
+    delim: u8,
+    escape: u8,
+    specials: Option<Vec<u8>>,
+    run: EscapeRun,
+    base: Option<usize>,
+    buf: Vec<u8>,
+}
+
+impl OwnedSplit {
+    /// Returns an iterator of the fields of the buffer, split as the original iterator would have
+    /// split them.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            delim: self.delim,
+            escape: self.escape,
+            specials: self.specials.as_deref(),
+            run: self.run,
+            origin: self
+                .base
+                .map(|base| (self.buf.as_ptr() as usize).wrapping_sub(base)),
+            inner: &self.buf,
+        }
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the rest of the inner slice as the buffer it was copied into.
+    #[inline]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_owned_remaining() {
+        for s in crate::test::strings(b"aXY", 7) {
+            let mut it = Iter::from_slice(&s, b'X', b'Y');
+            loop {
+                let rest = it.to_owned_remaining();
+                assert_eq!(rest.as_slice(), it.as_slice());
+                assert!(rest.iter().eq(it), "{:?}", s);
+                assert!(rest.iter().rev().eq(it.rev()), "{:?}", s);
+                if it.next().is_none() {
+                    break;
+                }
+            }
+        }
+
+        let mut it = Iter::with_specials(b"aXYbXYXc", b'X', b'Y', b"X");
+        it.next();
+        let rest = it.to_owned_remaining();
+        assert_eq!(rest.iter().collect::<Vec<_>>(), &[&b"Yb"[..], b"YXc"]);
+        assert_eq!(rest.into_inner(), b"XYbXYXc");
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::scanner::{Direction, Scanner};
+use crate::{unescape, Iter};
+
+/// Returns an iterator of `key=value` pairs.
+///
+/// Each field is split at the first unescaped `kv_delim`. A field without `kv_delim` yields
+/// `None` as its value.
+///
+/// ```
+/// use escaped_delimiter::pairs;
+///
+/// let s = b"a=1;b\\==2;c";
+/// let v: Vec<_> = pairs(s, b';', b'=', b'\\').collect();
+/// assert_eq!(v, &[
+///     (&b"a"[..], Some(&b"1"[..])),
+///     (&b"b\\="[..], Some(&b"2"[..])),
+///     (&b"c"[..], None),
+/// ]);
+/// ```
+pub fn pairs(slice: &[u8], field_delim: u8, kv_delim: u8, escape: u8) -> Pairs<'_> {
+    Pairs {
+        kv_delim,
+        fields: Iter::from_slice(slice, field_delim, escape),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pairs<'a> {
+    kv_delim: u8,
+    fields: Iter<'a>,
+}
+
+impl<'a> Iterator for Pairs<'a> {
+    type Item = (&'a [u8], Option<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.fields.next()?;
+        Some(self.split(field))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Pairs<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.fields.next_back()?;
+        Some(self.split(field))
+    }
+}
+
+impl<'a> Pairs<'a> {
+    fn split(&self, field: &'a [u8]) -> (&'a [u8], Option<&'a [u8]>) {
+        let scanner = Scanner::new(self.kv_delim, self.fields.escape);
+        match scanner.find_boundary(field, Direction::Forward) {
+            Some(i) => (&field[..i], Some(&field[i + 1..])),
+            None => (field, None),
+        }
+    }
+}
+
+/// Collects unescaped `key=value` pairs into a `HashMap`.
+///
+/// If a key appears more than once, the last value wins. A field without `kv_delim` is inserted
+/// with an empty value; use [`try_to_map`] to reject such fields.
+///
+/// ```
+/// use escaped_delimiter::to_map;
+///
+/// let m = to_map(b"a=1;b=2;a=3", b';', b'=', b'\\');
+/// assert_eq!(m.len(), 2);
+/// assert_eq!(m[&b"a"[..]], b"3");
+/// assert_eq!(m[&b"b"[..]], b"2");
+/// ```
+pub fn to_map(
+    slice: &[u8],
+    field_delim: u8,
+    kv_delim: u8,
+    escape: u8,
+) -> HashMap<Vec<u8>, Vec<u8>> {
+    pairs(slice, field_delim, kv_delim, escape)
+        .map(|(k, v)| {
+            let v = v.map_or(Vec::new(), |v| unescape(v, escape).into_owned());
+            (unescape(k, escape).into_owned(), v)
+        })
+        .collect()
+}
+
+/// Collects unescaped `key=value` pairs into a `Vec`, keeping their order and duplicate keys.
+///
+/// A field without `kv_delim` yields `None` as its value.
+///
+/// ```
+/// use escaped_delimiter::to_vec_pairs;
+///
+/// let v = to_vec_pairs(b"a=1;b\\;=2;a=3;c", b';', b'=', b'\\');
+/// assert_eq!(v, &[
+///     (b"a".to_vec(), Some(b"1".to_vec())),
+///     (b"b;".to_vec(), Some(b"2".to_vec())),
+///     (b"a".to_vec(), Some(b"3".to_vec())),
+///     (b"c".to_vec(), None),
+/// ]);
+/// ```
+pub fn to_vec_pairs(
+    slice: &[u8],
+    field_delim: u8,
+    kv_delim: u8,
+    escape: u8,
+) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+    pairs(slice, field_delim, kv_delim, escape)
+        .map(|(k, v)| {
+            let v = v.map(|v| unescape(v, escape).into_owned());
+            (unescape(k, escape).into_owned(), v)
+        })
+        .collect()
+}
+
+/// Same as [`to_map`], but fails on a field without `kv_delim`.
+///
+/// ```
+/// use escaped_delimiter::try_to_map;
+///
+/// assert!(try_to_map(b"a=1;b=", b';', b'=', b'\\').is_ok());
+///
+/// let err = try_to_map(b"a=1;b", b';', b'=', b'\\').unwrap_err();
+/// assert_eq!(err.index, 1);
+/// ```
+pub fn try_to_map(
+    slice: &[u8],
+    field_delim: u8,
+    kv_delim: u8,
+    escape: u8,
+) -> Result<HashMap<Vec<u8>, Vec<u8>>, MissingKvDelim> {
+    pairs(slice, field_delim, kv_delim, escape)
+        .enumerate()
+        .map(|(index, (k, v))| {
+            let v = v.ok_or(MissingKvDelim { index })?;
+            Ok((
+                unescape(k, escape).into_owned(),
+                unescape(v, escape).into_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// The error returned by [`try_to_map`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingKvDelim {
+    /// The index of the field without the key-value delimiter.
+    pub index: usize,
+}
+
+impl fmt::Display for MissingKvDelim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field {} has no key-value delimiter", self.index)
+    }
+}
+
+impl Error for MissingKvDelim {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pairs() {
+        let s = b"aZ1XbYZZ2XXcXdZXeYYZ3";
+        let mut it = pairs(s, b'X', b'Z', b'Y');
+        assert_eq!(it.next(), Some((&b"a"[..], Some(&b"1"[..]))));
+        assert_eq!(it.next(), Some((&b"bYZ"[..], Some(&b"2"[..]))));
+        assert_eq!(it.next(), Some((&b"c"[..], None)));
+        assert_eq!(it.next(), Some((&b"d"[..], Some(&b""[..]))));
+        assert_eq!(it.next(), Some((&b"eYY"[..], Some(&b"3"[..]))));
+        assert_eq!(it.next(), None);
+
+        let s = b"aZ1XbZ2";
+        let mut it = pairs(s, b'X', b'Z', b'Y').rev();
+        assert_eq!(it.next(), Some((&b"b"[..], Some(&b"2"[..]))));
+        assert_eq!(it.next(), Some((&b"a"[..], Some(&b"1"[..]))));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_pairs_leading_escape() {
+        let v: Vec<_> = pairs(b"YZaZ1XYYZ2XZ3", b'X', b'Z', b'Y').collect();
+        assert_eq!(
+            v,
+            &[
+                (&b"YZa"[..], Some(&b"1"[..])),
+                (b"YY", Some(b"2")),
+                (b"", Some(b"3")),
+            ]
+        );
+
+        // An escape character of 0 is no different.
+        let v: Vec<_> = pairs(b"Z1X\0Za\0\0Z2X\0\0\0ZZ3", b'X', b'Z', 0).collect();
+        assert_eq!(
+            v,
+            &[
+                (&b""[..], Some(&b"1"[..])),
+                (b"\0Za\0\0", Some(b"2")),
+                (b"\0\0\0Z", Some(b"3")),
+            ]
+        );
+        let m = to_map(b"\0\0Z1X\0ZbZ2", b'X', b'Z', 0);
+        assert_eq!(m[&b"\0"[..]], b"1");
+        assert_eq!(m[&b"Zb"[..]], b"2");
+    }
+
+    #[test]
+    fn test_to_map() {
+        let m = to_map(b"aZ1XbYZZ2XaZ3XcXdZ", b'X', b'Z', b'Y');
+        assert_eq!(m.len(), 4);
+        assert_eq!(m[&b"a"[..]], b"3");
+        assert_eq!(m[&b"bZ"[..]], b"2");
+        assert_eq!(m[&b"c"[..]], b"");
+        assert_eq!(m[&b"d"[..]], b"");
+    }
+
+    #[test]
+    fn test_to_vec_pairs() {
+        let v = to_vec_pairs(b"aZ1XbYZZ2XaZ3XcXdZXaZ1", b'X', b'Z', b'Y');
+        assert_eq!(
+            v,
+            &[
+                (b"a".to_vec(), Some(b"1".to_vec())),
+                (b"bZ".to_vec(), Some(b"2".to_vec())),
+                (b"a".to_vec(), Some(b"3".to_vec())),
+                (b"c".to_vec(), None),
+                (b"d".to_vec(), Some(Vec::new())),
+                (b"a".to_vec(), Some(b"1".to_vec())),
+            ]
+        );
+
+        assert!(to_vec_pairs(b"", b'X', b'Z', b'Y').is_empty());
+    }
+
+    #[test]
+    fn test_try_to_map() {
+        let m = try_to_map(b"aZ1XbYZZ2XaZ3XdZ", b'X', b'Z', b'Y').unwrap();
+        assert_eq!(m.len(), 3);
+        assert_eq!(m[&b"a"[..]], b"3");
+        assert_eq!(m[&b"bZ"[..]], b"2");
+        assert_eq!(m[&b"d"[..]], b"");
+
+        let err = try_to_map(b"aZ1XbYZ2XcZ3", b'X', b'Z', b'Y').unwrap_err();
+        assert_eq!(err, MissingKvDelim { index: 1 });
+    }
+}
@@ -0,0 +1,264 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::escape::unescape_into;
+use crate::Iter;
+
+/// Parses the unescaped fields into a tuple, each element with [`FromStr`].
+///
+/// ```
+/// use escaped_delimiter::{parse_fields, FieldsError};
+///
+/// let (id, name): (u32, String) = parse_fields(b"42 John\\ Doe", b' ', b'\\').unwrap();
+/// assert_eq!((id, name.as_str()), (42, "John Doe"));
+///
+/// let err = parse_fields::<(u32, String)>(b"x y", b' ', b'\\').unwrap_err();
+/// assert_eq!(err, FieldsError::Parse { index: 0 });
+/// ```
+pub fn parse_fields<T: FromFields>(slice: &[u8], delim: u8, escape: u8) -> Result<T, FieldsError> {
+    T::from_fields(Iter::from_slice(slice, delim, escape).unescaped())
+}
+
+/// A type which can be built from a fixed number of unescaped fields.
+///
+/// It is implemented for tuples of 2 to 6 elements implementing [`FromStr`].
+pub trait FromFields: Sized {
+    /// Builds the value from all the fields, failing if there are too many or too few.
+    fn from_fields<'a, I: Iterator<Item = Cow<'a, [u8]>>>(fields: I) -> Result<Self, FieldsError>;
+}
+
+/// The error returned by [`parse_fields`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldsError {
+    /// The number of fields does not match.
+    Count {
+        /// The number of fields the type needs.
+        expected: usize,
+        /// The number of fields in the slice.
+        found: usize,
+    },
+    /// The field at `index` is not valid UTF-8 or failed to parse.
+    Parse {
+        /// The index of the field.
+        index: usize,
+    },
+}
+
+impl fmt::Display for FieldsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count { expected, found } => {
+                write!(f, "expected {} fields, found {}", expected, found)
+            }
+            Self::Parse { index } => write!(f, "field {} failed to parse", index),
+        }
+    }
+}
+
+impl Error for FieldsError {}
+
+/// Parses every unescaped field with [`FromStr`], or returns the index and the error of the first
+/// field which fails to parse.
+///
+/// Each field is unescaped into the same buffer. A field which is not valid UTF-8 is parsed with
+/// its invalid sequences replaced by U+FFFD, so it fails to parse as a number.
+///
+/// ```
+/// use escaped_delimiter::parse_numbers;
+///
+/// assert_eq!(parse_numbers::<i32>(b"1,-2,3", b',', b'\\'), Ok(vec![1, -2, 3]));
+/// assert_eq!(parse_numbers::<f64>(b"1.5,1\\,5", b',', b'\\').unwrap_err().0, 1);
+/// ```
+pub fn parse_numbers<T: FromStr>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+) -> Result<Vec<T>, (usize, T::Err)> {
+    let mut buf = Vec::new();
+    Iter::from_slice(slice, delim, escape)
+        .enumerate()
+        .map(|(i, field)| {
+            buf.clear();
+            unescape_into(field, escape, &mut buf);
+            String::from_utf8_lossy(&buf).parse().map_err(|e| (i, e))
+        })
+        .collect()
+}
+
+/// Returns the raw fields if there are at least `min` and at most `max` of them.
+///
+/// The scan stops at the first field past `max`.
+///
+/// ```
+/// use escaped_delimiter::{parse_bounded, FieldCountError};
+///
+/// let fields = parse_bounded(b"a\\ b c", b' ', b'\\', 1, 2).unwrap();
+/// assert_eq!(fields, &[&b"a\\ b"[..], &b"c"[..]]);
+///
+/// let err = parse_bounded(b"a b c", b' ', b'\\', 1, 2).unwrap_err();
+/// assert_eq!(err, FieldCountError { min: 1, max: 2, found: 3 });
+/// ```
+pub fn parse_bounded(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    min: usize,
+    max: usize,
+) -> Result<Vec<&[u8]>, FieldCountError> {
+    let fields: Vec<_> = Iter::from_slice(slice, delim, escape)
+        .take(max.saturating_add(1))
+        .collect();
+    let found = fields.len();
+    if (min..=max).contains(&found) {
+        Ok(fields)
+    } else {
+        Err(FieldCountError { min, max, found })
+    }
+}
+
+/// The error returned by [`parse_bounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldCountError {
+    /// The least number of fields allowed.
+    pub min: usize,
+    /// The greatest number of fields allowed.
+    pub max: usize,
+    /// The number of fields in the slice, or `max + 1` if there are more than `max`.
+    pub found: usize,
+}
+
+impl fmt::Display for FieldCountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.found > self.max {
+            write!(f, "expected at most {} fields, found more", self.max)
+        } else {
+            write!(
+                f,
+                "expected at least {} fields, found {}",
+                self.min, self.found
+            )
+        }
+    }
+}
+
+impl Error for FieldCountError {}
+
+fn parse_field<T: FromStr>(field: Option<Cow<'_, [u8]>>, index: usize) -> Result<T, FieldsError> {
+    let field = field.ok_or(FieldsError::Parse { index })?;
+    std::str::from_utf8(&field)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(FieldsError::Parse { index })
+}
+
+macro_rules! impl_from_fields {
+    ($n:expr; $($t:ident $i:tt),+) => {
+        impl<$($t: FromStr),+> FromFields for ($($t,)+) {
+            fn from_fields<'a, I: Iterator<Item = Cow<'a, [u8]>>>(
+                fields: I,
+            ) -> Result<Self, FieldsError> {
+                let fields: Vec<_> = fields.collect();
+                if fields.len() != $n {
+                    return Err(FieldsError::Count {
+                        expected: $n,
+                        found: fields.len(),
+                    });
+                }
+
+                let mut fields = fields.into_iter();
+                Ok(($(parse_field::<$t>(fields.next(), $i)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_fields!(2; A 0, B 1);
+impl_from_fields!(3; A 0, B 1, C 2);
+impl_from_fields!(4; A 0, B 1, C 2, D 3);
+impl_from_fields!(5; A 0, B 1, C 2, D 3, E 4);
+impl_from_fields!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields() {
+        let s = b"1XaYXbX-2.5XtrueXc";
+        let v: (u8, String, f64, bool, char) = parse_fields(s, b'X', b'Y').unwrap();
+        assert_eq!(v, (1, "aXb".to_string(), -2.5, true, 'c'));
+
+        let v: (u8, u8) = parse_fields(b"XX1XX2XX", b'X', b'Y').unwrap();
+        assert_eq!(v, (1, 2));
+    }
+
+    #[test]
+    fn test_parse_fields_error() {
+        let err = parse_fields::<(u8, u8, u8)>(b"1X2", b'X', b'Y').unwrap_err();
+        assert_eq!(
+            err,
+            FieldsError::Count {
+                expected: 3,
+                found: 2
+            }
+        );
+
+        let err = parse_fields::<(u8, u8)>(b"1X2X3", b'X', b'Y').unwrap_err();
+        assert_eq!(
+            err,
+            FieldsError::Count {
+                expected: 2,
+                found: 3
+            }
+        );
+
+        let err = parse_fields::<(u8, u8, u8)>(b"1X256X3", b'X', b'Y').unwrap_err();
+        assert_eq!(err, FieldsError::Parse { index: 1 });
+
+        let err = parse_fields::<(String, String)>(b"aX\xff", b'X', b'Y').unwrap_err();
+        assert_eq!(err, FieldsError::Parse { index: 1 });
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(
+            parse_numbers::<u64>(b"X1XX2Y0X300X", b'X', b'Y'),
+            Ok(vec![1, 20, 300])
+        );
+        assert_eq!(parse_numbers::<i8>(b"1X-2Y3", b'X', b'Y'), Ok(vec![1, -23]));
+        assert_eq!(parse_numbers::<u8>(b"", b'X', b'Y'), Ok(vec![]));
+
+        let (index, err) = parse_numbers::<u8>(b"1X2X256X4XaX", b'X', b'Y').unwrap_err();
+        assert_eq!(index, 2);
+        assert_eq!(err, "256".parse::<u8>().unwrap_err());
+        assert_eq!(parse_numbers::<u8>(b"1XYaX2", b'X', b'Y').unwrap_err().0, 1);
+        assert_eq!(parse_numbers::<u8>(b"1X\xff", b'X', b'Y').unwrap_err().0, 1);
+
+        let v = parse_numbers::<String>(b"aYXbXc\xff", b'X', b'Y').unwrap();
+        assert_eq!(v, &["aXb", "c\u{fffd}"]);
+    }
+
+    #[test]
+    fn test_parse_bounded() {
+        let s = b"XaYXbXXcX";
+        let err = |found| FieldCountError {
+            min: 2,
+            max: 3,
+            found,
+        };
+        assert_eq!(
+            parse_bounded(s, b'X', b'Y', 2, 3).unwrap(),
+            &[&b"aYXb"[..], b"c"]
+        );
+        assert_eq!(parse_bounded(b"aXbXc", b'X', b'Y', 2, 3).unwrap().len(), 3);
+        assert_eq!(parse_bounded(b"aYXb", b'X', b'Y', 2, 3), Err(err(1)));
+        assert_eq!(parse_bounded(b"XX", b'X', b'Y', 2, 3), Err(err(0)));
+        assert_eq!(parse_bounded(b"aXbXcXd", b'X', b'Y', 2, 3), Err(err(4)));
+        assert_eq!(parse_bounded(b"aXbXcXdXe", b'X', b'Y', 2, 3), Err(err(4)));
+
+        assert_eq!(parse_bounded(b"", b'X', b'Y', 0, 0), Ok(Vec::new()));
+        assert!(parse_bounded(b"aXb", b'X', b'Y', 0, usize::MAX).is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::escape::unescape_into;
+use crate::Iter;
+
+/// A delimiter and an escape character, with buffers reused across inputs.
+///
+/// [`decoded_fields`](Parser::decoded_fields) decodes into the buffers instead of allocating
+/// for each field, so a single `Parser` suits a hot loop over many lines.
+///
+/// ```
+/// use escaped_delimiter::Parser;
+///
+/// let mut parser = Parser::new(b' ', b'\\');
+/// for (line, expected) in [(&b"a\\ b c"[..], &[&b"a b"[..], &b"c"[..]][..]), (b"d", &[b"d"])] {
+///     assert_eq!(parser.decoded_fields(line).collect::<Vec<_>>(), expected);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parser {
+    delim: u8,
+    escape: u8,
+    scratch: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl Parser {
+    #[inline]
+    pub fn new(delim: u8, escape: u8) -> Self {
+        Self::with_capacity(delim, escape, 0)
+    }
+
+    /// Same as [`Parser::new`], but the buffer for the decoded fields holds at least `capacity`
+    /// bytes without reallocating.
+    #[inline]
+    pub fn with_capacity(delim: u8, escape: u8, capacity: usize) -> Self {
+        Self {
+            delim,
+            escape,
+            scratch: Vec::with_capacity(capacity),
+            ends: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator of the raw fields of `slice`.
+    #[inline]
+    pub fn fields<'a>(&self, slice: &'a [u8]) -> Iter<'a> {
+        Iter::from_slice(slice, self.delim, self.escape)
+    }
+
+    /// Returns an iterator of the unescaped fields of `slice`, all of which are decoded up front
+    /// into the buffer, overwriting the fields of the previous call.
+    pub fn decoded_fields(&mut self, slice: &[u8]) -> impl Iterator<Item = &[u8]> {
+        self.scratch.clear();
+        self.ends.clear();
+        for field in Iter::from_slice(slice, self.delim, self.escape) {
+            unescape_into(field, self.escape, &mut self.scratch);
+            self.ends.push(self.scratch.len());
+        }
+
+        let scratch = &self.scratch[..];
+        let mut start = 0;
+        self.ends.iter().map(move |&end| {
+            let field = &scratch[start..end];
+            start = end;
+            field
+        })
+    }
+
+    #[inline]
+    pub fn delim(&self) -> u8 {
+        self.delim
+    }
+
+    #[inline]
+    pub fn escape(&self) -> u8 {
+        self.escape
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        let mut parser = Parser::with_capacity(b'X', b'Y', 16);
+        let inputs = [&b"aYXbXXcYY"[..], b"d", b"", b"XX", b"eYYXfYXgXhY", b"aYXb"];
+        let collected: Vec<Vec<Vec<u8>>> = inputs
+            .iter()
+            .map(|s| parser.decoded_fields(s).map(<[u8]>::to_vec).collect())
+            .collect();
+
+        for (s, fields) in inputs.iter().zip(&collected) {
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+                .unescaped()
+                .map(|f| f.into_owned())
+                .collect();
+            assert_eq!(fields, &expected, "{:?}", s);
+            assert_eq!(
+                parser.fields(s).collect::<Vec<_>>(),
+                Iter::from_slice(s, b'X', b'Y').collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(collected[0], &[&b"aXb"[..], b"cY"]);
+
+        // A field decoded by an earlier call is not left over.
+        assert_eq!(parser.decoded_fields(b"").next(), None);
+        assert_eq!(parser.scratch.capacity(), 16);
+        assert_eq!((parser.delim(), parser.escape()), (b'X', b'Y'));
+    }
+}
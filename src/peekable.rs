@@ -0,0 +1,157 @@
+use std::iter::FusedIterator;
+
+use crate::Iter;
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the same fields which can peek at the next field from either end.
+    ///
+    /// Unlike [`Iterator::peekable`], it keeps [`as_slice`](PeekableIter::as_slice) and peeks from
+    /// the back as well.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut it = iter(b"a b\\ c d", b' ', b'\\').peekable();
+    /// assert_eq!(it.peek(), Some(&b"a"[..]));
+    /// assert_eq!(it.peek_back(), Some(&b"d"[..]));
+    /// assert_eq!(it.as_slice(), &b"a b\\ c d"[..]);
+    /// assert_eq!(it.next(), Some(&b"a"[..]));
+    /// assert_eq!(it.next_back(), Some(&b"d"[..]));
+    /// assert_eq!(it.peek(), Some(&b"b\\ c"[..]));
+    /// assert_eq!(it.peek_back(), Some(&b"b\\ c"[..]));
+    /// ```
+    #[inline]
+    pub fn peekable(self) -> PeekableIter<'a> {
+        PeekableIter {
+            outer: self.inner,
+            front: None,
+            back: None,
+            inner: self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeekableIter<'a> {
+    /// The slice at the time of [`Iter::peekable`], which every field is in.
+    outer: &'a [u8],
+    front: Option<&'a [u8]>,
+    back: Option<&'a [u8]>,
+    inner: Iter<'a>,
+}
+
+impl<'a> Iterator for PeekableIter<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front
+            .take()
+            .or_else(|| self.inner.next())
+            .or_else(|| self.back.take())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let peeked = self.front.is_some() as usize + self.back.is_some() as usize;
+        let (lower, upper) = self.inner.size_hint();
+        (lower + peeked, upper.map(|upper| upper + peeked))
+    }
+}
+
+impl<'a> DoubleEndedIterator for PeekableIter<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back
+            .take()
+            .or_else(|| self.inner.next_back())
+            .or_else(|| self.front.take())
+    }
+}
+
+impl FusedIterator for PeekableIter<'_> {}
+
+impl<'a> PeekableIter<'a> {
+    /// Returns the field which [`next`](Iterator::next) would return, without consuming it.
+    #[inline]
+    pub fn peek(&mut self) -> Option<&'a [u8]> {
+        if self.front.is_none() {
+            self.front = self.inner.next().or_else(|| self.back.take());
+        }
+        self.front
+    }
+
+    /// Returns the field which [`next_back`](DoubleEndedIterator::next_back) would return,
+    /// without consuming it.
+    #[inline]
+    pub fn peek_back(&mut self) -> Option<&'a [u8]> {
+        if self.back.is_none() {
+            self.back = self.inner.next_back().or_else(|| self.front.take());
+        }
+        self.back
+    }
+
+    /// Returns the rest of the inner slice, including the fields peeked at.
+    pub fn as_slice(&self) -> &'a [u8] {
+        let offset = |s: &[u8]| (s.as_ptr() as usize) - (self.outer.as_ptr() as usize);
+        let rest = self.inner.as_slice();
+        let (start, end) = [self.front, self.back].into_iter().flatten().fold(
+            (offset(rest), offset(rest) + rest.len()),
+            |(start, end), s| (start.min(offset(s)), end.max(offset(s) + s.len())),
+        );
+        &self.outer[start..end]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_peekable() {
+        let s = b"XaYXbXXcYYXdXeX";
+        let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+
+        let mut it = Iter::from_slice(s, b'X', b'Y').peekable();
+        assert_eq!(it.peek(), Some(&b"aYXb"[..]));
+        assert_eq!(it.peek(), Some(&b"aYXb"[..]));
+        assert_eq!(it.peek_back(), Some(&b"e"[..]));
+        assert_eq!(it.next(), Some(&b"aYXb"[..]));
+        assert_eq!(it.peek_back(), Some(&b"e"[..]));
+        assert_eq!(it.next_back(), Some(&b"e"[..]));
+        assert_eq!(it.peek_back(), Some(&b"d"[..]));
+        assert_eq!(it.peek(), Some(&b"cYY"[..]));
+        assert_eq!(it.next_back(), Some(&b"d"[..]));
+        assert_eq!(it.peek_back(), Some(&b"cYY"[..]));
+        assert_eq!(it.peek(), Some(&b"cYY"[..]));
+        assert_eq!(it.next(), Some(&b"cYY"[..]));
+        assert_eq!(it.peek(), None);
+        assert_eq!(it.peek_back(), None);
+        assert_eq!(it.next_back(), None);
+
+        // Any sequence of calls yields the fields in order, from either end.
+        for ops in 0..4u32.pow(6) {
+            let mut it = Iter::from_slice(s, b'X', b'Y').peekable();
+            let (mut front, mut back) = (0, fields.len());
+            for op in (0..6).map(|i| ops / 4u32.pow(i) % 4) {
+                let expected_front = (front < back).then(|| fields[front]);
+                let expected_back = (front < back).then(|| fields[back - 1]);
+                match op {
+                    0 => assert_eq!(it.peek(), expected_front),
+                    1 => assert_eq!(it.peek_back(), expected_back),
+                    2 => {
+                        assert_eq!(it.next(), expected_front);
+                        front += (front < back) as usize;
+                    }
+                    _ => {
+                        assert_eq!(it.next_back(), expected_back);
+                        back -= (front < back) as usize;
+                    }
+                }
+                let rest: Vec<_> = Iter::from_slice(it.as_slice(), b'X', b'Y').collect();
+                assert_eq!(rest, fields[front..back], "{}", ops);
+                crate::test::check_size_hint(it);
+            }
+        }
+    }
+}
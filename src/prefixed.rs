@@ -0,0 +1,248 @@
+use crate::scanner::{Direction, Scanner};
+
+/// Returns an iterator of the fields which start at each unescaped delimiter, for formats where
+/// the delimiter leads a field (`|a|b|c`) instead of separating two of them.
+///
+/// Each field keeps its leading delimiter, and consecutive delimiters yield fields of just the
+/// delimiter. The bytes before the first unescaped delimiter are not a field; they are the
+/// [`preamble`](DelimiterPrefixed::preamble), which is `None` if the slice is empty or starts
+/// with a delimiter.
+///
+/// ```
+/// use escaped_delimiter::split_keep_delimiter_prefix;
+///
+/// let it = split_keep_delimiter_prefix(b"v1|a||b\\|c", b'|', b'\\');
+/// assert_eq!(it.preamble(), Some(&b"v1"[..]));
+/// let s_vec: Vec<_> = it.collect();
+/// assert_eq!(s_vec, &[&b"|a"[..], &b"|"[..], &b"|b\\|c"[..]]);
+/// ```
+pub fn split_keep_delimiter_prefix(slice: &[u8], delim: u8, escape: u8) -> DelimiterPrefixed<'_> {
+    let scanner = Scanner::new(delim, escape);
+    let start = scanner
+        .find_boundary(slice, Direction::Forward)
+        .unwrap_or(slice.len());
+
+    DelimiterPrefixed {
+        scanner,
+        preamble: (start > 0).then(|| &slice[..start]),
+        inner: &slice[start..],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelimiterPrefixed<'a> {
+    scanner: Scanner,
+    preamble: Option<&'a [u8]>,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for DelimiterPrefixed<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `self.inner` is empty or starts at an unescaped delimiter.
+        let rest = self.inner.get(1..)?;
+        let end = self
+            .scanner
+            .find_boundary(rest, Direction::Forward)
+            .map_or(self.inner.len(), |i| i + 1);
+
+        let (field, rest) = self.inner.split_at(end);
+        self.inner = rest;
+        Some(field)
+    }
+}
+
+impl<'a> DoubleEndedIterator for DelimiterPrefixed<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let start = self
+            .scanner
+            .find_boundary(self.inner, Direction::Backward)?;
+
+        let (rest, field) = self.inner.split_at(start);
+        self.inner = rest;
+        Some(field)
+    }
+}
+
+impl<'a> DelimiterPrefixed<'a> {
+    /// Returns the bytes before the first unescaped delimiter, if any.
+    #[inline]
+    pub fn preamble(&self) -> Option<&'a [u8]> {
+        self.preamble
+    }
+
+    /// Returns the rest of the inner slice, without the preamble.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+}
+
+/// Returns an iterator of the fields, each of which but the first starts with the unescaped
+/// delimiter before it, for formats where the delimiter belongs to the following field.
+///
+/// The first field is the bytes before the first unescaped delimiter, without any delimiter,
+/// and is not yielded if it is empty, so that no field is empty. Escaped delimiters stay inside
+/// the fields, and the fields concatenate back to the slice. Unlike
+/// [`split_keep_delimiter_prefix`], the first field is yielded like the others.
+///
+/// ```
+/// use escaped_delimiter::split_inclusive_left;
+///
+/// let s_vec: Vec<_> = split_inclusive_left(b"v1|a||b\\|c", b'|', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"v1"[..], &b"|a"[..], &b"|"[..], &b"|b\\|c"[..]]);
+///
+/// let s_vec: Vec<_> = split_inclusive_left(b"|a|b", b'|', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"|a"[..], &b"|b"[..]]);
+/// ```
+#[inline]
+pub fn split_inclusive_left(slice: &[u8], delim: u8, escape: u8) -> InclusiveLeft<'_> {
+    InclusiveLeft {
+        scanner: Scanner::new(delim, escape),
+        inner: slice,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InclusiveLeft<'a> {
+    scanner: Scanner,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for InclusiveLeft<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        // A delimiter at the start leads the field, so the field ends at the next one. Since it
+        // is not an escape character, the rest can be searched on its own.
+        let end = match self.scanner.find_boundary(self.inner, Direction::Forward) {
+            Some(0) => self
+                .scanner
+                .find_boundary(&self.inner[1..], Direction::Forward)
+                .map(|i| i + 1),
+            end => end,
+        };
+
+        let (field, rest) = self.inner.split_at(end.unwrap_or(self.inner.len()));
+        self.inner = rest;
+        Some(field)
+    }
+}
+
+impl<'a> DoubleEndedIterator for InclusiveLeft<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        let start = self
+            .scanner
+            .find_boundary(self.inner, Direction::Backward)
+            .unwrap_or(0);
+
+        let (rest, field) = self.inner.split_at(start);
+        self.inner = rest;
+        Some(field)
+    }
+}
+
+impl<'a> InclusiveLeft<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_keep_delimiter_prefix() {
+        // The preamble and the fields make up the whole slice.
+        let cases: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[]),
+            (b"X", &[b"X"]),
+            (b"XaXbXXc", &[b"Xa", b"Xb", b"X", b"Xc"]),
+            (b"XaX", &[b"Xa", b"X"]),
+            (b"abc", &[]),
+            (b"aXb", &[b"Xb"]),
+            (b"YXaXb", &[b"Xb"]),
+            (b"YYXaYXb", &[b"XaYXb"]),
+            (b"XYXaXYYXb", &[b"XYXa", b"XYY", b"Xb"]),
+        ];
+
+        for &(s, expected) in cases {
+            let preamble = &s[..s.len() - expected.concat().len()];
+            let it = split_keep_delimiter_prefix(s, b'X', b'Y');
+            assert_eq!(it.preamble().unwrap_or_default(), preamble, "{:?}", s);
+            assert_eq!(it.preamble().is_none(), preamble.is_empty(), "{:?}", s);
+            assert_eq!(it.collect::<Vec<_>>(), expected, "{:?}", s);
+
+            let mut fields: Vec<_> = it.rev().collect();
+            fields.reverse();
+            assert_eq!(fields, expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_split_keep_delimiter_prefix_mixed() {
+        let mut it = split_keep_delimiter_prefix(b"pXaXbYXXc", b'X', b'Y');
+        assert_eq!(it.next_back(), Some(&b"Xc"[..]));
+        assert_eq!(it.next(), Some(&b"Xa"[..]));
+        assert_eq!(it.as_slice(), b"XbYX");
+        assert_eq!(it.next_back(), Some(&b"XbYX"[..]));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.preamble(), Some(&b"p"[..]));
+    }
+
+    #[test]
+    fn test_split_inclusive_left() {
+        let s = b"aYXbXXcYYXdX";
+        let v: Vec<_> = split_inclusive_left(s, b'X', b'Y').collect();
+        assert_eq!(v, &[&b"aYXb"[..], b"X", b"XcYY", b"Xd", b"X"]);
+
+        let mut it = split_inclusive_left(s, b'X', b'Y');
+        assert_eq!(it.next_back(), Some(&b"X"[..]));
+        assert_eq!(it.next(), Some(&b"aYXb"[..]));
+        assert_eq!(it.as_slice(), b"XXcYYXd");
+        assert_eq!(it.next_back(), Some(&b"Xd"[..]));
+
+        assert_eq!(split_inclusive_left(b"", b'X', b'Y').next(), None);
+
+        for s in crate::test::strings(b"aXY", 8) {
+            let v: Vec<_> = split_inclusive_left(&s, b'X', b'Y').collect();
+            assert_eq!(v.concat(), s);
+
+            // Every field but the first starts with the delimiter, which is its only boundary.
+            let scanner = Scanner::new(b'X', b'Y');
+            for (i, field) in v.iter().enumerate() {
+                let start = field.as_ptr() as usize - s.as_ptr() as usize;
+                let boundaries: Vec<_> = (start..start + field.len())
+                    .filter(|&j| scanner.is_boundary(&s, j))
+                    .collect();
+                let expected: &[usize] = if i == 0 && boundaries.is_empty() {
+                    &[]
+                } else {
+                    &[start]
+                };
+                assert_eq!(boundaries, expected, "{:?}", s);
+            }
+
+            let it = split_keep_delimiter_prefix(&s, b'X', b'Y');
+            let expected: Vec<_> = it.preamble().into_iter().chain(it).collect();
+            assert_eq!(v, expected, "{:?}", s);
+
+            let mut rv: Vec<_> = split_inclusive_left(&s, b'X', b'Y').rev().collect();
+            rv.reverse();
+            assert_eq!(rv, v, "{:?}", s);
+        }
+    }
+}
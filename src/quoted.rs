@@ -0,0 +1,305 @@
+use crate::scanner::{Direction, Scanner};
+
+/// Returns an iterator of fields which may be quoted, so that the delimiters between the quotes
+/// are literal, as in `"a,b",c`.
+///
+/// A quote opens only at the start of a field, and the next unescaped quote closes it; any other
+/// quote is literal. The field goes on after the closing quote up to the next unescaped
+/// delimiter. If the quote is never closed, the field runs to the end of the slice. Escape
+/// characters work inside and outside of the quotes alike, and empty fields are skipped as in
+/// [`iter`](crate::iter).
+///
+/// The fields keep the quotes, unless [`Quoted::strip_quotes`] is set.
+///
+/// # Panics
+///
+/// Panics if the delimiter, the escape character and the quote are not all different.
+///
+/// ```
+/// use escaped_delimiter::iter_quoted;
+///
+/// let s_vec: Vec<_> = iter_quoted(b"\"a,b\",c\\,d", b',', b'\\', b'"').collect();
+/// assert_eq!(s_vec, &[&b"\"a,b\""[..], &b"c\\,d"[..]]);
+/// ```
+pub fn iter_quoted(slice: &[u8], delim: u8, escape: u8, quote: u8) -> Quoted<'_> {
+    assert!(
+        delim != escape && quote != delim && quote != escape,
+        "the delimiter, the escape character and the quote are not all different"
+    );
+
+    Quoted {
+        scanner: Scanner::new(delim, escape),
+        quote,
+        strip_quotes: false,
+        escape_in_quotes_only: false,
+        inner: slice,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quoted<'a> {
+    scanner: Scanner,
+    quote: u8,
+    strip_quotes: bool,
+    escape_in_quotes_only: bool,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for Quoted<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.find_boundary() {
+                Some(i) => {
+                    let field = &self.inner[..i];
+                    self.inner = &self.inner[i + 1..];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(self.strip(field));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoubleEndedIterator for Quoted<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // Whether a delimiter is quoted depends on where its field starts, which a backward scan
+        // cannot tell, so the boundaries are found from the front, up to the last field.
+        let mut rest = *self;
+        let mut last = None;
+        let mut start = 0;
+        while !rest.inner.is_empty() {
+            let (len, next) = match rest.find_boundary() {
+                Some(i) => (i, i + 1),
+                None => (rest.inner.len(), rest.inner.len()),
+            };
+            if len > 0 {
+                last = Some((start, start + len));
+            }
+            rest.inner = &rest.inner[next..];
+            start += next;
+        }
+
+        let (start, end) = last?;
+        let field = &self.inner[start..end];
+        self.inner = &self.inner[..start];
+        Some(self.strip(field))
+    }
+}
+
+impl<'a> Quoted<'a> {
+    /// Sets whether a quoted field is yielded without its quotes.
+    ///
+    /// Only a field which ends right at its closing quote is stripped; any other field, such as
+    /// one whose quote is never closed, is yielded as is. The escape characters are kept, so that
+    /// [`unescape`](crate::unescape) still decodes the stripped field.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter_quoted, unescape};
+    ///
+    /// let s = b"\"a,\\\"b\",c,\"d\"e,\"f";
+    /// let s_vec: Vec<_> = iter_quoted(s, b',', b'\\', b'"').strip_quotes(true).collect();
+    /// assert_eq!(s_vec, &[&b"a,\\\"b"[..], &b"c"[..], &b"\"d\"e"[..], &b"\"f"[..]]);
+    /// assert_eq!(unescape(s_vec[0], b'\\'), &b"a,\"b"[..]);
+    /// ```
+    #[inline]
+    pub fn strip_quotes(self, strip_quotes: bool) -> Self {
+        Self {
+            strip_quotes,
+            ..self
+        }
+    }
+
+    /// Sets whether the escape character is literal outside of the quotes, as in the CSV dialects
+    /// which escape only within quoted fields.
+    ///
+    /// Outside of the quotes, an escape character then neither escapes a delimiter nor an
+    /// opening quote. Within them, it still escapes the closing quote, so that a stripped field
+    /// is decoded by [`unescape`](crate::unescape) as before.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter_quoted;
+    ///
+    /// let s = b"\"a\\\",b\",c\\,d";
+    /// let s_vec: Vec<_> = iter_quoted(s, b',', b'\\', b'"').escape_in_quotes_only(true).collect();
+    /// assert_eq!(s_vec, &[&b"\"a\\\",b\""[..], &b"c\\"[..], &b"d"[..]]);
+    /// ```
+    #[inline]
+    pub fn escape_in_quotes_only(self, escape_in_quotes_only: bool) -> Self {
+        Self {
+            escape_in_quotes_only,
+            ..self
+        }
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// Returns the position of the first unescaped delimiter outside of the quotes.
+    fn find_boundary(&self) -> Option<usize> {
+        let start = if self.inner[0] == self.quote {
+            // Since the closing quote is not an escape character, the rest can be searched on
+            // its own.
+            self.closing_quote(self.inner)? + 1
+        } else {
+            0
+        };
+
+        let rest = &self.inner[start..];
+        let found = if self.escape_in_quotes_only {
+            rest.iter().position(|&c| c == self.scanner.delim)
+        } else {
+            self.scanner.find_boundary(rest, Direction::Forward)
+        };
+        found.map(|i| start + i)
+    }
+
+    /// Returns the position of the first unescaped quote after the opening one.
+    fn closing_quote(&self, field: &[u8]) -> Option<usize> {
+        // The opening quote is not an escape character, so the rest can be searched on its own.
+        Scanner::new(self.quote, self.scanner.escape)
+            .find_boundary(&field[1..], Direction::Forward)
+            .map(|i| i + 1)
+    }
+
+    fn strip(&self, field: &'a [u8]) -> &'a [u8] {
+        let quoted = field[0] == self.quote;
+        if self.strip_quotes && quoted && self.closing_quote(field) == Some(field.len() - 1) {
+            &field[1..field.len() - 1]
+        } else {
+            field
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split(s: &[u8], strip_quotes: bool) -> Vec<&[u8]> {
+        iter_quoted(s, b'X', b'Y', b'Q')
+            .strip_quotes(strip_quotes)
+            .collect()
+    }
+
+    #[test]
+    fn test_iter_quoted() {
+        let s = b"QaXbQXcYXdXXQeYQXfQXQQXgQhQiXaQbXQjYYQ";
+        assert_eq!(
+            split(s, false),
+            &[
+                &b"QaXbQ"[..],
+                b"cYXd",
+                b"QeYQXfQ",
+                b"QQ",
+                b"gQhQi",
+                b"aQb",
+                b"QjYYQ",
+            ]
+        );
+        assert_eq!(
+            split(s, true),
+            &[
+                &b"aXb"[..],
+                b"cYXd",
+                b"eYQXf",
+                b"",
+                b"gQhQi",
+                b"aQb",
+                b"jYY",
+            ]
+        );
+
+        // Bytes after the closing quote belong to the field, which is then left as is.
+        assert_eq!(split(b"QaXbQcXd", true), &[&b"QaXbQc"[..], b"d"]);
+        assert_eq!(split(b"QaQYXbXc", true), &[&b"QaQYXb"[..], b"c"]);
+
+        // An unterminated quote takes the rest of the slice.
+        assert_eq!(split(b"aXQbXc", true), &[&b"a"[..], b"QbXc"]);
+        assert_eq!(split(b"aXQbYQ", true), &[&b"a"[..], b"QbYQ"]);
+        assert_eq!(split(b"Q", true), &[&b"Q"[..]]);
+
+        // Without quotes, the fields are those of `iter`.
+        for s in crate::test::strings(b"aXY", 8) {
+            let expected: Vec<_> = crate::Iter::from_slice(&s, b'X', b'Y').collect();
+            assert_eq!(split(&s, true), expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_quoted_rev() {
+        let v: Vec<_> = iter_quoted(b"QaXbQXcYXdXQe", b'X', b'Y', b'Q')
+            .rev()
+            .collect();
+        assert_eq!(v, &[&b"Qe"[..], b"cYXd", b"QaXbQ"]);
+
+        for s in crate::test::strings(b"aXYQ", 7) {
+            for (strip_quotes, escape_in_quotes_only) in
+                [(false, false), (true, false), (false, true), (true, true)]
+            {
+                let words = iter_quoted(&s, b'X', b'Y', b'Q')
+                    .strip_quotes(strip_quotes)
+                    .escape_in_quotes_only(escape_in_quotes_only);
+                let mut expected: Vec<_> = words.collect();
+                let rwords: Vec<_> = words.rev().collect();
+                let forward = expected.clone();
+                expected.reverse();
+                assert_eq!(rwords, expected, "{:?}", s);
+
+                // From both ends at once.
+                let (mut words, mut front, mut back) = (words, Vec::new(), Vec::new());
+                loop {
+                    let (field, rfield) = (words.next(), words.next_back());
+                    front.extend(field);
+                    back.extend(rfield);
+                    if rfield.is_none() {
+                        break;
+                    }
+                }
+                back.reverse();
+                front.extend(back);
+                assert_eq!(front, forward, "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_escape_in_quotes_only() {
+        fn split_csv(s: &[u8], escape_in_quotes_only: bool) -> Vec<&[u8]> {
+            iter_quoted(s, b'X', b'Y', b'Q')
+                .escape_in_quotes_only(escape_in_quotes_only)
+                .collect()
+        }
+
+        // Inside the quotes, the escape character works either way.
+        for escape_in_quotes_only in [false, true] {
+            let v = split_csv(b"QaYQXbQXc", escape_in_quotes_only);
+            assert_eq!(v, &[&b"QaYQXbQ"[..], b"c"]);
+            let v = split_csv(b"QaYYQXb", escape_in_quotes_only);
+            assert_eq!(v, &[&b"QaYYQ"[..], b"b"]);
+        }
+
+        // Outside of them, it is literal.
+        assert_eq!(split_csv(b"aYXbXc", false), &[&b"aYXb"[..], b"c"]);
+        assert_eq!(split_csv(b"aYXbXc", true), &[&b"aY"[..], b"b", b"c"]);
+        assert_eq!(split_csv(b"QaQYXb", false), &[&b"QaQYXb"[..]]);
+        assert_eq!(split_csv(b"QaQYXb", true), &[&b"QaQY"[..], b"b"]);
+        assert_eq!(split_csv(b"YQaXbQ", true), &[&b"YQa"[..], b"bQ"]);
+
+        // Without quotes, the fields are those of splitting on every delimiter.
+        for s in crate::test::strings(b"aXY", 7) {
+            let expected: Vec<_> = s.split(|&c| c == b'X').filter(|f| !f.is_empty()).collect();
+            assert_eq!(split_csv(&s, true), expected, "{:?}", s);
+        }
+    }
+}
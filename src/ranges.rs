@@ -0,0 +1,114 @@
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use crate::Iter;
+
+/// Returns an iterator of the ranges of the fields in `slice`, which are the same as those of
+/// [`iter`](crate::iter).
+///
+/// ```
+/// use escaped_delimiter::field_ranges;
+///
+/// let v: Vec<_> = field_ranges(b" a\\ b  c", b' ', b'\\').collect();
+/// assert_eq!(v, &[1..5, 7..8]);
+/// ```
+#[inline]
+pub fn field_ranges(slice: &[u8], delim: u8, escape: u8) -> FieldRanges<'_> {
+    Iter::from_slice(slice, delim, escape).field_ranges()
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the ranges of the remaining fields, relative to the rest of the
+    /// slice, or to the base of [`Iter::from_slice_with_base`].
+    #[inline]
+    pub fn field_ranges(self) -> FieldRanges<'a> {
+        FieldRanges {
+            base: (self.inner.as_ptr() as usize).wrapping_sub(self.base_offset()),
+            inner: self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldRanges<'a> {
+    base: usize,
+    inner: Iter<'a>,
+}
+
+impl FieldRanges<'_> {
+    #[inline]
+    fn range(&self, field: &[u8]) -> Range<usize> {
+        let start = (field.as_ptr() as usize).wrapping_sub(self.base);
+        start..start + field.len()
+    }
+}
+
+impl Iterator for FieldRanges<'_> {
+    type Item = Range<usize>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next()?;
+        Some(self.range(field))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FieldRanges<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let field = self.inner.next_back()?;
+        Some(self.range(field))
+    }
+}
+
+impl FusedIterator for FieldRanges<'_> {}
+
+impl<'a> FieldRanges<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_field_ranges() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"abc", b"XaYXXbYYXcXYX", b"YYYXXYaYbYX"];
+        for s in inputs {
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            let fields: Vec<_> = field_ranges(s, b'X', b'Y').map(|r| &s[r]).collect();
+            assert_eq!(fields, expected);
+
+            let mut fields: Vec<_> = field_ranges(s, b'X', b'Y').rev().map(|r| &s[r]).collect();
+            fields.reverse();
+            assert_eq!(fields, expected);
+        }
+
+        let mut ranges = field_ranges(b"XaXbYXXc", b'X', b'Y');
+        assert_eq!(ranges.next_back(), Some(7..8));
+        assert_eq!(ranges.next(), Some(1..2));
+        assert_eq!(ranges.next(), Some(3..6));
+        assert_eq!(ranges.next_back(), None);
+    }
+
+    #[test]
+    fn test_field_ranges_size_hint() {
+        for s in crate::test::strings(b"aXY", 7) {
+            crate::test::check_size_hint(field_ranges(&s, b'X', b'Y'));
+        }
+
+        let mut ranges = field_ranges(b"XaXbYXXc", b'X', b'Y');
+        assert_eq!(ranges.size_hint(), (1, Some(4)));
+        ranges.next();
+        assert_eq!(ranges.size_hint(), (1, Some(3)));
+    }
+}
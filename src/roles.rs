@@ -0,0 +1,178 @@
+use crate::Iter;
+
+/// What a byte is to the splitting and the unescaping, yielded by [`Iter::byte_roles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteRole {
+    /// An unescaped delimiter, which separates the fields.
+    Delimiter,
+    /// A delimiter which is part of a field.
+    EscapedDelimiter,
+    /// An escape character which unescaping removes.
+    Escape,
+    /// An escape character made literal by the one before it.
+    EscapedEscape,
+    /// Any other byte, which unescaping keeps.
+    ///
+    /// This includes an escape character which escapes nothing, at the end of the slice or,
+    /// with [`Iter::with_specials`], before a byte which is not special.
+    Content,
+}
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the offset, the value and the role of each byte of the rest of the
+    /// slice.
+    ///
+    /// The offsets are relative to the rest of the slice, or to the base of
+    /// [`Iter::from_slice_with_base`].
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ByteRole::*};
+    ///
+    /// let it = iter(b"a\\X\\\\Xb", b'X', b'\\');
+    /// let roles: Vec<_> = it.byte_roles().map(|(_, _, role)| role).collect();
+    /// assert_eq!(roles, &[
+    ///     Content, Escape, EscapedDelimiter, Escape, EscapedEscape, Delimiter, Content,
+    /// ]);
+    /// ```
+    pub fn byte_roles(&self) -> impl Iterator<Item = (usize, u8, ByteRole)> + 'a {
+        let it = *self;
+        let base = it.base_offset();
+        let scanner = it.scanner();
+        let mut escaping = false;
+        it.inner.iter().enumerate().map(move |(i, &c)| {
+            let role = if c == it.delim {
+                if scanner.is_boundary(it.inner, i) {
+                    ByteRole::Delimiter
+                } else {
+                    ByteRole::EscapedDelimiter
+                }
+            } else if escaping {
+                if c == it.escape {
+                    ByteRole::EscapedEscape
+                } else {
+                    ByteRole::Content
+                }
+            } else if c == it.escape && it.inner.get(i + 1).is_some_and(|&c| it.is_special(c)) {
+                ByteRole::Escape
+            } else {
+                ByteRole::Content
+            };
+
+            escaping = role == ByteRole::Escape;
+            (base + i, c, role)
+        })
+    }
+}
+
+/// Renders the bytes of the slice on one line and their roles beneath, for finding out why the
+/// slice splits as it does.
+///
+/// The roles are `D` for [`ByteRole::Delimiter`], `d` for [`ByteRole::EscapedDelimiter`], `E`
+/// for [`ByteRole::Escape`], `e` for [`ByteRole::EscapedEscape`] and `.` for
+/// [`ByteRole::Content`]. A byte is shown as by [`u8::escape_ascii`], and its role is aligned to
+/// the first column of it.
+///
+/// ```
+/// use escaped_delimiter::debug_roles;
+///
+/// assert_eq!(debug_roles(b"a\\,,b\\\\,", b',', b'\\'), "a\\\\,,b\\\\\\\\,\n.E dD.E e D");
+/// ```
+pub fn debug_roles(slice: &[u8], delim: u8, escape: u8) -> String {
+    let mut bytes = String::new();
+    let mut roles = String::new();
+    for (_, c, role) in Iter::from_slice(slice, delim, escape).byte_roles() {
+        let shown = c.escape_ascii().to_string();
+        roles.push(match role {
+            ByteRole::Delimiter => 'D',
+            ByteRole::EscapedDelimiter => 'd',
+            ByteRole::Escape => 'E',
+            ByteRole::EscapedEscape => 'e',
+            ByteRole::Content => '.',
+        });
+        roles.extend(std::iter::repeat_n(' ', shown.len() - 1));
+        bytes.push_str(&shown);
+    }
+
+    bytes.push('\n');
+    bytes + &roles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ByteRole::*;
+
+    #[test]
+    fn test_byte_roles() {
+        let roles = |it: Iter| -> Vec<_> { it.byte_roles().map(|(_, _, role)| role).collect() };
+
+        let it = Iter::from_slice(b"aYXYYXbYYYXYcYdY", b'X', b'Y');
+        assert_eq!(
+            roles(it),
+            &[
+                Content,
+                Escape,
+                EscapedDelimiter,
+                Escape,
+                EscapedEscape,
+                Delimiter,
+                Content,
+                Escape,
+                EscapedEscape,
+                Escape,
+                EscapedDelimiter,
+                Escape,
+                Content,
+                Escape,
+                Content,
+                Content,
+            ]
+        );
+        let v: Vec<_> = it.byte_roles().map(|(i, c, _)| (i, c)).collect();
+        assert_eq!(
+            v,
+            b"aYXYYXbYYYXYcYdY"
+                .iter()
+                .copied()
+                .enumerate()
+                .collect::<Vec<_>>()
+        );
+
+        // Unescaping keeps exactly the bytes other than `Escape`, and splits at `Delimiter`.
+        for s in crate::test::strings(b"aXY", 7) {
+            let it = Iter::from_slice(&s, b'X', b'Y');
+            let kept: Vec<_> = it
+                .byte_roles()
+                .filter(|&(_, _, role)| role != Escape && role != Delimiter)
+                .map(|(_, c, _)| c)
+                .collect();
+            let unescaped: Vec<_> = it.unescaped().flat_map(|f| f.into_owned()).collect();
+            assert_eq!(kept, unescaped, "{:?}", s);
+
+            let delims = it.byte_roles().filter(|&(_, _, role)| role == Delimiter);
+            let boundaries = (0..s.len()).filter(|&i| it.scanner().is_boundary(&s, i));
+            assert!(delims.map(|(i, _, _)| i).eq(boundaries), "{:?}", s);
+        }
+
+        let it = Iter::with_specials(b"YaYXY#", b'X', b'Y', b"#");
+        assert_eq!(
+            roles(it),
+            &[Content, Content, Escape, EscapedDelimiter, Escape, Content]
+        );
+
+        let mut it = Iter::from_slice(b"aXYXb", b'X', b'Y');
+        it.next();
+        assert_eq!(roles(it), &[Delimiter, Escape, EscapedDelimiter, Content]);
+    }
+
+    #[test]
+    fn test_debug_roles() {
+        assert_eq!(
+            debug_roles(b"aYXYYXbYYYXY", b'X', b'Y'),
+            "aYXYYXbYYYXY\n.EdEeD.EeEd."
+        );
+        assert_eq!(debug_roles(b"Y\tX\n", b'X', b'Y'), "Y\\tX\\n\nE. D. ");
+        assert_eq!(debug_roles(b"", b'X', b'Y'), "\n");
+    }
+}
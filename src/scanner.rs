@@ -0,0 +1,519 @@
+#[cfg(feature = "memchr")]
+use memchr::{memchr, memrchr};
+
+/// How a run of escape characters before a delimiter is interpreted, set with
+/// [`Iter::escape_run`](crate::Iter::escape_run).
+///
+/// Take `a\\X b` with `X` as the delimiter:
+///
+/// ```
+/// use escaped_delimiter::{EscapeRun, Iter};
+///
+/// let s = b"a\\\\X b";
+/// let it = Iter::from_slice(s, b'X', b'\\');
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"a\\\\"[..], &b" b"[..]]);
+///
+/// let it = it.escape_run(EscapeRun::Sticky);
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"a\\\\X b"[..]]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EscapeRun {
+    /// Escape characters pair up, so a delimiter is escaped iff an odd number of them precede
+    /// it: `a\\X b` splits into `a\\` and ` b`, and `a\\\X b` does not split.
+    #[default]
+    Parity,
+    /// A delimiter is escaped iff at least one escape character precedes it: neither `a\\X b`
+    /// nor `a\\\X b` splits.
+    ///
+    /// In other words, escapes do not stack: any run of them protects the one delimiter after
+    /// it, without the parity of the run to keep track of.
+    ///
+    /// This only changes where the slice is split. The fields are still unescaped pairwise.
+    Sticky,
+}
+
+/// The direction in which [`Scanner`] searches a slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Forward,
+    Backward,
+}
+
+/// The escape-parity rule, shared by both directions of every iterator.
+///
+/// A delimiter is a boundary iff it is preceded by an even number of consecutive escape
+/// characters. The slice must start at the beginning of the slice being split or right after a
+/// boundary, so that no escape run is cut off.
+///
+/// With [`EscapeRun::Sticky`], a delimiter is a boundary iff it is not right after an escape
+/// character instead.
+///
+/// If the delimiter is also the escape character, it cannot escape itself, so every delimiter is
+/// a boundary.
+///
+/// With [`Scanner::with_delims`], any byte of a set is a delimiter instead of `delim`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Scanner {
+    pub(crate) delim: u8,
+    pub(crate) escape: u8,
+    pub(crate) run: EscapeRun,
+    delims: Option<ByteSet>,
+}
+
+/// A set of bytes as a bitmap, so that a [`Scanner`] stays `Copy` without borrowing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    #[inline]
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        let mut set = [0; 4];
+        for &c in bytes {
+            set[c as usize / 64] |= 1 << (c % 64);
+        }
+        Self(set)
+    }
+
+    #[inline]
+    pub(crate) fn contains(self, c: u8) -> bool {
+        self.0[c as usize / 64] & (1 << (c % 64)) != 0
+    }
+}
+
+impl Scanner {
+    #[inline]
+    pub(crate) fn new(delim: u8, escape: u8) -> Self {
+        Self {
+            delim,
+            escape,
+            run: EscapeRun::Parity,
+            delims: None,
+        }
+    }
+
+    /// Returns a scanner on which any byte of `delims` is a delimiter.
+    ///
+    /// The delimiters are then searched for by looking each byte up in the set, rather than by
+    /// `memchr`.
+    #[inline]
+    pub(crate) fn with_delims(delims: &[u8], escape: u8) -> Self {
+        Self {
+            delims: Some(ByteSet::new(delims)),
+            ..Self::new(delims[0], escape)
+        }
+    }
+
+    /// Returns whether `c` is a delimiter, escaped or not.
+    #[inline]
+    pub(crate) fn is_delim(self, c: u8) -> bool {
+        match self.delims {
+            Some(delims) => delims.contains(c),
+            None => c == self.delim,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_run(self, run: EscapeRun) -> Self {
+        Self { run, ..self }
+    }
+
+    /// Returns whether `slice[i]` is an unescaped delimiter.
+    #[inline]
+    pub(crate) fn is_boundary(self, slice: &[u8], i: usize) -> bool {
+        // An escape character which is a delimiter escapes nothing.
+        if !self.is_delim(slice[i]) || self.is_delim(self.escape) {
+            return self.is_delim(slice[i]);
+        }
+
+        match self.run {
+            EscapeRun::Parity => is_unescaped(slice, i, self.escape),
+            EscapeRun::Sticky => i == 0 || slice[i - 1] != self.escape,
+        }
+    }
+
+    /// Returns the position of the first boundary in `dir`.
+    ///
+    /// Past the first [`HEAD`] bytes, only the delimiters are checked, which `memchr` finds in
+    /// bulk, so the bytes in between cost nothing but the search.
+    #[inline]
+    pub(crate) fn find_boundary(self, slice: &[u8], dir: Direction) -> Option<usize> {
+        let is_boundary = |&i: &usize| self.is_boundary(slice, i);
+        match dir {
+            Direction::Forward => {
+                let head = slice.len().min(HEAD);
+                (0..head)
+                    .find(is_boundary)
+                    .or_else(|| self.find_boundary_after(slice, head))
+            }
+            Direction::Backward => {
+                let tail = slice.len().saturating_sub(HEAD);
+                (tail..slice.len())
+                    .rev()
+                    .find(is_boundary)
+                    .or_else(|| self.rfind_boundary_before(slice, tail))
+            }
+        }
+    }
+
+    /// Returns the position of the first boundary in `slice[start..]`, searching by `memchr`.
+    #[inline(never)]
+    fn find_boundary_after(self, slice: &[u8], mut start: usize) -> Option<usize> {
+        while let Some(i) = self.find_delim(&slice[start..]) {
+            let i = start + i;
+            if self.is_boundary(slice, i) {
+                return Some(i);
+            }
+            start = i + 1;
+        }
+        None
+    }
+
+    /// Returns the position of the last boundary in `slice[..end]`, searching by `memrchr`.
+    #[inline(never)]
+    fn rfind_boundary_before(self, slice: &[u8], mut end: usize) -> Option<usize> {
+        while let Some(i) = self.rfind_delim(&slice[..end]) {
+            if self.is_boundary(slice, i) {
+                return Some(i);
+            }
+            end = i;
+        }
+        None
+    }
+
+    /// Same as [`Scanner::find_boundary`], but also returns the number of escape characters
+    /// which unescaping the bytes between the boundary and the end of `slice` that the search
+    /// starts from removes, where an escape character before a byte for which `is_special`
+    /// does not hold is kept.
+    ///
+    /// Every byte up to the boundary is read, so the search goes without `memchr`.
+    pub(crate) fn find_boundary_counting(
+        self,
+        slice: &[u8],
+        dir: Direction,
+        is_special: impl Fn(u8) -> bool,
+    ) -> (Option<usize>, usize) {
+        match dir {
+            Direction::Forward => {
+                let mut count = 0;
+                let mut escaped = false;
+                for (i, &c) in slice.iter().enumerate() {
+                    if self.is_delim(c) && self.is_boundary(slice, i) {
+                        return (Some(i), count);
+                    }
+                    if std::mem::take(&mut escaped) {
+                        count += is_special(c) as usize;
+                    } else {
+                        escaped = c == self.escape;
+                    }
+                }
+                (None, count)
+            }
+            Direction::Backward => {
+                // Counted by runs of escape characters, read pairwise from the first one of
+                // each run, and the byte right after the run, if any.
+                let run_count = |run: usize, after: Option<u8>| {
+                    run / 2 + (run % 2 == 1 && after.is_some_and(&is_special)) as usize
+                };
+                let (mut count, mut run, mut after) = (0, 0, None);
+                for i in (0..slice.len()).rev() {
+                    let c = slice[i];
+                    if c == self.escape && !self.is_delim(c) {
+                        run += 1;
+                        continue;
+                    }
+                    count += run_count(run, after);
+                    run = 0;
+                    if self.is_boundary(slice, i) {
+                        return (Some(i), count);
+                    }
+                    after = Some(c);
+                }
+                (None, count + run_count(run, after))
+            }
+        }
+    }
+
+    /// Returns the position of the first delimiter, escaped or not.
+    #[inline]
+    fn find_delim(self, haystack: &[u8]) -> Option<usize> {
+        match self.delims {
+            Some(delims) => haystack.iter().position(|&c| delims.contains(c)),
+            None => memchr(self.delim, haystack),
+        }
+    }
+
+    /// Returns the position of the last delimiter, escaped or not.
+    #[inline]
+    fn rfind_delim(self, haystack: &[u8]) -> Option<usize> {
+        match self.delims {
+            Some(delims) => haystack.iter().rposition(|&c| delims.contains(c)),
+            None => memrchr(self.delim, haystack),
+        }
+    }
+
+    /// Returns the number of consecutive boundaries at the end of `slice` in `dir`.
+    #[inline]
+    pub(crate) fn skip_boundaries(self, slice: &[u8], dir: Direction) -> usize {
+        let is_boundary = |&i: &usize| self.is_boundary(slice, i);
+        match dir {
+            Direction::Forward => (0..slice.len()).take_while(is_boundary).count(),
+            Direction::Backward => (0..slice.len()).rev().take_while(is_boundary).count(),
+        }
+    }
+}
+
+/// The number of bytes checked one by one before calling `memchr`, which is slow to start, so
+/// that short fields stay cheap.
+const HEAD: usize = 16;
+
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&c| c == needle)
+}
+
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&c| c == needle)
+}
+
+/// Returns whether `slice[i]` is preceded by an even number of consecutive escape characters.
+#[inline]
+pub(crate) fn is_unescaped(slice: &[u8], i: usize, escape: u8) -> bool {
+    let escapes = slice[..i]
+        .iter()
+        .rev()
+        .take_while(|&&c| c == escape)
+        .count();
+    escapes & 1 == 0
+}
+
+/// The escape-parity rule of [`Scanner`] for scans which read one byte at a time and cannot look
+/// back, such as those which carry it over from one read to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EscapeTracker {
+    escape: u8,
+    /// Whether the last byte read is an escape character which makes the next byte literal.
+    escaped: bool,
+}
+
+impl EscapeTracker {
+    /// Returns a tracker at the start of a field.
+    #[inline]
+    pub(crate) fn new(escape: u8) -> Self {
+        Self {
+            escape,
+            escaped: false,
+        }
+    }
+
+    /// Reads the next byte `c`, and returns whether it is a target which is not escaped.
+    ///
+    /// A target is never read as an escape character, so that a delimiter which is also the
+    /// escape character is always a boundary, as in [`Scanner::is_boundary`].
+    #[inline]
+    pub(crate) fn is_unescaped_target(&mut self, c: u8, is_target: bool) -> bool {
+        if std::mem::take(&mut self.escaped) {
+            return false;
+        }
+        self.escaped = !is_target && c == self.escape;
+        is_target
+    }
+}
+
+/// Returns the position of the first byte of `slice` for which `is_target` holds and which is
+/// not escaped, reading the escape characters pairwise from the start of `slice`.
+#[inline]
+pub(crate) fn find_unescaped(
+    slice: &[u8],
+    escape: u8,
+    mut is_target: impl FnMut(u8) -> bool,
+) -> Option<usize> {
+    let mut tracker = EscapeTracker::new(escape);
+    slice
+        .iter()
+        .position(|&c| tracker.is_unescaped_target(c, is_target(c)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_boundary_counting() {
+        let scanners = [Scanner::new(b'X', b'Y'), Scanner::new(b'X', b'X')]
+            .into_iter()
+            .flat_map(|s| [s, s.with_run(EscapeRun::Sticky)]);
+        for scanner in scanners {
+            for s in crate::test::strings(b"aXYZ", 7) {
+                for is_special in [|_| true, |c| c != b'Z'] {
+                    let count = |field: &[u8]| {
+                        crate::escape::count_escapes_with(field, scanner.escape, is_special)
+                    };
+
+                    let found = scanner.find_boundary(&s, Direction::Forward);
+                    let field = &s[..found.unwrap_or(s.len())];
+                    assert_eq!(
+                        scanner.find_boundary_counting(&s, Direction::Forward, is_special),
+                        (found, count(field)),
+                        "{:?}",
+                        s
+                    );
+
+                    let found = scanner.find_boundary(&s, Direction::Backward);
+                    let field = &s[found.map_or(0, |i| i + 1)..];
+                    assert_eq!(
+                        scanner.find_boundary_counting(&s, Direction::Backward, is_special),
+                        (found, count(field)),
+                        "{:?}",
+                        s
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_escape_tracker() {
+        for scanner in [Scanner::new(b'X', b'Y'), Scanner::new(b'X', b'X')] {
+            for s in crate::test::strings(b"aXY", 8) {
+                let mut tracker = EscapeTracker::new(scanner.escape);
+                let found: Vec<_> = (0..s.len())
+                    .filter(|&i| tracker.is_unescaped_target(s[i], s[i] == scanner.delim))
+                    .collect();
+                let expected: Vec<_> = (0..s.len())
+                    .filter(|&i| scanner.is_boundary(&s, i))
+                    .collect();
+                assert_eq!(found, expected, "{:?}", s);
+                assert_eq!(
+                    find_unescaped(&s, scanner.escape, |c| c == scanner.delim),
+                    expected.first().copied()
+                );
+            }
+        }
+    }
+
+    fn boundaries(scanner: Scanner, slice: &[u8], dir: Direction) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut rest = slice;
+        while let Some(i) = scanner.find_boundary(rest, dir) {
+            match dir {
+                Direction::Forward => {
+                    found.push(slice.len() - rest.len() + i);
+                    rest = &rest[i + 1..];
+                }
+                Direction::Backward => {
+                    found.push(i);
+                    rest = &rest[..i];
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+
+    #[test]
+    fn test_find_boundary() {
+        let scanner = Scanner::new(b'X', b'Y');
+        let s = b"XaXYXbYYXcYYYXX";
+        assert_eq!(boundaries(scanner, s, Direction::Forward), &[0, 2, 8, 14]);
+        assert_eq!(scanner.skip_boundaries(s, Direction::Forward), 1);
+        assert_eq!(scanner.skip_boundaries(s, Direction::Backward), 1);
+        assert_eq!(scanner.skip_boundaries(b"XXX", Direction::Backward), 3);
+        assert_eq!(scanner.find_boundary(b"", Direction::Backward), None);
+    }
+
+    /// Checks every byte, as [`Scanner::find_boundary`] did before it searched for the
+    /// delimiters.
+    fn find_boundary_bytewise(scanner: Scanner, slice: &[u8], dir: Direction) -> Option<usize> {
+        let is_boundary = |&i: &usize| scanner.is_boundary(slice, i);
+        match dir {
+            Direction::Forward => (0..slice.len()).find(is_boundary),
+            Direction::Backward => (0..slice.len()).rev().find(is_boundary),
+        }
+    }
+
+    #[test]
+    fn test_find_boundary_bytewise() {
+        let scanners = [
+            Scanner::new(b'X', b'Y'),
+            Scanner::new(b'X', b'X'),
+            Scanner::with_delims(b"Xa", b'Y'),
+            Scanner::with_delims(b"XY", b'Y'),
+        ]
+        .into_iter()
+        .flat_map(|s| [s, s.with_run(EscapeRun::Sticky)]);
+        for scanner in scanners {
+            for s in crate::test::strings(b"aXY", 8) {
+                // Padded, past the bytes checked one by one.
+                let pad = [b'a'; HEAD];
+                let padded = [&pad[..], &s, &pad].concat();
+                for (s, dir) in [&s, &padded]
+                    .into_iter()
+                    .flat_map(|s| [(s, Direction::Forward), (s, Direction::Backward)])
+                {
+                    assert_eq!(
+                        scanner.find_boundary(s, dir),
+                        find_boundary_bytewise(scanner, s, dir),
+                        "{:?}",
+                        s
+                    );
+                }
+            }
+        }
+
+        // Long enough for the vectorized search, with each byte from a xorshift.
+        let mut x = 0x2545_f491u32;
+        let s: Vec<_> = (0..1 << 16)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                [b'a', b'b', b'c', b'd', b'X', b'Y', b'Y', b'a'][x as usize % 8]
+            })
+            .collect();
+        let scanner = Scanner::new(b'X', b'Y');
+        for dir in [Direction::Forward, Direction::Backward] {
+            assert_eq!(
+                boundaries(scanner, &s, dir),
+                (0..s.len())
+                    .filter(|&i| scanner.is_boundary(&s, i))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_boundary_symmetric() {
+        // Exhaustive over short inputs, including an escape character of 0 and one equal to the
+        // delimiter.
+        let scanners = [(b'X', b'Y'), (b'X', 0), (b'X', b'X')]
+            .into_iter()
+            .map(|(delim, escape)| Scanner::new(delim, escape))
+            .flat_map(|s| [s, s.with_run(EscapeRun::Sticky)]);
+        for scanner in scanners {
+            let (delim, escape) = (scanner.delim, scanner.escape);
+            let alphabet = [b'a', delim, escape];
+            for len in 0..=8u32 {
+                for mut k in 0..3usize.pow(len) {
+                    let s: Vec<_> = (0..len)
+                        .map(|_| {
+                            let c = alphabet[k % 3];
+                            k /= 3;
+                            c
+                        })
+                        .collect();
+
+                    assert_eq!(
+                        boundaries(scanner, &s, Direction::Forward),
+                        boundaries(scanner, &s, Direction::Backward),
+                        "{:?}",
+                        s
+                    );
+                }
+            }
+        }
+    }
+}
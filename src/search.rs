@@ -0,0 +1,646 @@
+use std::borrow::Cow;
+
+use crate::scanner::{Direction, Scanner};
+use crate::{field_ranges, unescape, Iter};
+
+/// Returns whether the slice contains an unescaped delimiter, stopping at the first one.
+///
+/// ```
+/// use escaped_delimiter::has_delimiter;
+///
+/// assert!(has_delimiter(b"a\\ b c", b' ', b'\\'));
+/// assert!(!has_delimiter(b"a\\ b\\ c", b' ', b'\\'));
+/// ```
+#[inline]
+pub fn has_delimiter(slice: &[u8], delim: u8, escape: u8) -> bool {
+    Scanner::new(delim, escape)
+        .find_boundary(slice, Direction::Forward)
+        .is_some()
+}
+
+/// Returns whether the slice ends with an unescaped delimiter, which tells `a,b,` from `a,b`
+/// without [`Iter::keep_empty`].
+///
+/// ```
+/// use escaped_delimiter::had_trailing_delimiter;
+///
+/// assert!(had_trailing_delimiter(b"a b ", b' ', b'\\'));
+/// assert!(!had_trailing_delimiter(b"a b\\ ", b' ', b'\\'));
+/// assert!(!had_trailing_delimiter(b"a b", b' ', b'\\'));
+/// ```
+#[inline]
+pub fn had_trailing_delimiter(slice: &[u8], delim: u8, escape: u8) -> bool {
+    !slice.is_empty() && Scanner::new(delim, escape).is_boundary(slice, slice.len() - 1)
+}
+
+/// Returns whether the first unescaped field is `value`.
+///
+/// Only the first field is split and unescaped.
+///
+/// ```
+/// use escaped_delimiter::starts_with_field;
+///
+/// assert!(starts_with_field(b"a\\ b c", b' ', b'\\', b"a b"));
+/// assert!(!starts_with_field(b"a\\ b c", b' ', b'\\', b"a"));
+/// ```
+pub fn starts_with_field(slice: &[u8], delim: u8, escape: u8, value: &[u8]) -> bool {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .next()
+        .is_some_and(|field| *field == *value)
+}
+
+/// If the first field starts with `prefix` when unescaped, returns an iterator of the fields
+/// without it, so that the rest of the first field is the new first field.
+///
+/// Only the first field is split and unescaped, so an escaped delimiter in the field matches
+/// a delimiter in `prefix`. If `prefix` is the whole first field, the iterator starts at the
+/// second one.
+///
+/// ```
+/// use escaped_delimiter::strip_field_prefix;
+///
+/// let it = strip_field_prefix(b"v2:a,b,c", b',', b'\\', b"v2:").unwrap();
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"a"[..], &b"b"[..], &b"c"[..]]);
+///
+/// let it = strip_field_prefix(b"a\\,b,c", b',', b'\\', b"a,").unwrap();
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"b"[..], &b"c"[..]]);
+/// assert!(strip_field_prefix(b"v1:a,b", b',', b'\\', b"v2:").is_none());
+/// ```
+pub fn strip_field_prefix<'a>(
+    slice: &'a [u8],
+    delim: u8,
+    escape: u8,
+    prefix: &[u8],
+) -> Option<Iter<'a>> {
+    let field = Iter::from_slice(slice, delim, escape).next()?;
+    let start = field.as_ptr() as usize - slice.as_ptr() as usize;
+
+    let ends = token_ends(field, escape);
+    let end = match prefix.len() {
+        0 => 0,
+        n => *ends.get(n - 1)?,
+    };
+    let matched = unescape(&field[..end], escape);
+    (*matched == *prefix).then(|| Iter::from_slice(&slice[start + end..], delim, escape))
+}
+
+/// If the last field ends with `suffix` when unescaped, returns an iterator of the fields
+/// without it, so that the rest of the last field is the new last field.
+///
+/// This is the counterpart of [`strip_field_prefix`] at the end of the slice.
+///
+/// ```
+/// use escaped_delimiter::strip_field_suffix;
+///
+/// let it = strip_field_suffix(b"a,b,c\\,\\,", b',', b'\\', b",,").unwrap();
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"a"[..], &b"b"[..], &b"c"[..]]);
+/// assert!(strip_field_suffix(b"a,b", b',', b'\\', b",b").is_none());
+/// ```
+pub fn strip_field_suffix<'a>(
+    slice: &'a [u8],
+    delim: u8,
+    escape: u8,
+    suffix: &[u8],
+) -> Option<Iter<'a>> {
+    let field = Iter::from_slice(slice, delim, escape).next_back()?;
+    let start = field.as_ptr() as usize - slice.as_ptr() as usize;
+
+    // The raw field is cut right after the token which the suffix does not cover.
+    let ends = token_ends(field, escape);
+    let end = match ends.len().checked_sub(suffix.len())? {
+        0 => 0,
+        n => ends[n - 1],
+    };
+    let matched = unescape(&field[end..], escape);
+    (*matched == *suffix).then(|| Iter::from_slice(&slice[..start + end], delim, escape))
+}
+
+/// Returns the end of each byte which [`unescape`] yields for the field, as a raw position.
+fn token_ends(field: &[u8], escape: u8) -> Vec<usize> {
+    let mut ends = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        i += if field[i] == escape && i + 1 < field.len() {
+            2
+        } else {
+            1
+        };
+        ends.push(i);
+    }
+    ends
+}
+
+/// Returns whether the last unescaped field is `value`.
+///
+/// Only the last field is split and unescaped.
+///
+/// ```
+/// use escaped_delimiter::ends_with_field;
+///
+/// assert!(ends_with_field(b"a b\\ c", b' ', b'\\', b"b c"));
+/// assert!(!ends_with_field(b"a b\\ c", b' ', b'\\', b"c"));
+/// ```
+pub fn ends_with_field(slice: &[u8], delim: u8, escape: u8, value: &[u8]) -> bool {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .next_back()
+        .is_some_and(|field| *field == *value)
+}
+
+/// Returns the last raw field, scanning the slice from the back only as far as its start.
+///
+/// Trailing delimiters are skipped, and a trailing escape character stays in the field, as in
+/// [`iter`](crate::iter).
+///
+/// ```
+/// use escaped_delimiter::last_field;
+///
+/// assert_eq!(last_field(b"a b\\ c  ", b' ', b'\\'), Some(&b"b\\ c"[..]));
+/// assert_eq!(last_field(b"a b\\", b' ', b'\\'), Some(&b"b\\"[..]));
+/// assert_eq!(last_field(b"  ", b' ', b'\\'), None);
+/// ```
+#[inline]
+pub fn last_field(slice: &[u8], delim: u8, escape: u8) -> Option<&[u8]> {
+    Iter::from_slice(slice, delim, escape).next_back()
+}
+
+/// Returns the number of unescaped fields which are `value`.
+///
+/// A field is unescaped only if its raw length allows it to match: unescaping at most halves
+/// the length.
+///
+/// ```
+/// use escaped_delimiter::count_field_matches;
+///
+/// assert_eq!(count_field_matches(b"a b a\\ b a", b' ', b'\\', b"a"), 2);
+/// assert_eq!(count_field_matches(b"a b a\\ b a", b' ', b'\\', b"a b"), 1);
+/// ```
+pub fn count_field_matches(slice: &[u8], delim: u8, escape: u8, value: &[u8]) -> usize {
+    Iter::from_slice(slice, delim, escape)
+        .filter(|field| decodes_to(field, escape, value))
+        .count()
+}
+
+/// Returns the index of the first field which is `value` when unescaped.
+///
+/// As in [`count_field_matches`], a field is unescaped only if its raw length allows it to
+/// match.
+///
+/// ```
+/// use escaped_delimiter::position_of_decoded;
+///
+/// assert_eq!(position_of_decoded(b"a b a\\ b a", b' ', b'\\', b"a"), Some(0));
+/// assert_eq!(position_of_decoded(b"a b a\\ b a", b' ', b'\\', b"a b"), Some(2));
+/// assert_eq!(position_of_decoded(b"a b a\\ b a", b' ', b'\\', b"c"), None);
+/// ```
+pub fn position_of_decoded(slice: &[u8], delim: u8, escape: u8, value: &[u8]) -> Option<usize> {
+    Iter::from_slice(slice, delim, escape).position(|field| decodes_to(field, escape, value))
+}
+
+/// Returns the index of the last field which is `value` when unescaped.
+///
+/// The fields are searched from the back, and once one is found, those before it are counted
+/// without unescaping them.
+///
+/// ```
+/// use escaped_delimiter::rposition_of_decoded;
+///
+/// assert_eq!(rposition_of_decoded(b"a b a\\ b a", b' ', b'\\', b"a"), Some(3));
+/// assert_eq!(rposition_of_decoded(b"a b a\\ b a", b' ', b'\\', b"c"), None);
+/// ```
+pub fn rposition_of_decoded(slice: &[u8], delim: u8, escape: u8, value: &[u8]) -> Option<usize> {
+    let mut it = Iter::from_slice(slice, delim, escape);
+    it.by_ref()
+        .rfind(|field| decodes_to(field, escape, value))?;
+    Some(it.count())
+}
+
+/// Returns the index of a field which is `target` when unescaped, given that the unescaped
+/// fields are sorted, like [`slice::binary_search`].
+///
+/// The ranges of the fields are collected in a single pass first, and then only the fields
+/// visited by the binary search are unescaped. If several fields match, any one of them may be
+/// returned, and if the fields are not sorted, the result is meaningless.
+///
+/// ```
+/// use escaped_delimiter::find_sorted_field;
+///
+/// let s = b"apple b\\ c cherry date";
+/// assert_eq!(find_sorted_field(s, b' ', b'\\', b"b c"), Some(1));
+/// assert_eq!(find_sorted_field(s, b' ', b'\\', b"banana"), None);
+/// ```
+pub fn find_sorted_field(slice: &[u8], delim: u8, escape: u8, target: &[u8]) -> Option<usize> {
+    let ranges: Vec<_> = field_ranges(slice, delim, escape).collect();
+    ranges
+        .binary_search_by(|range| (*unescape(&slice[range.clone()], escape)).cmp(target))
+        .ok()
+}
+
+/// Returns whether the field is `value` when unescaped, checking the lengths first.
+#[inline]
+fn decodes_to(field: &[u8], escape: u8, value: &[u8]) -> bool {
+    field.len().div_ceil(2) <= value.len()
+        && value.len() <= field.len()
+        && *unescape(field, escape) == *value
+}
+
+/// Returns the index and the raw bytes of the field which contains the byte at `offset`.
+///
+/// An unescaped delimiter belongs to no field, so `None` is returned for its offset, as well as
+/// for an offset out of bounds.
+///
+/// ```
+/// use escaped_delimiter::field_at_offset;
+///
+/// let s = b"a  b\\ c";
+/// assert_eq!(field_at_offset(s, b' ', b'\\', 0), Some((0, &b"a"[..])));
+/// assert_eq!(field_at_offset(s, b' ', b'\\', 1), None);
+/// assert_eq!(field_at_offset(s, b' ', b'\\', 5), Some((1, &b"b\\ c"[..])));
+/// ```
+pub fn field_at_offset(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    offset: usize,
+) -> Option<(usize, &[u8])> {
+    let start = |field: &[u8]| field.as_ptr() as usize - slice.as_ptr() as usize;
+    Iter::from_slice(slice, delim, escape)
+        .enumerate()
+        .take_while(|(_, field)| start(field) <= offset)
+        .find(|(_, field)| offset < start(field) + field.len())
+}
+
+/// Returns the index and the unescaped bytes of the first field which satisfies `pred`.
+///
+/// Every field is unescaped before `pred` sees it, up to the one found, but only one with
+/// escape characters is allocated.
+///
+/// ```
+/// use escaped_delimiter::find_field;
+///
+/// let s = b"a b\\ c d";
+/// let found = find_field(s, b' ', b'\\', |f| f.contains(&b' '));
+/// assert_eq!(found, Some((1, b"b c"[..].into())));
+/// ```
+pub fn find_field<F: Fn(&[u8]) -> bool>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    pred: F,
+) -> Option<(usize, Cow<'_, [u8]>)> {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .enumerate()
+        .find(|(_, field)| pred(field))
+}
+
+/// Collects the unescaped fields which match the glob `pattern`.
+///
+/// In `pattern`, `*` matches any bytes and `?` matches any one byte. Every other byte, including
+/// the escape character, matches itself: there are no character classes, and no way of matching
+/// a literal `*` or `?` only.
+///
+/// ```
+/// use escaped_delimiter::fields_matching_glob;
+///
+/// let v = fields_matching_glob(b"id user_id user\\ name ip", b' ', b'\\', b"*id");
+/// assert_eq!(v, &[&b"id"[..], &b"user_id"[..]]);
+/// let v = fields_matching_glob(b"id user_id user\\ name ip", b' ', b'\\', b"* n?me");
+/// assert_eq!(v, &[&b"user name"[..]]);
+/// ```
+pub fn fields_matching_glob<'a>(
+    slice: &'a [u8],
+    delim: u8,
+    escape: u8,
+    pattern: &[u8],
+) -> Vec<Cow<'a, [u8]>> {
+    Iter::from_slice(slice, delim, escape)
+        .unescaped()
+        .filter(|field| glob_match(pattern, field))
+        .collect()
+}
+
+/// Returns whether `text` matches the glob `pattern` of [`fields_matching_glob`].
+///
+/// On a mismatch, only the last `*` is retried with one more byte, which is enough since any
+/// match of the earlier ones can be extended.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_has_delimiter() {
+        for s in [&b"X"[..], b"aX", b"YXX", b"YYX", b"aYXbYYYXcXd"] {
+            assert!(has_delimiter(s, b'X', b'Y'), "{:?}", s);
+        }
+        for s in [
+            &b""[..],
+            b"abc",
+            b"YX",
+            b"aYXb",
+            b"YYYXYX",
+            b"XY"[1..].as_ref(),
+        ] {
+            assert!(!has_delimiter(s, b'X', b'Y'), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_had_trailing_delimiter() {
+        for s in [
+            &b"X"[..],
+            b"aX",
+            b"aXX",
+            b"YYX",
+            b"aYXbX",
+            b"XY"[..1].as_ref(),
+        ] {
+            assert!(had_trailing_delimiter(s, b'X', b'Y'), "{:?}", s);
+        }
+        for s in [&b""[..], b"a", b"Xa", b"YX", b"aYX", b"aYYYX", b"aXY"] {
+            assert!(!had_trailing_delimiter(s, b'X', b'Y'), "{:?}", s);
+        }
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let ends_empty = Iter::from_slice(&s, b'X', b'Y')
+                .keep_empty()
+                .next_back()
+                .is_some_and(|f| f.is_empty());
+            assert_eq!(
+                had_trailing_delimiter(&s, b'X', b'Y'),
+                ends_empty && !s.is_empty(),
+                "{:?}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_starts_with_field() {
+        let s = b"XaYXbXcXdYX";
+        assert!(starts_with_field(s, b'X', b'Y', b"aXb"));
+        assert!(!starts_with_field(s, b'X', b'Y', b"aYXb"));
+        assert!(!starts_with_field(s, b'X', b'Y', b"a"));
+        assert!(!starts_with_field(b"", b'X', b'Y', b""));
+    }
+
+    #[test]
+    fn test_ends_with_field() {
+        let s = b"XaYXbXcXdYXX";
+        assert!(ends_with_field(s, b'X', b'Y', b"dX"));
+        assert!(!ends_with_field(s, b'X', b'Y', b"d"));
+        assert!(!ends_with_field(s, b'X', b'Y', b"c"));
+        assert!(!ends_with_field(b"XX", b'X', b'Y', b""));
+    }
+
+    #[test]
+    fn test_strip_field_prefix() {
+        let fields = |it: Option<Iter<'static>>| it.map(|it| it.collect::<Vec<_>>());
+
+        let s = b"XtYXYYaXbYXcXd";
+        assert_eq!(
+            fields(strip_field_prefix(s, b'X', b'Y', b"tX")),
+            Some(vec![&b"YYa"[..], b"bYXc", b"d"])
+        );
+        assert_eq!(
+            fields(strip_field_prefix(s, b'X', b'Y', b"tXY")),
+            Some(vec![&b"a"[..], b"bYXc", b"d"])
+        );
+        assert_eq!(
+            fields(strip_field_prefix(s, b'X', b'Y', b"tXYa")),
+            Some(vec![&b"bYXc"[..], b"d"])
+        );
+        assert_eq!(
+            fields(strip_field_prefix(s, b'X', b'Y', b"")),
+            fields(Some(Iter::from_slice(s, b'X', b'Y')))
+        );
+        assert_eq!(fields(strip_field_prefix(s, b'X', b'Y', b"tY")), None);
+        assert_eq!(fields(strip_field_prefix(s, b'X', b'Y', b"tXYab")), None);
+        assert_eq!(
+            fields(strip_field_prefix(b"aY", b'X', b'Y', b"aY")),
+            Some(vec![])
+        );
+        assert_eq!(fields(strip_field_prefix(b"XX", b'X', b'Y', b"")), None);
+    }
+
+    #[test]
+    fn test_strip_field_suffix() {
+        let fields = |it: Option<Iter<'static>>| it.map(|it| it.collect::<Vec<_>>());
+
+        let s = b"aXbYXcXdYYYXeXX";
+        assert_eq!(
+            fields(strip_field_suffix(s, b'X', b'Y', b"Xe")),
+            Some(vec![&b"a"[..], b"bYXc", b"dYY"])
+        );
+        assert_eq!(
+            fields(strip_field_suffix(s, b'X', b'Y', b"YXe")),
+            Some(vec![&b"a"[..], b"bYXc", b"d"])
+        );
+        assert_eq!(
+            fields(strip_field_suffix(s, b'X', b'Y', b"dYXe")),
+            Some(vec![&b"a"[..], b"bYXc"])
+        );
+        assert_eq!(fields(strip_field_suffix(s, b'X', b'Y', b"YYXe")), None);
+        assert_eq!(fields(strip_field_suffix(s, b'X', b'Y', b"cdYXe")), None);
+        assert_eq!(
+            fields(strip_field_suffix(b"aY", b'X', b'Y', b"Y")),
+            Some(vec![&b"a"[..]])
+        );
+
+        // Every split of the decoded last field is found.
+        let s = b"XaXYXbYYcY";
+        let decoded = unescape(Iter::from_slice(s, b'X', b'Y').next_back().unwrap(), b'Y');
+        for i in 1..=decoded.len() {
+            let it = strip_field_suffix(s, b'X', b'Y', &decoded[i..]).unwrap();
+            let last = it.unescaped().next_back().unwrap();
+            assert_eq!(last, &decoded[..i], "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_last_field() {
+        assert_eq!(last_field(b"aXbYXXX", b'X', b'Y'), Some(&b"bYX"[..]));
+        assert_eq!(last_field(b"aXbYYXX", b'X', b'Y'), Some(&b"bYY"[..]));
+        assert_eq!(last_field(b"aXbY", b'X', b'Y'), Some(&b"bY"[..]));
+        assert_eq!(last_field(b"", b'X', b'Y'), None);
+
+        for s in crate::test::strings(b"aXY", 9) {
+            // As found from the front.
+            let last = Iter::from_slice(&s, b'X', b'Y').collect::<Vec<_>>().pop();
+            assert_eq!(last_field(&s, b'X', b'Y'), last, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_field_at_offset() {
+        let s = b"XaYXbXXcYYXd";
+        let fields: Vec<_> = (0..s.len() + 2)
+            .map(|i| field_at_offset(s, b'X', b'Y', i).map(|(n, _)| n))
+            .collect();
+        let (a, b, c) = (Some(0), Some(1), Some(2));
+        assert_eq!(
+            fields,
+            &[None, a, a, a, a, None, None, b, b, b, None, c, None, None]
+        );
+
+        assert_eq!(field_at_offset(s, b'X', b'Y', 2), Some((0, &b"aYXb"[..])));
+        assert_eq!(field_at_offset(s, b'X', b'Y', 9), Some((1, &b"cYY"[..])));
+        assert_eq!(field_at_offset(b"", b'X', b'Y', 0), None);
+    }
+
+    #[test]
+    fn test_find_field() {
+        let s = b"XaXbYXcXdX";
+        let found = find_field(s, b'X', b'Y', |f| f == b"bXc");
+        assert_eq!(found, Some((1, Cow::Owned(b"bXc".to_vec()))));
+
+        let found = find_field(s, b'X', b'Y', |f| f.len() == 1 && f != b"a");
+        assert!(matches!(found, Some((2, Cow::Borrowed(b"d")))));
+
+        assert_eq!(find_field(s, b'X', b'Y', |f| f == b"bYXc"), None);
+        assert_eq!(find_field(b"", b'X', b'Y', |_| true), None);
+
+        // It stops at the first match.
+        let seen = std::cell::Cell::new(0);
+        find_field(s, b'X', b'Y', |f| {
+            seen.set(seen.get() + 1);
+            f == b"a"
+        });
+        assert_eq!(seen.get(), 1);
+    }
+
+    #[test]
+    fn test_position_of_decoded() {
+        let s = b"aXbYXcXaXbYXcXbYYXa";
+        let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+        for value in [&b"a"[..], b"bXc", b"bY", b"", b"d"] {
+            let expected = fields.iter().position(|f| **f == *value);
+            assert_eq!(position_of_decoded(s, b'X', b'Y', value), expected);
+            let expected = fields.iter().rposition(|f| **f == *value);
+            assert_eq!(rposition_of_decoded(s, b'X', b'Y', value), expected);
+        }
+
+        assert_eq!(position_of_decoded(s, b'X', b'Y', b"bXc"), Some(1));
+        assert_eq!(rposition_of_decoded(s, b'X', b'Y', b"bXc"), Some(3));
+        assert_eq!(rposition_of_decoded(s, b'X', b'Y', b"a"), Some(5));
+        assert_eq!(position_of_decoded(b"", b'X', b'Y', b""), None);
+    }
+
+    #[test]
+    fn test_find_sorted_field() {
+        let s = b"XaXaYXXbXbYYXcYXdXXe";
+        let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y').unescaped().collect();
+        assert_eq!(fields, &[&b"a"[..], b"aX", b"b", b"bY", b"cXd", b"e"]);
+        for (i, field) in fields.iter().enumerate() {
+            assert_eq!(find_sorted_field(s, b'X', b'Y', field), Some(i));
+        }
+        for target in [&b""[..], b"X", b"aa", b"aY", b"ba", b"c", b"cX", b"f"] {
+            assert_eq!(
+                find_sorted_field(s, b'X', b'Y', target),
+                None,
+                "{:?}",
+                target
+            );
+        }
+
+        assert_eq!(find_sorted_field(b"", b'X', b'Y', b""), None);
+        assert_eq!(
+            find_sorted_field(b"aXaXa", b'X', b'Y', b"a").map(|i| i < 3),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_count_field_matches() {
+        let s = b"aXYXXaXaYXaXYXYXXaYXXYaYX";
+        assert_eq!(count_field_matches(s, b'X', b'Y', b"a"), 2);
+        assert_eq!(count_field_matches(s, b'X', b'Y', b"X"), 1);
+        assert_eq!(count_field_matches(s, b'X', b'Y', b"aX"), 2);
+        assert_eq!(count_field_matches(s, b'X', b'Y', b"b"), 0);
+        assert_eq!(count_field_matches(s, b'X', b'Y', b""), 0);
+        assert_eq!(count_field_matches(b"YYXYYY", b'X', b'Y', b"YY"), 1);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        fn reference(pattern: &[u8], text: &[u8]) -> bool {
+            match pattern.split_first() {
+                None => text.is_empty(),
+                Some((b'*', rest)) => (0..=text.len()).any(|i| reference(rest, &text[i..])),
+                Some((&c, rest)) => text
+                    .split_first()
+                    .is_some_and(|(&t, text)| (c == b'?' || c == t) && reference(rest, text)),
+            }
+        }
+
+        let texts: Vec<_> = crate::test::strings(b"ab", 5).collect();
+        for pattern in crate::test::strings(b"a*?", 4) {
+            for text in &texts {
+                assert_eq!(
+                    glob_match(&pattern, text),
+                    reference(&pattern, text),
+                    "{:?} {:?}",
+                    pattern,
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields_matching_glob() {
+        let s = b"XfooXbarfooYXXYXfoobXaxXbxXxXfoXaxx";
+        type Case<'a> = (&'a [u8], &'a [&'a [u8]]);
+        let cases: &[Case] = &[
+            (b"*foo*", &[b"foo", b"barfooX", b"Xfoob"]),
+            (b"?x", &[b"ax", b"bx"]),
+            (b"*X*", &[b"barfooX", b"Xfoob"]),
+            (
+                b"*",
+                &[
+                    b"foo", b"barfooX", b"Xfoob", b"ax", b"bx", b"x", b"fo", b"axx",
+                ],
+            ),
+            (b"", &[]),
+            (b"YX*", &[]),
+        ];
+        for &(pattern, expected) in cases {
+            assert_eq!(
+                fields_matching_glob(s, b'X', b'Y', pattern),
+                expected,
+                "{:?}",
+                pattern
+            );
+        }
+
+        let v = fields_matching_glob(s, b'X', b'Y', b"foo");
+        assert!(matches!(v[..], [Cow::Borrowed(b"foo")]));
+    }
+}
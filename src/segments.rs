@@ -0,0 +1,150 @@
+use std::iter::FusedIterator;
+
+use crate::scanner::{Direction, Scanner};
+
+/// A raw field and the delimiters following it, yielded by [`raw_segments`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment<'a> {
+    /// The raw field, which is empty only for the leading delimiters of the slice.
+    pub field: &'a [u8],
+    /// The run of unescaped delimiters after the field, which is empty only at the end of the
+    /// slice.
+    pub delims: &'a [u8],
+}
+
+impl Segment<'_> {
+    /// Returns the number of delimiters after the field.
+    #[inline]
+    pub fn delim_count(&self) -> usize {
+        self.delims.len()
+    }
+}
+
+/// Returns an iterator of the raw fields with the exact delimiters between them, so that
+/// concatenating the segments gives back the slice byte for byte.
+///
+/// The fields are the same as those of [`iter`](crate::iter), except that leading delimiters
+/// come in a segment with an empty field.
+///
+/// ```
+/// use escaped_delimiter::{raw_segments, Segment};
+///
+/// let v: Vec<_> = raw_segments(b" a\\ b  c", b' ', b'\\').collect();
+/// assert_eq!(v, &[
+///     Segment { field: b"", delims: b" " },
+///     Segment { field: b"a\\ b", delims: b"  " },
+///     Segment { field: b"c", delims: b"" },
+/// ]);
+/// ```
+#[inline]
+pub fn raw_segments(slice: &[u8], delim: u8, escape: u8) -> RawSegments<'_> {
+    RawSegments {
+        scanner: Scanner::new(delim, escape),
+        inner: slice,
+    }
+}
+
+/// An iterator of the [`Segment`]s of a slice, returned by [`raw_segments`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawSegments<'a> {
+    scanner: Scanner,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for RawSegments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        let rest = self.inner;
+        let end = self
+            .scanner
+            .find_boundary(rest, Direction::Forward)
+            .unwrap_or(rest.len());
+        let run = self
+            .scanner
+            .skip_boundaries(&rest[end..], Direction::Forward);
+        self.inner = &rest[end + run..];
+
+        Some(Segment {
+            field: &rest[..end],
+            delims: &rest[end..end + run],
+        })
+    }
+}
+
+impl<'a> DoubleEndedIterator for RawSegments<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        let rest = self.inner;
+        let end = rest.len() - self.scanner.skip_boundaries(rest, Direction::Backward);
+        let start = self
+            .scanner
+            .find_boundary(&rest[..end], Direction::Backward)
+            .map_or(0, |i| i + 1);
+        self.inner = &rest[..start];
+
+        Some(Segment {
+            field: &rest[start..end],
+            delims: &rest[end..],
+        })
+    }
+}
+
+impl FusedIterator for RawSegments<'_> {}
+
+impl<'a> RawSegments<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Iter;
+
+    #[test]
+    fn test_raw_segments() {
+        let inputs: &[&[u8]] = &[b"", b"X", b"abc", b"XXaYXbXXXcYYXdYYYXXeY", b"aXYXbXX"];
+
+        for s in inputs {
+            let segments: Vec<_> = raw_segments(s, b'X', b'Y').collect();
+            let concat: Vec<u8> = segments
+                .iter()
+                .flat_map(|seg| [seg.field, seg.delims].concat())
+                .collect();
+            assert_eq!(&concat, s);
+
+            let fields: Vec<_> = segments
+                .iter()
+                .map(|seg| seg.field)
+                .filter(|f| !f.is_empty())
+                .collect();
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            assert_eq!(fields, expected);
+
+            let mut rsegments: Vec<_> = raw_segments(s, b'X', b'Y').rev().collect();
+            rsegments.reverse();
+            assert_eq!(segments, rsegments);
+        }
+
+        let mut segments = raw_segments(b"XaXXbYX", b'X', b'Y');
+        let seg = segments.next().unwrap();
+        assert_eq!((seg.field, seg.delim_count()), (&b""[..], 1));
+        let seg = segments.next_back().unwrap();
+        assert_eq!((seg.field, seg.delim_count()), (&b"bYX"[..], 0));
+        let seg = segments.next().unwrap();
+        assert_eq!((seg.field, seg.delim_count()), (&b"a"[..], 2));
+        assert_eq!(segments.next_back(), None);
+    }
+}
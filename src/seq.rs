@@ -0,0 +1,277 @@
+use std::ops::Range;
+
+use crate::scanner::is_unescaped;
+
+/// Returns an iterator of fields separated by a delimiter of several bytes, such as `\r\n`.
+///
+/// An escape character right before the delimiter makes the whole sequence literal, and escape
+/// characters pair up as in [`iter`](crate::iter), so `\\\r\n` is a literal escape character
+/// followed by a delimiter. Empty fields are skipped, and [`unescape`](crate::unescape) decodes
+/// the fields.
+///
+/// An escape character only ever applies to the one byte after it. If that byte starts the
+/// delimiter, the whole delimiter is escaped; if not, the escape character may still be right
+/// before a delimiter starting at the next byte, as in `\\\r\r\n`. An escape character inside the
+/// sequence, as in `\r\\\n`, breaks it, so there is no delimiter at all.
+///
+/// The slice is read from the front, so if the delimiter can overlap itself, like `::` in `:::`,
+/// the first occurrence wins, in both directions.
+///
+/// # Panics
+///
+/// Panics if `delim` is empty or contains the escape character.
+///
+/// ```
+/// use escaped_delimiter::{iter_seq, unescape};
+///
+/// let s = b"a\\\r\nb\r\nc\\\\\r\n\\\rd";
+/// let s_vec: Vec<_> = iter_seq(s, b"\r\n", b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a\\\r\nb"[..], &b"c\\\\"[..], &b"\\\rd"[..]]);
+/// assert_eq!(unescape(s_vec[0], b'\\'), &b"a\r\nb"[..]);
+/// ```
+#[inline]
+pub fn iter_seq<'a>(slice: &'a [u8], delim: &'a [u8], escape: u8) -> SeqIter<'a> {
+    SeqIter::from_slice(slice, delim, escape)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeqIter<'a> {
+    delim: &'a [u8],
+    escape: u8,
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for SeqIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.find_boundary() {
+                Some(i) => {
+                    let field = &self.inner[..i];
+                    self.inner = &self.inner[i + self.delim.len()..];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DoubleEndedIterator for SeqIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while !self.inner.is_empty() {
+            let field = match self.rfind_boundary() {
+                Some(i) => {
+                    let field = &self.inner[i + self.delim.len()..];
+                    self.inner = &self.inner[..i];
+                    field
+                }
+                None => std::mem::take(&mut self.inner),
+            };
+
+            if !field.is_empty() {
+                return Some(field);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> SeqIter<'a> {
+    /// Returns the start of the first boundary, i.e. of the first delimiter not consumed by an
+    /// escape character or by an earlier delimiter.
+    fn find_boundary(&self) -> Option<usize> {
+        let mut i = 0;
+        while let Some(start) = self.find_delim(i..self.inner.len()) {
+            if is_unescaped(self.inner, start, self.escape) {
+                return Some(start);
+            }
+            i = start + self.delim.len();
+        }
+
+        None
+    }
+
+    /// Returns the start of the last boundary.
+    ///
+    /// Occurrences of the delimiter which overlap each other are read from the first one, which
+    /// no earlier occurrence overlaps, so the same ones are boundaries as from the front. No
+    /// escape character is among them, since the delimiter has none.
+    fn rfind_boundary(&self) -> Option<usize> {
+        let n = self.delim.len();
+        let mut end = self.inner.len();
+        while let Some(last) = self.rfind_delim(0..end) {
+            let mut start = last;
+            while let Some(i) = self.find_delim(start.saturating_sub(n - 1)..start + n - 1) {
+                start = i;
+            }
+
+            let mut boundary = None;
+            let mut escaped = !is_unescaped(self.inner, start, self.escape);
+            let mut i = start;
+            while i <= last {
+                if self.is_delim_at(i) {
+                    if !escaped {
+                        boundary = Some(i);
+                    }
+                    escaped = false;
+                    i += n;
+                } else {
+                    i += 1;
+                }
+            }
+
+            if boundary.is_some() {
+                return boundary;
+            }
+            end = start;
+        }
+
+        None
+    }
+
+    #[inline]
+    fn is_delim_at(&self, i: usize) -> bool {
+        self.inner[i..].starts_with(self.delim)
+    }
+
+    /// Returns the start of the first occurrence of the delimiter within `range`.
+    #[inline]
+    fn find_delim(&self, range: Range<usize>) -> Option<usize> {
+        let start = range.start;
+        self.inner[range]
+            .windows(self.delim.len())
+            .position(|w| w == self.delim)
+            .map(|i| start + i)
+    }
+
+    /// Returns the start of the last occurrence of the delimiter within `range`.
+    #[inline]
+    fn rfind_delim(&self, range: Range<usize>) -> Option<usize> {
+        let start = range.start;
+        self.inner[range]
+            .windows(self.delim.len())
+            .rposition(|w| w == self.delim)
+            .map(|i| start + i)
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner
+    }
+
+    /// See [`iter_seq`].
+    #[inline]
+    pub fn from_slice(inner: &'a [u8], delim: &'a [u8], escape: u8) -> Self {
+        assert!(!delim.is_empty(), "the delimiter is empty");
+        assert!(
+            !delim.contains(&escape),
+            "the delimiter contains the escape character"
+        );
+        Self {
+            delim,
+            escape,
+            inner,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Splits the slice by reading it from the front, one token at a time.
+    fn split_seq<'a>(s: &'a [u8], delim: &[u8]) -> Vec<&'a [u8]> {
+        let mut fields = Vec::new();
+        let (mut start, mut i) = (0, 0);
+        while i < s.len() {
+            if s[i] == b'Y' {
+                i += if s[i + 1..].starts_with(delim) {
+                    1 + delim.len()
+                } else {
+                    2
+                };
+            } else if s[i..].starts_with(delim) {
+                fields.push(&s[start..i]);
+                i += delim.len();
+                start = i;
+            } else {
+                i += 1;
+            }
+        }
+        fields.push(&s[start..]);
+        fields.retain(|f| !f.is_empty());
+        fields
+    }
+
+    #[test]
+    fn test_seq() {
+        let s = b"aYXZbXZcYYXZYXdXZZXZYYYXZ";
+        let mut words = iter_seq(s, b"XZ", b'Y');
+        assert_eq!(words.next(), Some(&b"aYXZb"[..]));
+        assert_eq!(words.next(), Some(&b"cYY"[..]));
+        assert_eq!(words.next(), Some(&b"YXd"[..]));
+        assert_eq!(words.next_back(), Some(&b"YYYXZ"[..]));
+        assert_eq!(words.next_back(), Some(&b"Z"[..]));
+        assert_eq!(words.next(), None);
+
+        // An escape character before only the first byte of the delimiter escapes the whole.
+        let v: Vec<_> = iter_seq(b"aYXZb", b"XZ", b'Y').rev().collect();
+        assert_eq!(v, &[&b"aYXZb"[..]]);
+        // One before a lone first byte leaves the delimiter right after it.
+        let v: Vec<_> = iter_seq(b"aYXXZb", b"XZ", b'Y').rev().collect();
+        assert_eq!(v, &[&b"b"[..], &b"aYX"[..]]);
+        // One inside the delimiter breaks it.
+        let v: Vec<_> = iter_seq(b"aXYZb", b"XZ", b'Y').collect();
+        assert_eq!(v, &[&b"aXYZb"[..]]);
+
+        // Overlapping occurrences are read from the front.
+        let v: Vec<_> = iter_seq(b"aXXXb", b"XX", b'Y').rev().collect();
+        assert_eq!(v, &[&b"Xb"[..], &b"a"[..]]);
+        let v: Vec<_> = iter_seq(b"aYXXXb", b"XX", b'Y').rev().collect();
+        assert_eq!(v, &[&b"aYXXXb"[..]]);
+    }
+
+    #[test]
+    fn test_seq_exhaustive() {
+        for delim in [&b"XZ"[..], b"XX", b"XZX", b"X"] {
+            for s in crate::test::strings(b"aXYZ", 7) {
+                let expected = split_seq(&s, delim);
+                let words: Vec<_> = iter_seq(&s, delim, b'Y').collect();
+                assert_eq!(words, expected, "{:?}", s);
+
+                let mut rwords: Vec<_> = iter_seq(&s, delim, b'Y').rev().collect();
+                rwords.reverse();
+                assert_eq!(rwords, expected, "{:?}", s);
+
+                // From both ends at once.
+                let mut it = iter_seq(&s, delim, b'Y');
+                let (mut front, mut back) = (Vec::new(), Vec::new());
+                while let Some(field) = it.next() {
+                    front.push(field);
+                    back.extend(it.next_back());
+                }
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, expected, "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_seq_single_byte() {
+        for s in crate::test::strings(b"aXY", 8) {
+            let words: Vec<_> = iter_seq(&s, b"X", b'Y').collect();
+            let expected: Vec<_> = crate::Iter::from_slice(&s, b'X', b'Y').collect();
+            assert_eq!(words, expected, "{:?}", s);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::escape::unescape_into_with;
+use crate::Iter;
+
+/// A destination for unescaped fields, used by [`Iter::collect_in`].
+///
+/// Implement it for an arena or any other storage to collect the fields without a `Vec` for each.
+pub trait FieldSink {
+    /// Pushes a field which has nothing to unescape, straight from the slice.
+    fn push_field(&mut self, field: &[u8]);
+
+    /// Pushes a field which has been unescaped into a scratch buffer, reused for the next field.
+    ///
+    /// Defaults to [`FieldSink::push_field`].
+    #[inline]
+    fn push_decoded(&mut self, decoded: &[u8]) {
+        self.push_field(decoded);
+    }
+}
+
+impl FieldSink for Vec<Vec<u8>> {
+    #[inline]
+    fn push_field(&mut self, field: &[u8]) {
+        self.push(field.to_vec());
+    }
+}
+
+impl Iter<'_> {
+    /// Pushes every unescaped field into `sink`.
+    ///
+    /// Fields with nothing to unescape go to [`FieldSink::push_field`], and the others to
+    /// [`FieldSink::push_decoded`]. As in [`unescaped`](Iter::unescaped), the escape characters
+    /// before bytes which are not special to [`Iter::with_specials`] are kept.
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let mut v: Vec<Vec<u8>> = Vec::new();
+    /// iter(b"a\\ b c", b' ', b'\\').collect_in(&mut v);
+    /// assert_eq!(v, &[&b"a b"[..], &b"c"[..]]);
+    /// ```
+    pub fn collect_in<A: FieldSink>(self, sink: &mut A) {
+        let escape = self.escape;
+        let is_special = |c| self.is_special(c);
+        let mut buf = Vec::new();
+        for field in self {
+            if field.windows(2).any(|w| w[0] == escape && is_special(w[1])) {
+                buf.clear();
+                unescape_into_with(field, escape, is_special, &mut buf);
+                sink.push_decoded(&buf);
+            } else {
+                sink.push_field(field);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_collect_in() {
+        for s in crate::test::strings(b"aXY", 6) {
+            let mut v = Vec::new();
+            Iter::from_slice(&s, b'X', b'Y').collect_in(&mut v);
+            assert_eq!(
+                v,
+                Iter::from_slice(&s, b'X', b'Y').to_owned_fields(),
+                "{:?}",
+                s
+            );
+
+            let mut v = Vec::new();
+            let words = Iter::with_specials(&s, b'X', b'Y', b"a");
+            words.collect_in(&mut v);
+            let expected: Vec<_> = words.unescaped().map(|f| f.into_owned()).collect();
+            assert_eq!(v, expected, "{:?}", s);
+        }
+
+        let mut v = Vec::new();
+        Iter::with_specials(b"a\\qb c", b' ', b'\\', b"#").collect_in(&mut v);
+        assert_eq!(v, &[&b"a\\qb"[..], b"c"]);
+    }
+
+    #[test]
+    fn test_collect_in_arena() {
+        #[derive(Default)]
+        struct Arena {
+            bytes: Vec<u8>,
+            ends: Vec<usize>,
+            decoded: usize,
+        }
+        impl FieldSink for Arena {
+            fn push_field(&mut self, field: &[u8]) {
+                self.bytes.extend_from_slice(field);
+                self.ends.push(self.bytes.len());
+            }
+            fn push_decoded(&mut self, decoded: &[u8]) {
+                self.decoded += 1;
+                self.push_field(decoded);
+            }
+        }
+
+        let mut arena = Arena::default();
+        Iter::from_slice(b"aXbYXcXYYXd", b'X', b'Y').collect_in(&mut arena);
+        assert_eq!(arena.bytes, b"abXcYd");
+        assert_eq!(arena.ends, &[1, 4, 5, 6]);
+        assert_eq!(arena.decoded, 2);
+    }
+}
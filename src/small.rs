@@ -0,0 +1,36 @@
+use smallvec::SmallVec;
+
+use crate::Iter;
+
+/// Collects the raw fields into a [`SmallVec`], which keeps up to `N` of them inline before
+/// spilling to the heap.
+///
+/// ```
+/// use escaped_delimiter::collect_small;
+///
+/// let fields = collect_small::<4>(b"a b\\ c", b' ', b'\\');
+/// assert_eq!(&fields[..], &[&b"a"[..], &b"b\\ c"[..]]);
+/// assert!(!fields.spilled());
+/// ```
+#[inline]
+pub fn collect_small<const N: usize>(slice: &[u8], delim: u8, escape: u8) -> SmallVec<[&[u8]; N]> {
+    Iter::from_slice(slice, delim, escape).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_collect_small() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"aXbYXc", b"aXbXcXd", b"aXbXcXdXeYYXf"];
+        for s in inputs {
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            let fields = collect_small::<4>(s, b'X', b'Y');
+            assert_eq!(&fields[..], &expected[..]);
+            assert_eq!(fields.spilled(), expected.len() > 4);
+        }
+
+        assert!(collect_small::<0>(b"a", b'X', b'Y').spilled());
+    }
+}
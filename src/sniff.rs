@@ -0,0 +1,124 @@
+use crate::scanner::{Direction, Scanner};
+use crate::Iter;
+
+/// Returns the first of `candidates` which appears unescaped in the slice, and an iterator
+/// splitting on it.
+///
+/// If none of them appears, the first candidate is returned, so that the iterator yields the
+/// whole slice as one field.
+///
+/// # Panics
+///
+/// Panics if `candidates` is empty.
+///
+/// ```
+/// use escaped_delimiter::auto_delim;
+///
+/// let (delim, it) = auto_delim(b"a,b\tc,d", b"\t, ", b'\\');
+/// assert_eq!(delim, b'\t');
+/// assert_eq!(it.collect::<Vec<_>>(), &[&b"a,b"[..], &b"c,d"[..]]);
+///
+/// let (delim, _) = auto_delim(b"a\\\tb,c", b"\t, ", b'\\');
+/// assert_eq!(delim, b',');
+/// ```
+pub fn auto_delim<'a>(slice: &'a [u8], candidates: &[u8], escape: u8) -> (u8, Iter<'a>) {
+    let first = *candidates.first().expect("no candidate delimiter");
+    let delim = candidates
+        .iter()
+        .copied()
+        .find(|&delim| {
+            Scanner::new(delim, escape)
+                .find_boundary(slice, Direction::Forward)
+                .is_some()
+        })
+        .unwrap_or(first);
+
+    (delim, Iter::from_slice(slice, delim, escape))
+}
+
+/// Guesses which of `candidates` separates the columns of the lines of the slice, or returns
+/// `None` if none of them appears unescaped.
+///
+/// The slice is split into lines on unescaped `\n`, and a candidate scores by how many lines
+/// have the same, non-zero number of unescaped occurrences of it. The candidate with the most
+/// such lines wins, then that with the most occurrences per line, then the earlier one.
+///
+/// ```
+/// use escaped_delimiter::sniff_delimiter;
+///
+/// let s = b"name,note\nAlice,hi there\nBob,a\\, b c d\n";
+/// assert_eq!(sniff_delimiter(s, b" ,;", b'\\'), Some(b','));
+/// assert_eq!(sniff_delimiter(b"abc\ndef", b" ,;", b'\\'), None);
+/// ```
+pub fn sniff_delimiter(slice: &[u8], candidates: &[u8], escape: u8) -> Option<u8> {
+    let mut best = None;
+    for &delim in candidates {
+        let mut counts: Vec<_> = Iter::from_slice(slice, b'\n', escape)
+            .map(|line| Iter::from_slice(line, delim, escape).count_delimiters())
+            .filter(|&n| n > 0)
+            .collect();
+        counts.sort_unstable();
+
+        // The number of lines of the most common count, and the count.
+        let score = counts
+            .chunk_by(|a, b| a == b)
+            .map(|run| (run.len(), run[0]))
+            .max();
+        if let Some(score) = score {
+            if best.is_none_or(|(best, _)| score > best) {
+                best = Some((score, delim));
+            }
+        }
+    }
+
+    best.map(|(_, delim)| delim)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_auto_delim() {
+        let cases: &[(&[u8], u8)] = &[
+            (b"aZbYcXd", b'X'),
+            (b"aZbYc", b'Y'),
+            (b"aZb", b'Z'),
+            (b"aEXbZc", b'Z'),
+            (b"aEXbEEYc", b'Y'),
+            (b"aEXbEYc", b'X'),
+            (b"abc", b'X'),
+            (b"", b'X'),
+        ];
+
+        for &(s, expected) in cases {
+            let (delim, it) = auto_delim(s, b"XYZ", b'E');
+            assert_eq!(delim, expected, "{:?}", s);
+            assert_eq!(it, Iter::from_slice(s, expected, b'E'));
+        }
+
+        let (delim, it) = auto_delim(b"aEXbEYc", b"XY", b'E');
+        assert_eq!(delim, b'X');
+        assert_eq!(it.collect::<Vec<_>>(), &[&b"aEXbEYc"[..]]);
+    }
+
+    #[test]
+    fn test_sniff_delimiter() {
+        let s = b"id;name;comment\n1;Alice;a, b, c\n2;Bob;hello\n\n3;Carol;x,y\n";
+        assert_eq!(sniff_delimiter(s, b",;\t", b'\\'), Some(b';'));
+        assert_eq!(sniff_delimiter(s, b",\t", b'\\'), Some(b','));
+
+        // Escaped occurrences do not count, and neither do escaped newlines split lines.
+        let s = b"a,b;c\nd,e\\;f\ng,h";
+        assert_eq!(sniff_delimiter(s, b";,", b'\\'), Some(b','));
+        let s = b"a;b\\\nc;d\ne;f";
+        assert_eq!(sniff_delimiter(s, b",;", b'\\'), Some(b';'));
+
+        // A tie goes to the earlier candidate.
+        assert_eq!(sniff_delimiter(b"a,b;c", b";,", b'\\'), Some(b';'));
+
+        assert_eq!(sniff_delimiter(b"abc", b",;", b'\\'), None);
+        assert_eq!(sniff_delimiter(b"a\\,b\n", b",;", b'\\'), None);
+        assert_eq!(sniff_delimiter(b"", b",;", b'\\'), None);
+    }
+}
@@ -0,0 +1,673 @@
+use std::borrow::Cow;
+
+use crate::scanner::{is_unescaped, Direction, Scanner};
+use crate::{unescape, Iter};
+
+/// Same as [`slice::split`], but skips the delimiters escaped by `escape`.
+///
+/// As in [`slice::split`], consecutive delimiters are not collapsed: see
+/// [`Iter::keep_empty`](crate::Iter::keep_empty). As in [`iter`](crate::iter), if `pred` returns
+/// `true` for the escape character, it always splits, and then escapes nothing.
+///
+/// ```
+/// use escaped_delimiter::split_escaped;
+///
+/// let s_vec: Vec<_> = split_escaped(b"a,b\\;c;;d", |c| c == b',' || c == b';', b'\\').collect();
+/// assert_eq!(s_vec, &[&b"a"[..], &b"b\\;c"[..], &b""[..], &b"d"[..]]);
+/// ```
+#[inline]
+pub fn split_escaped<P: FnMut(u8) -> bool>(
+    slice: &[u8],
+    pred: P,
+    escape: u8,
+) -> SplitEscaped<'_, P> {
+    SplitEscaped {
+        inner: slice,
+        pred,
+        escape,
+        finished: false,
+    }
+}
+
+/// Splits the slice at the first unescaped delimiter at or after `offset`, which belongs to
+/// neither half.
+///
+/// Neither half has a part of a field, so this is useful to cut a slice into chunks of about
+/// the same size. If there is no such delimiter, the second half is empty.
+///
+/// ```
+/// use escaped_delimiter::split_at_offset;
+///
+/// let s = b"ab c\\ d e";
+/// assert_eq!(split_at_offset(s, b' ', b'\\', 1), (&b"ab"[..], &b"c\\ d e"[..]));
+/// assert_eq!(split_at_offset(s, b' ', b'\\', 4), (&b"ab c\\ d"[..], &b"e"[..]));
+/// assert_eq!(split_at_offset(s, b' ', b'\\', 8), (&s[..], &b""[..]));
+/// ```
+pub fn split_at_offset(slice: &[u8], delim: u8, escape: u8, offset: usize) -> (&[u8], &[u8]) {
+    let scanner = Scanner::new(delim, escape);
+    match (offset..slice.len()).find(|&i| scanner.is_boundary(slice, i)) {
+        Some(i) => (&slice[..i], &slice[i + 1..]),
+        None => (slice, &[]),
+    }
+}
+
+/// Splits the slice at the `n`th unescaped delimiter, counting from 0, which belongs to neither
+/// half.
+///
+/// Every delimiter is counted, even the consecutive ones, so unlike
+/// [`Iter::split_at_field`](crate::Iter::split_at_field) this counts the empty fields too.
+/// Returns `None` if there are not as many delimiters.
+///
+/// ```
+/// use escaped_delimiter::split_at_nth_delimiter;
+///
+/// let s = b"a,,b\\,c,d";
+/// assert_eq!(split_at_nth_delimiter(s, b',', b'\\', 1), Some((&b"a,"[..], &b"b\\,c,d"[..])));
+/// assert_eq!(split_at_nth_delimiter(s, b',', b'\\', 2), Some((&b"a,,b\\,c"[..], &b"d"[..])));
+/// assert_eq!(split_at_nth_delimiter(s, b',', b'\\', 3), None);
+/// ```
+pub fn split_at_nth_delimiter(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    n: usize,
+) -> Option<(&[u8], &[u8])> {
+    let scanner = Scanner::new(delim, escape);
+    let i = (0..slice.len())
+        .filter(|&i| scanner.is_boundary(slice, i))
+        .nth(n)?;
+    Some((&slice[..i], &slice[i + 1..]))
+}
+
+/// Returns the first fields whose lengths add up to at most `max_bytes`, and the rest of the
+/// slice after them as is.
+///
+/// The lengths are those of the raw fields, without the delimiters. A first field longer than
+/// `max_bytes` is returned alone, so that chunking a slice with this always makes progress. The
+/// rest starts as in [`split_limited`].
+///
+/// ```
+/// use escaped_delimiter::fields_until_bytes;
+///
+/// let (chunk, rest) = fields_until_bytes(b"ab c\\ d e", b' ', b'\\', 5);
+/// assert_eq!(chunk, &[&b"ab"[..]]);
+/// assert_eq!(rest, b"c\\ d e");
+///
+/// let (chunk, rest) = fields_until_bytes(rest, b' ', b'\\', 3);
+/// assert_eq!(chunk, &[&b"c\\ d"[..]]);
+/// assert_eq!(rest, b"e");
+/// ```
+pub fn fields_until_bytes(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    max_bytes: usize,
+) -> (Vec<&[u8]>, &[u8]) {
+    let it = Iter::from_slice(slice, delim, escape);
+    let mut total = 0;
+    let fields: Vec<_> = it
+        .enumerate()
+        .take_while(|(i, field)| {
+            total += field.len();
+            *i == 0 || total <= max_bytes
+        })
+        .map(|(_, field)| field)
+        .collect();
+    let (_, tail) = it.split_at_field(fields.len());
+    (fields, tail.as_slice())
+}
+
+/// Returns the first `limit` fields, and the rest of the slice after them as is, if it has any
+/// more fields.
+///
+/// The rest starts right after the delimiter which ends the last field returned, as in
+/// [`Iter::split_at_field`](crate::Iter::split_at_field), so it may start with more
+/// delimiters. Unlike the last field of [`slice::splitn`], it is returned on its own.
+///
+/// ```
+/// use escaped_delimiter::split_limited;
+///
+/// let s = b"GET /a\\ b HTTP/1.1 body";
+/// let (head, rest) = split_limited(s, b' ', b'\\', 2);
+/// assert_eq!(head, &[&b"GET"[..], &b"/a\\ b"[..]]);
+/// assert_eq!(rest, Some(&b"HTTP/1.1 body"[..]));
+/// assert_eq!(split_limited(s, b' ', b'\\', 4).1, None);
+/// ```
+pub fn split_limited(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    limit: usize,
+) -> (Vec<&[u8]>, Option<&[u8]>) {
+    let (head, tail) = Iter::from_slice(slice, delim, escape).split_at_field(limit);
+    let mut probe = tail;
+    let rest = probe.next().map(|_| tail.as_slice());
+    (head.collect(), rest)
+}
+
+/// Splits the slice at the last unescaped delimiter, which belongs to neither half, and unescapes
+/// both halves.
+///
+/// The head is unescaped as a single field, even if it has unescaped delimiters of its own,
+/// which are kept as is; only the escape characters are removed. Trailing delimiters are not
+/// skipped, as in [`slice::rsplit_once`], so the tail may be empty.
+///
+/// ```
+/// use escaped_delimiter::rsplit_once_decoded;
+///
+/// let (head, tail) = rsplit_once_decoded(b"a=b\\=c=d\\=e", b'=', b'\\').unwrap();
+/// assert_eq!((&*head, &*tail), (&b"a=b=c"[..], &b"d=e"[..]));
+/// assert_eq!(rsplit_once_decoded(b"a\\=b", b'=', b'\\'), None);
+/// ```
+pub fn rsplit_once_decoded(slice: &[u8], delim: u8, escape: u8) -> Option<Halves<'_>> {
+    let i = Scanner::new(delim, escape).find_boundary(slice, Direction::Backward)?;
+    Some((
+        unescape(&slice[..i], escape),
+        unescape(&slice[i + 1..], escape),
+    ))
+}
+
+/// Returns an iterator of the tokens of a `cmd.exe` command line, which are separated by runs of
+/// space, `&` and `|`, with `^` as the escape character.
+///
+/// Decode a token with [`unescape`], which removes each `^` and keeps the byte after it, even if
+/// it is not special, as `cmd.exe` does. A lone `^` at the end of the slice, which continues the
+/// line in `cmd.exe`, is dropped. The operators made of `&` and `|` are not told apart, and
+/// double quotes, within which `cmd.exe` neither splits nor escapes, are out of scope: they are
+/// bytes like any other.
+///
+/// ```
+/// use escaped_delimiter::{cmd_tokenize, unescape};
+///
+/// let s_vec: Vec<_> = cmd_tokenize(b"echo a^&b && dir^ x | more^").collect();
+/// assert_eq!(s_vec, &[&b"echo"[..], &b"a^&b"[..], &b"dir^ x"[..], &b"more"[..]]);
+/// assert_eq!(unescape(s_vec[1], b'^'), &b"a&b"[..]);
+/// ```
+pub fn cmd_tokenize(slice: &[u8]) -> impl DoubleEndedIterator<Item = &[u8]> {
+    let slice = match slice.len().checked_sub(1) {
+        Some(last) if slice[last] == b'^' && is_unescaped(slice, last, b'^') => &slice[..last],
+        _ => slice,
+    };
+    split_escaped(slice, |c| matches!(c, b' ' | b'&' | b'|'), b'^').filter(|t| !t.is_empty())
+}
+
+/// The unescaped head and tail returned by [`rsplit_once_decoded`].
+type Halves<'a> = (Cow<'a, [u8]>, Cow<'a, [u8]>);
+
+/// Same as [`str::split_whitespace`], but skips the whitespace escaped by `escape`.
+///
+/// The whitespace is ASCII: space, `\t`, `\n`, `\x0b` (VT), `\x0c` (FF), and `\r`. Runs of
+/// whitespace are collapsed, so no field is empty.
+///
+/// ```
+/// use escaped_delimiter::split_whitespace_escaped;
+///
+/// let s_vec: Vec<_> = split_whitespace_escaped(b" cp a\\ b\t\n c ", b'\\').collect();
+/// assert_eq!(s_vec, &[&b"cp"[..], &b"a\\ b"[..], &b"c"[..]]);
+/// ```
+#[inline]
+pub fn split_whitespace_escaped(slice: &[u8], escape: u8) -> SplitWhitespaceEscaped<'_> {
+    SplitWhitespaceEscaped {
+        inner: split_escaped(slice, is_whitespace, escape),
+    }
+}
+
+fn is_whitespace(c: u8) -> bool {
+    c.is_ascii_whitespace() || c == b'\x0b'
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitEscaped<'a, P> {
+    inner: &'a [u8],
+    pred: P,
+    escape: u8,
+    finished: bool,
+}
+
+impl<'a, P: FnMut(u8) -> bool> Iterator for SplitEscaped<'a, P> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.inner;
+        match (0..slice.len()).find(|&i| self.is_boundary(i)) {
+            Some(i) => {
+                self.inner = &slice[i + 1..];
+                Some(&slice[..i])
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+}
+
+impl<'a, P: FnMut(u8) -> bool> DoubleEndedIterator for SplitEscaped<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.inner;
+        match (0..slice.len()).rev().find(|&i| self.is_boundary(i)) {
+            Some(i) => {
+                self.inner = &slice[..i];
+                Some(&slice[i + 1..])
+            }
+            None => {
+                self.finished = true;
+                Some(slice)
+            }
+        }
+    }
+}
+
+impl<'a, P: FnMut(u8) -> bool> SplitEscaped<'a, P> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        if self.finished {
+            &[]
+        } else {
+            self.inner
+        }
+    }
+
+    /// Returns an iterator of the same fields together with the delimiter which ends each, or
+    /// `None` for the last field, so that the delimiters can be kept when the fields are joined
+    /// back.
+    ///
+    /// ```
+    /// use escaped_delimiter::split_escaped;
+    ///
+    /// let it = split_escaped(b"a b\\\tc\td", |c| c == b' ' || c == b'\t', b'\\');
+    /// let v: Vec<_> = it.with_terminators().collect();
+    /// assert_eq!(v, &[(&b"a"[..], Some(b' ')), (&b"b\\\tc"[..], Some(b'\t')), (&b"d"[..], None)]);
+    /// ```
+    #[inline]
+    pub fn with_terminators(self) -> WithTerminators<'a, P> {
+        WithTerminators {
+            back: None,
+            inner: self,
+        }
+    }
+
+    #[inline]
+    fn is_boundary(&mut self, i: usize) -> bool {
+        let c = self.inner[i];
+        if (self.pred)(self.escape) {
+            return (self.pred)(c);
+        }
+        (self.pred)(c) && is_unescaped(self.inner, i, self.escape)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WithTerminators<'a, P> {
+    /// The delimiter before the last field yielded from the back.
+    back: Option<u8>,
+    inner: SplitEscaped<'a, P>,
+}
+
+impl<'a, P: FnMut(u8) -> bool> Iterator for WithTerminators<'a, P> {
+    type Item = (&'a [u8], Option<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = self.inner.inner;
+        let field = self.inner.next()?;
+        let terminator = if self.inner.finished {
+            self.back
+        } else {
+            Some(slice[field.len()])
+        };
+        Some((field, terminator))
+    }
+}
+
+impl<'a, P: FnMut(u8) -> bool> DoubleEndedIterator for WithTerminators<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = self.inner.inner;
+        let field = self.inner.next_back()?;
+        let terminator = self.back;
+        if !self.inner.finished {
+            self.back = Some(slice[self.inner.inner.len()]);
+        }
+        Some((field, terminator))
+    }
+}
+
+impl<'a, P: FnMut(u8) -> bool> WithTerminators<'a, P> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitWhitespaceEscaped<'a> {
+    inner: SplitEscaped<'a, fn(u8) -> bool>,
+}
+
+impl<'a> Iterator for SplitWhitespaceEscaped<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().find(|field| !field.is_empty())
+    }
+}
+
+impl<'a> DoubleEndedIterator for SplitWhitespaceEscaped<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().rev().find(|field| !field.is_empty())
+    }
+}
+
+impl<'a> SplitWhitespaceEscaped<'a> {
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Iter;
+
+    #[test]
+    fn test_split_escaped() {
+        let is_delim = |c| c == b'X' || c == b'Z';
+        let s = b"aXbYXcZZdYYZeY";
+        let words: Vec<_> = split_escaped(s, is_delim, b'Y').collect();
+        assert_eq!(
+            words,
+            &[&b"a"[..], &b"bYXc"[..], &b""[..], &b"dYY"[..], &b"eY"[..]]
+        );
+        let mut rwords: Vec<_> = split_escaped(s, is_delim, b'Y').rev().collect();
+        rwords.reverse();
+        assert_eq!(words, rwords);
+
+        // The same as `keep_empty` with a single delimiter.
+        let s = b"XaYXbXXcYYXX";
+        let words: Vec<_> = split_escaped(s, |c| c == b'X', b'Y').collect();
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').keep_empty().collect();
+        assert_eq!(words, expected);
+
+        // A delimiter which is also the escape character always splits.
+        let words: Vec<_> = split_escaped(b"aXbXc", |c| c == b'X', b'X').collect();
+        assert_eq!(words, &[&b"a"[..], b"b", b"c"]);
+        for s in crate::test::strings(b"aXY", 6) {
+            let words = split_escaped(&s, |c| c == b'X', b'X');
+            let expected = Iter::from_slice(&s, b'X', b'X').keep_empty();
+            assert!(words.clone().eq(expected), "{:?}", s);
+            assert!(words.rev().eq(expected.rev()), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_with_terminators() {
+        let is_delim = |c| c == b' ' || c == b'\t';
+        let s = b"a\tbY c\t\tdYY eY\t";
+        let expected = [
+            (&b"a"[..], Some(b'\t')),
+            (b"bY c", Some(b'\t')),
+            (b"", Some(b'\t')),
+            (b"dYY", Some(b' ')),
+            (b"eY\t", None),
+        ];
+        let it = split_escaped(s, is_delim, b'Y').with_terminators();
+        assert_eq!(it.clone().collect::<Vec<_>>(), expected);
+        assert!(it.clone().rev().eq(expected.iter().copied().rev()));
+
+        // Mixing both ends, every field still gets the delimiter right after it.
+        for front in 0..=expected.len() {
+            let mut it = it.clone();
+            let mut v: Vec<_> = it.by_ref().take(front).collect();
+            let mut back: Vec<_> = it.rev().collect();
+            back.reverse();
+            v.extend(back);
+            assert_eq!(v, expected, "{}", front);
+        }
+
+        // Joining the fields with their terminators gives the slice back.
+        let mut joined = Vec::new();
+        for (field, terminator) in it {
+            joined.extend_from_slice(field);
+            joined.extend(terminator);
+        }
+        assert_eq!(joined, s);
+    }
+
+    #[test]
+    fn test_split_at_nth_delimiter() {
+        let s = b"XaXXbYXcYYXX";
+        type Case<'a> = (usize, Option<(&'a [u8], &'a [u8])>);
+        let cases: &[Case] = &[
+            (0, Some((b"", b"aXXbYXcYYXX"))),
+            (1, Some((b"Xa", b"XbYXcYYXX"))),
+            (2, Some((b"XaX", b"bYXcYYXX"))),
+            (3, Some((b"XaXXbYXcYY", b"X"))),
+            (4, Some((b"XaXXbYXcYYX", b""))),
+            (5, None),
+        ];
+        for &(n, expected) in cases {
+            assert_eq!(split_at_nth_delimiter(s, b'X', b'Y', n), expected, "{}", n);
+        }
+        // Counting fields would skip the empty ones instead.
+        let (_, tail) = Iter::from_slice(s, b'X', b'Y').split_at_field(2);
+        assert_eq!(tail.as_slice(), b"X");
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let fields: Vec<_> = Iter::from_slice(&s, b'X', b'Y').keep_empty().collect();
+            for n in 0..fields.len() {
+                let expected = (n + 1 < fields.len()).then(|| {
+                    let before = fields[..=n].iter().map(|f| f.len() + 1).sum::<usize>() - 1;
+                    (&s[..before], &s[before + 1..])
+                });
+                assert_eq!(
+                    split_at_nth_delimiter(&s, b'X', b'Y', n),
+                    expected,
+                    "{:?} {}",
+                    s,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields_until_bytes() {
+        let mut rest = &b"aXbYXcXXdddddXeXfX"[..];
+        let mut chunks = Vec::new();
+        loop {
+            let (chunk, tail) = fields_until_bytes(rest, b'X', b'Y', 4);
+            if chunk.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+            rest = tail;
+        }
+        assert_eq!(
+            chunks,
+            &[&[&b"a"[..]][..], &[b"bYXc"], &[b"ddddd"], &[b"e", b"f"]]
+        );
+        assert_eq!(rest, b"");
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let fields: Vec<_> = Iter::from_slice(&s, b'X', b'Y').collect();
+            for max in 0..=s.len() {
+                let (chunk, rest) = fields_until_bytes(&s, b'X', b'Y', max);
+                let n = chunk.len();
+                assert!(n > 0 || fields.is_empty(), "{:?} {}", s, max);
+                assert_eq!(chunk, fields[..n], "{:?} {}", s, max);
+                let total: usize = chunk.iter().map(|f| f.len()).sum();
+                assert!(total <= max || n == 1, "{:?} {}", s, max);
+                if let Some(next) = fields.get(n) {
+                    assert!(total + next.len() > max, "{:?} {}", s, max);
+                }
+                assert!(Iter::from_slice(rest, b'X', b'Y').eq(fields[n..].iter().copied()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_limited() {
+        let s = b"XaXbYXcXXdYYXeX";
+        type Case<'a> = (usize, &'a [&'a [u8]], Option<&'a [u8]>);
+        let cases: &[Case] = &[
+            (0, &[], Some(s)),
+            (1, &[b"a"], Some(b"bYXcXXdYYXeX")),
+            (2, &[b"a", b"bYXc"], Some(b"XdYYXeX")),
+            (3, &[b"a", b"bYXc", b"dYY"], Some(b"eX")),
+            (4, &[b"a", b"bYXc", b"dYY", b"e"], None),
+            (5, &[b"a", b"bYXc", b"dYY", b"e"], None),
+        ];
+        for &(limit, head, rest) in cases {
+            assert_eq!(
+                split_limited(s, b'X', b'Y', limit),
+                (head.to_vec(), rest),
+                "{}",
+                limit
+            );
+        }
+
+        for s in crate::test::strings(b"aXY", 6) {
+            let fields: Vec<_> = Iter::from_slice(&s, b'X', b'Y').collect();
+            for limit in 0..=fields.len() + 1 {
+                let (head, rest) = split_limited(&s, b'X', b'Y', limit);
+                let rest: Vec<_> = rest
+                    .into_iter()
+                    .flat_map(|r| Iter::from_slice(r, b'X', b'Y'))
+                    .collect();
+                assert_eq!([head, rest].concat(), fields, "{:?} {}", s, limit);
+            }
+        }
+        assert_eq!(split_limited(b"", b'X', b'Y', 0), (vec![], None));
+    }
+
+    #[test]
+    fn test_split_at_offset() {
+        let s = b"aXbYYYXcYYXdXX";
+        let cases: &[(usize, &[u8], &[u8])] = &[
+            (0, b"a", b"bYYYXcYYXdXX"),
+            (1, b"a", b"bYYYXcYYXdXX"),
+            (2, b"aXbYYYXcYY", b"dXX"),
+            (6, b"aXbYYYXcYY", b"dXX"),
+            (11, b"aXbYYYXcYYXd", b"X"),
+            (13, b"aXbYYYXcYYXdX", b""),
+            (14, s, b""),
+            (20, s, b""),
+        ];
+        for &(offset, head, tail) in cases {
+            assert_eq!(
+                split_at_offset(s, b'X', b'Y', offset),
+                (head, tail),
+                "{}",
+                offset
+            );
+        }
+
+        // The split point is never escaped, wherever the offset falls in an escape run.
+        let s = b"aYYYYYXbYYYYXc";
+        for offset in 0..s.len() {
+            let (head, _) = split_at_offset(s, b'X', b'Y', offset);
+            let expected: &[u8] = if offset <= 12 { b"aYYYYYXbYYYY" } else { s };
+            assert_eq!(head, expected, "{}", offset);
+        }
+    }
+
+    #[test]
+    fn test_rsplit_once_decoded() {
+        let (head, tail) = rsplit_once_decoded(b"aXbYXcXdYXeYY", b'X', b'Y').unwrap();
+        assert_eq!((&*head, &*tail), (&b"aXbXc"[..], &b"dXeY"[..]));
+        assert!(matches!(head, Cow::Owned(_)));
+
+        let (head, tail) = rsplit_once_decoded(b"aXbYYX", b'X', b'Y').unwrap();
+        assert_eq!((&*head, &*tail), (&b"aXbY"[..], &b""[..]));
+        assert!(matches!(tail, Cow::Borrowed(_)));
+
+        let (head, tail) = rsplit_once_decoded(b"Xa", b'X', b'Y').unwrap();
+        assert_eq!((&*head, &*tail), (&b""[..], &b"a"[..]));
+
+        assert_eq!(rsplit_once_decoded(b"aYXbYXc", b'X', b'Y'), None);
+        assert_eq!(rsplit_once_decoded(b"", b'X', b'Y'), None);
+    }
+
+    #[test]
+    fn test_cmd_tokenize() {
+        fn decoded(s: &[u8]) -> Vec<Vec<u8>> {
+            cmd_tokenize(s)
+                .map(|t| unescape(t, b'^').into_owned())
+                .collect()
+        }
+
+        assert_eq!(
+            decoded(b"echo hello world"),
+            &[&b"echo"[..], b"hello", b"world"]
+        );
+        assert_eq!(decoded(b"echo a^&b"), &[&b"echo"[..], b"a&b"]);
+        assert_eq!(decoded(b"echo ^^"), &[&b"echo"[..], b"^"]);
+        assert_eq!(decoded(b"echo ^^^|x"), &[&b"echo"[..], b"^|x"]);
+        assert_eq!(decoded(b"echo ^a^b"), &[&b"echo"[..], b"ab"]);
+        assert_eq!(
+            decoded(b"a&&b||c|d&e"),
+            &[&b"a"[..], b"b", b"c", b"d", b"e"]
+        );
+        assert_eq!(decoded(b"echo^ hi"), &[&b"echo hi"[..]]);
+
+        // A trailing `^` is dropped only if it escapes nothing.
+        assert_eq!(decoded(b"echo hello^"), &[&b"echo"[..], b"hello"]);
+        assert_eq!(decoded(b"echo hello^^"), &[&b"echo"[..], b"hello^"]);
+        assert_eq!(decoded(b"echo ^"), &[&b"echo"[..]]);
+        assert_eq!(decoded(b"^"), Vec::<Vec<u8>>::new());
+        assert_eq!(decoded(b" & "), Vec::<Vec<u8>>::new());
+
+        let mut rtokens: Vec<_> = cmd_tokenize(b"a^ b & c d^").rev().collect();
+        rtokens.reverse();
+        assert_eq!(rtokens, cmd_tokenize(b"a^ b & c d^").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_split_whitespace_escaped() {
+        let s = b"  a\\ b\t\\\tc\r\n\x0b\x0cd\\\\ e\\\n";
+        let expected = [&b"a\\ b"[..], b"\\\tc", b"d\\\\", b"e\\\n"];
+        let words: Vec<_> = split_whitespace_escaped(s, b'\\').collect();
+        assert_eq!(words, expected);
+        let words: Vec<_> = split_whitespace_escaped(s, b'\\').rev().collect();
+        assert!(words.into_iter().eq(expected.into_iter().rev()));
+
+        let mut words = split_whitespace_escaped(b"a b c", b'\\');
+        assert_eq!(words.next_back(), Some(&b"c"[..]));
+        assert_eq!(words.next(), Some(&b"a"[..]));
+        assert_eq!(words.as_slice(), b"b");
+
+        // Without escape characters, it is the same as `str::split_whitespace`.
+        for s in ["", " ", "a", " a  b\tc\n", "\x0ca\r\nb "] {
+            let expected: Vec<_> = s.split_whitespace().map(str::as_bytes).collect();
+            let words: Vec<_> = split_whitespace_escaped(s.as_bytes(), b'\\').collect();
+            assert_eq!(words, expected);
+        }
+    }
+
+    #[test]
+    fn test_split_escaped_slice_split() {
+        for s in [&b""[..], b"X", b"XX", b"aXXb", b"XaX", b"abc", b"aXbXc"] {
+            let expected: Vec<_> = s.split(|&c| c == b'X').collect();
+            let words: Vec<_> = split_escaped(s, |c| c == b'X', b'Y').collect();
+            assert_eq!(words, expected);
+
+            let expected: Vec<_> = s.split(|&c| c == b'X').rev().collect();
+            let words: Vec<_> = split_escaped(s, |c| c == b'X', b'Y').rev().collect();
+            assert_eq!(words, expected);
+        }
+    }
+}
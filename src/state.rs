@@ -0,0 +1,186 @@
+use crate::{EscapeRun, Iter};
+
+/// A snapshot of the position of an [`Iter`], which can be stored apart from the slice.
+///
+/// The specials of [`Iter::with_specials`] are borrowed, so they are not part of the state:
+/// [`Iter::from_state`] escapes every byte, and [`Iter::from_state_with_specials`] restores them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IterState {
+    /// The offset of the rest of the slice from the start of the original slice.
+    pub start: usize,
+    /// The offset of the end of the rest of the slice from the start of the original slice.
+    pub end: usize,
+    /// The delimiter.
+    pub delim: u8,
+    /// The escape character.
+    pub escape: u8,
+    /// How a run of escape characters before a delimiter is read.
+    pub run: EscapeRun,
+    /// The offset of the start of the original slice from where reported offsets are counted,
+    /// if set by [`Iter::from_slice_with_base`].
+    pub base: Option<usize>,
+}
+
+impl<'a> Iter<'a> {
+    /// Returns the position of the iterator relative to `original`, the slice it was created
+    /// from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rest of the slice does not lie within `original`.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, Iter};
+    ///
+    /// let s = b"a b c d";
+    /// let mut it = iter(s, b' ', b'\\');
+    /// it.next();
+    /// it.next_back();
+    ///
+    /// let state = it.state(s);
+    /// assert_eq!((state.start, state.end), (1, 6));
+    ///
+    /// let it2 = Iter::from_state(s, state);
+    /// assert!(it.eq(it2));
+    /// ```
+    pub fn state(&self, original: &[u8]) -> IterState {
+        let base = original.as_ptr() as usize;
+        let start = (self.inner.as_ptr() as usize)
+            .checked_sub(base)
+            .expect("the iterator is not over the original slice");
+        let end = start + self.inner.len();
+        assert!(
+            end <= original.len(),
+            "the iterator is not over the original slice"
+        );
+
+        IterState {
+            start,
+            end,
+            delim: self.delim,
+            escape: self.escape,
+            run: self.run,
+            base: self.origin.map(|_| self.base_offset().wrapping_sub(start)),
+        }
+    }
+
+    /// Restores an iterator from the state returned by [`Iter::state`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offsets are out of bounds of `original`.
+    #[inline]
+    pub fn from_state(original: &'a [u8], state: IterState) -> Self {
+        Self {
+            origin: state
+                .base
+                .map(|base| (original.as_ptr() as usize).wrapping_sub(base)),
+            ..Self::from_slice(&original[state.start..state.end], state.delim, state.escape)
+                .escape_run(state.run)
+        }
+    }
+
+    /// Same as [`Iter::from_state`], for an iterator created by [`Iter::with_specials`].
+    ///
+    /// ```
+    /// use escaped_delimiter::Iter;
+    ///
+    /// let s = b"\\# \\b c";
+    /// let mut it = Iter::with_specials(s, b' ', b'\\', b"#");
+    /// it.next();
+    ///
+    /// let restored = Iter::from_state_with_specials(s, it.state(s), b"#");
+    /// assert_eq!(restored, it);
+    /// assert_eq!(restored.unescaped().next().as_deref(), Some(&b"\\b"[..]));
+    /// ```
+    #[inline]
+    pub fn from_state_with_specials(
+        original: &'a [u8],
+        state: IterState,
+        specials: &'a [u8],
+    ) -> Self {
+        Self {
+            specials: Some(specials),
+            ..Self::from_state(original, state)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_state() {
+        let s = b"XXabXYXcdeXYfYXXYYYXgYYX";
+        let mut words = Iter::from_slice(s, b'X', b'Y');
+        let state = words.state(s);
+        assert_eq!(state.start, 0);
+        assert_eq!(state.end, s.len());
+
+        assert_eq!(words.next(), Some(&b"ab"[..]));
+        assert_eq!(words.next_back(), Some(&b"YYYXgYY"[..]));
+        let state = words.state(s);
+        let mut restored = Iter::from_state(s, state);
+        assert_eq!(restored, words);
+        assert_eq!(restored.next(), words.next());
+        assert_eq!(restored.next(), words.next());
+        assert_eq!(restored.next(), None);
+        assert_eq!(words.next(), None);
+
+        let state = words.state(s);
+        assert_eq!(Iter::from_state(s, state).next(), None);
+    }
+
+    #[test]
+    fn test_state_escape_run() {
+        let s = b"a\\\\X bXc";
+        let mut words = Iter::from_slice(s, b'X', b'\\').escape_run(EscapeRun::Sticky);
+        assert_eq!(words.next_back(), Some(&b"c"[..]));
+
+        let restored = Iter::from_state(s, words.state(s));
+        assert_eq!(restored, words);
+        assert!(restored.eq(words));
+        assert_eq!(restored.collect::<Vec<_>>(), &[&b"a\\\\X b"[..]]);
+    }
+
+    #[test]
+    fn test_state_base_and_specials() {
+        let s = b"XaXbYcXdX";
+        let mut words = Iter::from_slice_with_base(&s[1..], 10, b'X', b'Y');
+        words.next();
+        let state = words.state(&s[1..]);
+        assert_eq!(state.base, Some(10));
+
+        let restored = Iter::from_state(&s[1..], state);
+        assert_eq!(restored, words);
+        assert!(restored.field_ranges().eq(words.field_ranges()));
+        assert!(restored.located().eq(words.located()));
+
+        // Restored from a larger slice, the offsets are still counted from the same base.
+        let state = words.state(s);
+        assert_eq!(state.base, Some(9));
+        assert!(Iter::from_state(s, state)
+            .field_ranges()
+            .eq([12..15, 16..17]));
+
+        // The specials are not kept, unless given again.
+        let mut words = Iter::with_specials(s, b'X', b'Y', b"X");
+        words.next();
+        let state = words.state(s);
+        let restored: Vec<_> = Iter::from_state(s, state).unescaped().collect();
+        assert_eq!(restored, &[&b"bc"[..], b"d"]);
+        let restored = Iter::from_state_with_specials(s, state, b"X");
+        assert_eq!(restored, words);
+        assert!(restored.unescaped().eq(words.unescaped()));
+        assert_eq!(words.unescaped().next().as_deref(), Some(&b"bYc"[..]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_state_foreign_slice() {
+        let s = b"abc";
+        let t = b"abc".to_vec();
+        Iter::from_slice(s, b'X', b'Y').state(&t);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::escape::count_escapes;
+use crate::Iter;
+
+/// Statistics returned by [`stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SplitStats {
+    /// The number of fields.
+    pub field_count: usize,
+    /// The number of fields which contain at least one escape character removed by
+    /// [`unescape`](crate::unescape).
+    pub escaped_field_count: usize,
+    /// The length of the longest (raw) field.
+    pub max_field_len: usize,
+    /// The total number of escape characters removed by [`unescape`](crate::unescape).
+    pub total_escape_bytes: usize,
+}
+
+/// Computes [`SplitStats`] in a single pass over the fields.
+///
+/// ```
+/// use escaped_delimiter::{stats, SplitStats};
+///
+/// let s = stats(b"a\\ b c\\\\ d", b' ', b'\\');
+/// assert_eq!(s, SplitStats {
+///     field_count: 3,
+///     escaped_field_count: 2,
+///     max_field_len: 4,
+///     total_escape_bytes: 2,
+/// });
+/// ```
+pub fn stats(slice: &[u8], delim: u8, escape: u8) -> SplitStats {
+    let mut stats = SplitStats::default();
+
+    for field in Iter::from_slice(slice, delim, escape) {
+        let escapes = count_escapes(field, escape);
+
+        stats.field_count += 1;
+        if escapes > 0 {
+            stats.escaped_field_count += 1;
+        }
+        stats.max_field_len = stats.max_field_len.max(field.len());
+        stats.total_escape_bytes += escapes;
+    }
+
+    stats
+}
+
+/// Returns the total length of the unescaped fields, without allocating.
+///
+/// This is the length of the buffer which [`compact_in_place`](crate::compact_in_place) leaves.
+///
+/// ```
+/// use escaped_delimiter::decoded_len;
+///
+/// assert_eq!(decoded_len(b"a\\ b  c\\\\", b' ', b'\\'), 5);
+/// ```
+pub fn decoded_len(slice: &[u8], delim: u8, escape: u8) -> usize {
+    Iter::from_slice(slice, delim, escape)
+        .map(|field| field.len() - count_escapes(field, escape))
+        .sum()
+}
+
+/// Returns the length of the longest raw field, or 0 if there are no fields.
+///
+/// ```
+/// use escaped_delimiter::max_field_len;
+///
+/// assert_eq!(max_field_len(b"a\\ b c\\\\", b' ', b'\\'), 4);
+/// ```
+pub fn max_field_len(slice: &[u8], delim: u8, escape: u8) -> usize {
+    Iter::from_slice(slice, delim, escape)
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Same as [`max_field_len`], but of the unescaped fields, without allocating.
+///
+/// ```
+/// use escaped_delimiter::max_decoded_field_len;
+///
+/// assert_eq!(max_decoded_field_len(b"a\\ b c\\\\", b' ', b'\\'), 3);
+/// ```
+pub fn max_decoded_field_len(slice: &[u8], delim: u8, escape: u8) -> usize {
+    Iter::from_slice(slice, delim, escape)
+        .map(|field| field.len() - count_escapes(field, escape))
+        .max()
+        .unwrap_or(0)
+}
+
+type Predicate<'p> = &'p dyn Fn(&[u8]) -> bool;
+
+/// Returns, for each predicate, the number of unescaped fields which it returns `true` for, in a
+/// single pass over the fields.
+///
+/// Every predicate sees every field, so a field may count towards several of them. Empty fields
+/// are skipped as in [`iter`](crate::iter), so none of them are counted.
+///
+/// ```
+/// use escaped_delimiter::tally;
+///
+/// let is_numeric = |f: &[u8]| f.iter().all(u8::is_ascii_digit);
+/// let is_short = |f: &[u8]| f.len() < 3;
+/// let counts = tally(b"12 a\\ b 345 c", b' ', b'\\', [&is_numeric, &is_short]);
+/// assert_eq!(counts, [2, 2]);
+/// ```
+pub fn tally<const N: usize>(
+    slice: &[u8],
+    delim: u8,
+    escape: u8,
+    preds: [Predicate<'_>; N],
+) -> [usize; N] {
+    let mut counts = [0; N];
+    for field in Iter::from_slice(slice, delim, escape).unescaped() {
+        for (count, pred) in counts.iter_mut().zip(preds) {
+            if pred(&field) {
+                *count += 1;
+            }
+        }
+    }
+
+    counts
+}
+
+impl Iter<'_> {
+    /// Returns the number of unescaped delimiters in the rest of the slice, including those
+    /// around the empty fields which are collapsed.
+    ///
+    /// Since empty fields are skipped, `count_fields()` is at most `count_delimiters() + 1`, and
+    /// the difference is the number of empty fields. See [`KeepEmpty::count_delimiters`] for the
+    /// mode keeping them.
+    ///
+    /// [`KeepEmpty::count_delimiters`]: crate::KeepEmpty::count_delimiters
+    ///
+    /// ```
+    /// use escaped_delimiter::iter;
+    ///
+    /// let it = iter(b"a,,b\\,c", b',', b'\\');
+    /// assert_eq!(it.count_delimiters(), 2);
+    /// assert_eq!(it.count_fields(), 2);
+    /// ```
+    pub fn count_delimiters(&self) -> usize {
+        let mut it = *self;
+        let mut count = 0;
+        while let Some(i) = it.find_delim() {
+            count += 1;
+            it.inner = &it.inner[i + 1..];
+        }
+
+        count
+    }
+
+    /// Returns the number of fields left, without consuming the iterator.
+    #[inline]
+    pub fn count_fields(&self) -> usize {
+        (*self).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decoded_len() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"Y", b"abc", b"XaYXXbYYXcXYX", b"YYYXXYaYbY"];
+        for s in inputs {
+            let fields = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+            assert_eq!(decoded_len(s, b'X', b'Y'), fields.concat().len(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_max_field_len() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"Y", b"abc", b"XaYXXbYYXcXYX", b"aYYYYXbcdXY"];
+        for s in inputs {
+            let fields: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            let max = fields.iter().map(|f| f.len()).max().unwrap_or(0);
+            assert_eq!(max_field_len(s, b'X', b'Y'), max, "{:?}", s);
+
+            let fields = Iter::from_slice(s, b'X', b'Y').to_owned_fields();
+            let max = fields.iter().map(Vec::len).max().unwrap_or(0);
+            assert_eq!(max_decoded_field_len(s, b'X', b'Y'), max, "{:?}", s);
+        }
+
+        // The longest raw field need not be the longest unescaped one.
+        assert_eq!(max_field_len(b"aYYYYXbcd", b'X', b'Y'), 5);
+        assert_eq!(max_decoded_field_len(b"aYYYYXbcd", b'X', b'Y'), 3);
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(stats(b"", b'X', b'Y'), SplitStats::default());
+
+        let s = b"aXXbYXcXYYYYXdddddXeY";
+        let st = stats(s, b'X', b'Y');
+        assert_eq!(st.field_count, 5);
+        assert_eq!(st.escaped_field_count, 2);
+        assert_eq!(st.max_field_len, 5);
+        assert_eq!(st.total_escape_bytes, 3);
+    }
+
+    #[test]
+    fn test_tally() {
+        let is_empty = |f: &[u8]| f.is_empty();
+        let is_numeric = |f: &[u8]| !f.is_empty() && f.iter().all(u8::is_ascii_digit);
+        let has_delim = |f: &[u8]| f.contains(&b'X');
+        let s = b"12XX3Y4XaYX1XY5XXYYX";
+        assert_eq!(
+            tally(s, b'X', b'Y', [&is_empty, &is_numeric, &has_delim]),
+            [0, 3, 1]
+        );
+        assert_eq!(tally(b"", b'X', b'Y', [&is_empty]), [0]);
+        assert_eq!(tally(s, b'X', b'Y', []), []);
+
+        let reference = |pred: &dyn Fn(&[u8]) -> bool| {
+            let it = Iter::from_slice(s, b'X', b'Y').unescaped();
+            it.filter(|f| pred(f)).count()
+        };
+        let counts = tally(s, b'X', b'Y', [&is_numeric, &has_delim, &is_numeric]);
+        assert_eq!(
+            counts,
+            [
+                reference(&is_numeric),
+                reference(&has_delim),
+                reference(&is_numeric)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_delimiters() {
+        let words = Iter::from_slice(b"", b'X', b'Y');
+        assert_eq!((words.count_delimiters(), words.count_fields()), (0, 0));
+
+        let words = Iter::from_slice(b"XX", b'X', b'Y');
+        assert_eq!((words.count_delimiters(), words.count_fields()), (2, 0));
+
+        let mut words = Iter::from_slice(b"aXXbYXcYYXdX", b'X', b'Y');
+        assert_eq!((words.count_delimiters(), words.count_fields()), (4, 3));
+        words.next();
+        assert_eq!((words.count_delimiters(), words.count_fields()), (4, 2));
+        words.next_back();
+        assert_eq!((words.count_delimiters(), words.count_fields()), (3, 1));
+    }
+}
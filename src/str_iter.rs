@@ -0,0 +1,320 @@
+use crate::Iter;
+
+/// Same as [`iter`](crate::iter), but splits a `str` and yields `&str`.
+///
+/// The delimiter and the escape character are each an ASCII `u8` or any `char`. A `char` is
+/// only ever matched whole, so no field is cut inside a multi-byte character, and an escape
+/// character right before a multi-byte character escapes all of it.
+///
+/// # Panics
+///
+/// Panics if `delim` or `escape` is a `u8` which is not ASCII.
+///
+/// ```
+/// use escaped_delimiter::iter_str;
+///
+/// let s_vec: Vec<_> = iter_str("caf\u{e9}\\ au lait th\u{e9}", b' ', b'\\').collect();
+/// assert_eq!(s_vec, &["caf\u{e9}\\ au", "lait", "th\u{e9}"]);
+///
+/// let s = "a\u{b7}b\u{a5}\u{b7}c\u{b7}\u{b7}d\u{a5}\u{e9}";
+/// let s_vec: Vec<_> = iter_str(s, '\u{b7}', '\u{a5}').collect();
+/// assert_eq!(s_vec, &["a", "b\u{a5}\u{b7}c", "d\u{a5}\u{e9}"]);
+/// ```
+#[inline]
+pub fn iter_str<D: StrDelimiter, E: StrDelimiter>(s: &str, delim: D, escape: E) -> StrIter<'_> {
+    StrIter::new(s, delim, escape)
+}
+
+/// The `char`-based counterpart of [`iter_str`], which takes a `char` for both the delimiter and
+/// the escape character.
+///
+/// ```
+/// use escaped_delimiter::str_iter;
+///
+/// let s_vec: Vec<_> = str_iter("a\\\u{b7}b\u{b7}c", '\u{b7}', '\\').collect();
+/// assert_eq!(s_vec, &["a\\\u{b7}b", "c"]);
+/// ```
+#[inline]
+pub fn str_iter(s: &str, delim: char, escape: char) -> StrIter<'_> {
+    iter_str(s, delim, escape)
+}
+
+/// A delimiter or an escape character of [`iter_str`]: an ASCII `u8` or any `char`.
+pub trait StrDelimiter: Copy {
+    /// Returns the character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is a `u8` which is not ASCII.
+    fn into_char(self) -> char;
+}
+
+impl StrDelimiter for u8 {
+    #[inline]
+    fn into_char(self) -> char {
+        assert!(self.is_ascii(), "the byte is not ASCII");
+        self as char
+    }
+}
+
+impl StrDelimiter for char {
+    #[inline]
+    fn into_char(self) -> char {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrIter<'a> {
+    inner: Repr<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repr<'a> {
+    /// Both the delimiter and the escape character are ASCII, so the bytes can be split as is.
+    Bytes(Iter<'a>),
+    Chars(CharSplit<'a>),
+}
+
+impl<'a> Iterator for StrIter<'a> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Repr::Bytes(inner) => inner.next().map(to_str),
+            Repr::Chars(inner) => inner.next(),
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for StrIter<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Repr::Bytes(inner) => inner.next_back().map(to_str),
+            Repr::Chars(inner) => inner.next_back(),
+        }
+    }
+}
+
+impl<'a> StrIter<'a> {
+    /// Returns the rest of the inner `str`.
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        match self.inner {
+            Repr::Bytes(inner) => to_str(inner.as_slice()),
+            Repr::Chars(inner) => inner.inner,
+        }
+    }
+
+    /// Returns the rest of the inner `str`, same as [`as_str`](Self::as_str).
+    #[inline]
+    pub fn as_slice(&self) -> &'a str {
+        self.as_str()
+    }
+
+    /// See [`iter_str`].
+    #[inline]
+    pub fn new<D: StrDelimiter, E: StrDelimiter>(s: &'a str, delim: D, escape: E) -> Self {
+        let (delim, escape) = (delim.into_char(), escape.into_char());
+        if delim.is_ascii() && escape.is_ascii() {
+            return Self {
+                inner: Repr::Bytes(Iter::from_slice(s.as_bytes(), delim as u8, escape as u8)),
+            };
+        }
+
+        Self {
+            inner: Repr::Chars(CharSplit {
+                delim,
+                escape,
+                inner: s,
+            }),
+        }
+    }
+}
+
+/// Splits a `str` on whole `char`s, for a delimiter or an escape character which is not ASCII.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CharSplit<'a> {
+    delim: char,
+    escape: char,
+    inner: &'a str,
+}
+
+impl<'a> Iterator for CharSplit<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.inner.starts_with(self.delim) {
+            self.inner = &self.inner[self.delim.len_utf8()..];
+        }
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        let pos = self
+            .inner
+            .match_indices(self.delim)
+            .map(|(i, _)| i)
+            .find(|&i| self.is_boundary(i))
+            .unwrap_or(self.inner.len());
+        let field = &self.inner[..pos];
+        self.inner = &self.inner[pos..];
+        Some(field)
+    }
+}
+
+impl<'a> DoubleEndedIterator for CharSplit<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // Like `Iter`, the rest is kept as is if there is no field left.
+        let mut inner = self.inner;
+        while let Some(rest) = inner.strip_suffix(self.delim) {
+            if !self.is_boundary(rest.len()) {
+                break;
+            }
+            inner = rest;
+        }
+        if inner.is_empty() {
+            return None;
+        }
+
+        let pos = inner
+            .rmatch_indices(self.delim)
+            .map(|(i, _)| i)
+            .find(|&i| self.is_boundary(i))
+            .map_or(0, |i| i + self.delim.len_utf8());
+        self.inner = &inner[..pos];
+        Some(&inner[pos..])
+    }
+}
+
+impl CharSplit<'_> {
+    /// Returns whether the delimiter at `i` is preceded by an even number of consecutive escape
+    /// characters, as [`Scanner`](crate::scanner::Scanner) does for bytes.
+    fn is_boundary(&self, i: usize) -> bool {
+        if self.delim == self.escape {
+            return true;
+        }
+
+        let mut rest = &self.inner[..i];
+        let mut escapes = 0;
+        while let Some(r) = rest.strip_suffix(self.escape) {
+            rest = r;
+            escapes += 1;
+        }
+        escapes & 1 == 0
+    }
+}
+
+/// Converts a slice cut from a `str` at ASCII bytes only.
+#[inline]
+fn to_str(slice: &[u8]) -> &str {
+    debug_assert!(std::str::from_utf8(slice).is_ok());
+    // SAFETY: the iterator only cuts its `str` right before or after a delimiter, which is
+    // ASCII. Every byte of a multi-byte UTF-8 sequence is non-ASCII, so no cut falls inside a
+    // character.
+    unsafe { std::str::from_utf8_unchecked(slice) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_iter_str() {
+        let s = "\u{3042}X\u{3044}YX\u{1f600}XX\u{3046}YYX";
+        let words: Vec<_> = iter_str(s, b'X', b'Y').collect();
+        assert_eq!(words, &["\u{3042}", "\u{3044}YX\u{1f600}", "\u{3046}YY"]);
+
+        let mut words = iter_str(s, b'X', b'Y');
+        assert_eq!(words.next_back(), Some("\u{3046}YY"));
+        assert_eq!(words.next(), Some("\u{3042}"));
+        assert_eq!(words.as_str(), "X\u{3044}YX\u{1f600}XX");
+        assert_eq!(words.next_back(), Some("\u{3044}YX\u{1f600}"));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_iter_str_chars() {
+        // Non-ASCII characters in place of `X` and `Y` split the same way.
+        let to_str = |s: &[u8]| -> String {
+            s.iter()
+                .map(|&c| match c {
+                    b'X' => '\u{e9}',
+                    b'Y' => '\u{1f600}',
+                    c => c as char,
+                })
+                .collect()
+        };
+        for s in crate::test::strings(b"aXY", 7) {
+            let expected: Vec<_> = Iter::from_slice(&s, b'X', b'Y').map(to_str).collect();
+            let t = to_str(&s);
+            for (delim, escape) in [('\u{e9}', '\u{1f600}'), ('\u{e9}', 'Y'), ('X', '\u{1f600}')] {
+                let t = t.replace('\u{e9}', delim.encode_utf8(&mut [0; 4]));
+                let t = t.replace('\u{1f600}', escape.encode_utf8(&mut [0; 4]));
+                let expected: Vec<_> = expected
+                    .iter()
+                    .map(|f| {
+                        f.replace('\u{e9}', delim.encode_utf8(&mut [0; 4]))
+                            .replace('\u{1f600}', escape.encode_utf8(&mut [0; 4]))
+                    })
+                    .collect();
+
+                let words: Vec<_> = iter_str(&t, delim, escape).collect();
+                assert_eq!(words, expected, "{:?}", t);
+                let mut rwords: Vec<_> = iter_str(&t, delim, escape).rev().collect();
+                rwords.reverse();
+                assert_eq!(rwords, expected, "{:?}", t);
+
+                // From both ends at once, leaving the same rest as the bytes.
+                let mut it = iter_str(&t, delim, escape);
+                let mut bytes = Iter::from_slice(&s, b'X', b'Y');
+                let (mut front, mut back) = (Vec::new(), Vec::new());
+                while let Some(field) = it.next() {
+                    front.push(field);
+                    back.extend(it.next_back());
+                    bytes.next();
+                    bytes.next_back();
+                    let rest = to_str(bytes.as_slice())
+                        .replace('\u{e9}', delim.encode_utf8(&mut [0; 4]))
+                        .replace('\u{1f600}', escape.encode_utf8(&mut [0; 4]));
+                    assert_eq!(it.as_str(), rest, "{:?}", t);
+                }
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, expected, "{:?}", t);
+            }
+        }
+
+        // A delimiter only matches a whole character, never a byte of another one: U+00E9 and
+        // U+00A9 share their last byte.
+        let words: Vec<_> = iter_str("a\u{a9}b\u{e9}c", '\u{e9}', '\\').collect();
+        assert_eq!(words, &["a\u{a9}b", "c"]);
+
+        // An escape character before a multi-byte character escapes all of it.
+        let words: Vec<_> = iter_str("a\\\u{e9}b\u{e9}\\\u{1f600}\u{e9}", '\u{e9}', '\\').collect();
+        assert_eq!(words, &["a\\\u{e9}b", "\\\u{1f600}"]);
+        let words: Vec<_> = iter_str("\u{a5}\u{e9} \u{a5}\u{a5} c", ' ', '\u{a5}').collect();
+        assert_eq!(words, &["\u{a5}\u{e9}", "\u{a5}\u{a5}", "c"]);
+
+        let mut it = iter_str("\u{b7}a\u{b7}b\u{b7}c\u{b7}", '\u{b7}', '\\');
+        assert_eq!(it.next_back(), Some("c"));
+        assert_eq!(it.next(), Some("a"));
+        assert_eq!(it.as_str(), "\u{b7}b\u{b7}");
+        assert_eq!(it.next(), Some("b"));
+        assert_eq!(it.as_str(), "\u{b7}");
+        assert_eq!(it.next_back(), None);
+
+        assert_eq!(str_iter("a\u{e9}b", '\u{e9}', '\\').as_slice(), "a\u{e9}b");
+
+        // ASCII characters split the bytes as is, mixed with bytes or not.
+        assert_eq!(iter_str("a b", ' ', '\\'), iter_str("a b", b' ', b'\\'));
+        assert_eq!(iter_str("a b", b' ', '\\'), iter_str("a b", b' ', b'\\'));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_iter_str_non_ascii() {
+        iter_str("a", 0xe3, b'Y');
+    }
+}
@@ -0,0 +1,259 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use crate::scanner::EscapeTracker;
+use crate::unescape;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Returns a stream of the unescaped fields read from `reader`.
+///
+/// Fields are yielded as soon as their delimiter arrives, so a field may straddle any number of
+/// reads. Empty fields are skipped as in [`iter`](crate::iter).
+///
+/// ```
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+/// use futures_core::Stream;
+/// use escaped_delimiter::stream;
+///
+/// let mut s = pin!(stream(&b"a\\ b c"[..], b' ', b'\\'));
+/// let mut cx = Context::from_waker(Waker::noop());
+/// let field = s.as_mut().poll_next(&mut cx);
+/// assert!(matches!(field, Poll::Ready(Some(Ok(f))) if f == b"a b"));
+/// ```
+#[inline]
+pub fn stream<R: AsyncRead>(reader: R, delim: u8, escape: u8) -> EscapedStream<R> {
+    EscapedStream::from_reader(reader, delim, escape)
+}
+
+#[derive(Debug)]
+pub struct EscapedStream<R> {
+    reader: R,
+    delim: u8,
+    escape: u8,
+    buf: Vec<u8>,
+    /// The start of the current field in `buf`.
+    start: usize,
+    /// The end of the scanned part of `buf`.
+    scanned: usize,
+    /// The escape parity at the end of the scanned part.
+    tracker: EscapeTracker,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> Stream for EscapedStream<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(i) = this.find_eow() {
+                let field = &this.buf[this.start..i];
+                this.start = i + 1;
+                if !field.is_empty() {
+                    return Poll::Ready(Some(Ok(unescape(field, this.escape).into_owned())));
+                }
+                continue;
+            }
+
+            if this.eof {
+                let field = &this.buf[this.start..];
+                if field.is_empty() {
+                    return Poll::Ready(None);
+                }
+                let field = unescape(field, this.escape).into_owned();
+                this.start = this.buf.len();
+                return Poll::Ready(Some(Ok(field)));
+            }
+
+            if let Err(e) = std::task::ready!(this.poll_fill(cx)) {
+                return Poll::Ready(Some(Err(e)));
+            }
+        }
+    }
+}
+
+impl<R> EscapedStream<R> {
+    /// See [`stream`].
+    #[inline]
+    pub fn from_reader(reader: R, delim: u8, escape: u8) -> Self {
+        Self {
+            reader,
+            delim,
+            escape,
+            buf: Vec::new(),
+            start: 0,
+            scanned: 0,
+            tracker: EscapeTracker::new(escape),
+            eof: false,
+        }
+    }
+
+    /// Returns the underlying reader, dropping any buffered bytes.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Scans the newly read bytes for an unescaped delimiter, carrying the parity of the escape
+    /// run over from the previous read.
+    fn find_eow(&mut self) -> Option<usize> {
+        while self.scanned < self.buf.len() {
+            let i = self.scanned;
+            let c = self.buf[i];
+            self.scanned += 1;
+
+            if self.tracker.is_unescaped_target(c, c == self.delim) {
+                return Some(i);
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: AsyncRead + Unpin> EscapedStream<R> {
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_SIZE, 0);
+        loop {
+            match Pin::new(&mut self.reader).poll_read(cx, &mut self.buf[len..]) {
+                Poll::Ready(Ok(n)) => {
+                    self.buf.truncate(len + n);
+                    self.eof = n == 0;
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(e)) if e.kind() == io::ErrorKind::Interrupted => {}
+                Poll::Ready(Err(e)) => {
+                    self.buf.truncate(len);
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Pending => {
+                    self.buf.truncate(len);
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::task::Waker;
+
+    use crate::Iter;
+
+    /// Returns at most `chunk` bytes per read, and `Pending` before every other read.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        chunk: usize,
+        pending: bool,
+    }
+
+    impl AsyncRead for Trickle<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let n = self.chunk.min(self.data.len()).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    fn collect<R: AsyncRead + Unpin>(mut s: EscapedStream<R>) -> Vec<Vec<u8>> {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut fields = Vec::new();
+        loop {
+            match Pin::new(&mut s).poll_next(&mut cx) {
+                Poll::Ready(Some(field)) => fields.push(field.unwrap()),
+                Poll::Ready(None) => return fields,
+                Poll::Pending => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let s = b"XXabXYXcdeXYYXfYYYXgYYYYXX";
+        let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .unescaped()
+            .map(|f| f.into_owned())
+            .collect();
+        assert_eq!(expected.len(), 4);
+
+        for chunk in 1..=s.len() {
+            let reader = Trickle {
+                data: s,
+                chunk,
+                pending: false,
+            };
+            assert_eq!(
+                collect(stream(reader, b'X', b'Y')),
+                expected,
+                "chunk {chunk}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_delim_is_escape() {
+        // Every delimiter is a boundary, as in `Iter`.
+        let s = b"a,,b,c,";
+        let expected: Vec<_> = Iter::from_slice(s, b',', b',')
+            .unescaped()
+            .map(|f| f.into_owned())
+            .collect();
+        assert_eq!(expected, &[&b"a"[..], b"b", b"c"]);
+        for chunk in 1..=s.len() {
+            let reader = Trickle {
+                data: s,
+                chunk,
+                pending: false,
+            };
+            assert_eq!(collect(stream(reader, b',', b',')), expected);
+        }
+    }
+
+    #[test]
+    fn test_stream_trailing_escape() {
+        let reader = Trickle {
+            data: b"aXbY",
+            chunk: 1,
+            pending: false,
+        };
+        assert_eq!(
+            collect(stream(reader, b'X', b'Y')),
+            &[&b"a"[..], &b"bY"[..]]
+        );
+
+        let reader = Trickle {
+            data: b"",
+            chunk: 1,
+            pending: false,
+        };
+        assert!(collect(stream(reader, b'X', b'Y')).is_empty());
+    }
+}
@@ -0,0 +1,587 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::scanner::{Direction, Scanner};
+use crate::Iter;
+
+impl<'a> Iter<'a> {
+    /// Returns an iterator of the same fields, which reports malformed fields as errors instead
+    /// of yielding them.
+    ///
+    /// By default, a trailing escape character which escapes nothing is an error and the
+    /// iteration stops at the first error. See [`TryIter::max_len`], [`TryIter::quote`] and
+    /// [`TryIter::on_error`].
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ParseError, ParseErrorKind};
+    ///
+    /// let mut it = iter(b"a b\\", b' ', b'\\').strict();
+    /// assert_eq!(it.next(), Some(Ok(&b"a"[..])));
+    /// assert_eq!(it.next(), Some(Err(ParseError { offset: 3, kind: ParseErrorKind::TrailingEscape })));
+    /// assert_eq!(it.next(), None);
+    /// ```
+    #[inline]
+    pub fn strict(self) -> TryIter<'a> {
+        TryIter {
+            base: (self.inner.as_ptr() as usize).wrapping_sub(self.base_offset()),
+            inner: self,
+            max_len: None,
+            quote: None,
+            reject_bare_escapes: false,
+            policy: ErrorPolicy::Stop,
+            failed: false,
+        }
+    }
+}
+
+/// Returns the index of the first malformed field, or `None` if every field is well-formed.
+///
+/// A field is malformed if it ends with an escape character which escapes nothing, or if an
+/// escape character in it escapes a byte other than the delimiter or the escape character, as
+/// with [`TryIter::reject_bare_escapes`]. Nothing is unescaped.
+///
+/// ```
+/// use escaped_delimiter::first_invalid_field;
+///
+/// assert_eq!(first_invalid_field(b"a\\  b\\\\ \\c", b' ', b'\\'), Some(2));
+/// assert_eq!(first_invalid_field(b"a\\  b\\\\ c", b' ', b'\\'), None);
+/// ```
+pub fn first_invalid_field(slice: &[u8], delim: u8, escape: u8) -> Option<usize> {
+    Iter::from_slice(slice, delim, escape)
+        .strict()
+        .reject_bare_escapes(true)
+        .on_error(ErrorPolicy::Continue)
+        .position(|field| field.is_err())
+}
+
+/// What [`TryIter`] does after an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ErrorPolicy {
+    /// Yields `None` after the first error.
+    #[default]
+    Stop,
+    /// Skips the malformed field and goes on to the next one.
+    Continue,
+}
+
+/// An error yielded by [`TryIter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The offset from the start of the slice, relative to the rest of the slice at the time of
+    /// [`Iter::strict`], or to the base of [`Iter::from_slice_with_base`].
+    pub offset: usize,
+    /// What is wrong with the field.
+    pub kind: ParseErrorKind,
+}
+
+/// What is wrong with the field of a [`ParseError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// The field, which starts at the offset, is longer than [`TryIter::max_len`].
+    TooLong,
+    /// The field starts with the quote of [`TryIter::quote`], at the offset, which is never
+    /// closed.
+    UnterminatedQuote,
+    /// The slice ends with an escape character, at the offset, which escapes nothing.
+    TrailingEscape,
+    /// The escape character at the offset escapes a byte other than the delimiter or the
+    /// escape character. See [`TryIter::reject_bare_escapes`].
+    BareEscape,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::TooLong => write!(f, "field at offset {} is too long", self.offset),
+            ParseErrorKind::UnterminatedQuote => {
+                write!(f, "unterminated quote at offset {}", self.offset)
+            }
+            ParseErrorKind::TrailingEscape => {
+                write!(f, "trailing escape character at offset {}", self.offset)
+            }
+            ParseErrorKind::BareEscape => {
+                write!(f, "bare escape character at offset {}", self.offset)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryIter<'a> {
+    base: usize,
+    inner: Iter<'a>,
+    max_len: Option<usize>,
+    quote: Option<u8>,
+    reject_bare_escapes: bool,
+    policy: ErrorPolicy,
+    failed: bool,
+}
+
+impl<'a> Iterator for TryIter<'a> {
+    type Item = Result<&'a [u8], ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let field = self.inner.next()?;
+        match self.check(field) {
+            Ok(()) => Some(Ok(field)),
+            Err(e) => {
+                self.failed = self.policy == ErrorPolicy::Stop;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> TryIter<'a> {
+    /// Makes a field longer than `max_len` bytes (before unescaping) an error.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ErrorPolicy, ParseError, ParseErrorKind};
+    ///
+    /// let v: Vec<_> = iter(b"ab abc a", b' ', b'\\')
+    ///     .strict()
+    ///     .max_len(2)
+    ///     .on_error(ErrorPolicy::Continue)
+    ///     .collect();
+    /// assert_eq!(v, &[
+    ///     Ok(&b"ab"[..]),
+    ///     Err(ParseError { offset: 3, kind: ParseErrorKind::TooLong }),
+    ///     Ok(&b"a"[..]),
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn max_len(self, max_len: usize) -> Self {
+        Self {
+            max_len: Some(max_len),
+            ..self
+        }
+    }
+
+    /// Makes a field which starts with `quote` an error, unless an unescaped `quote` later in the
+    /// field closes it.
+    ///
+    /// The fields are still split at every unescaped delimiter, quoted or not; see
+    /// [`iter_quoted`](crate::iter_quoted) for the delimiters between the quotes to be literal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quote` is the delimiter or the escape character.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ParseError, ParseErrorKind};
+    ///
+    /// let mut it = iter(b"\"a\" \"b\\\" c", b' ', b'\\').strict().quote(b'"');
+    /// assert_eq!(it.next(), Some(Ok(&b"\"a\""[..])));
+    /// assert_eq!(it.next(), Some(Err(ParseError { offset: 4, kind: ParseErrorKind::UnterminatedQuote })));
+    /// ```
+    #[inline]
+    pub fn quote(self, quote: u8) -> Self {
+        assert!(
+            quote != self.inner.delim && quote != self.inner.escape,
+            "the quote is the delimiter or the escape character"
+        );
+        Self {
+            quote: Some(quote),
+            ..self
+        }
+    }
+
+    /// Sets whether an escape character followed by a byte other than the delimiter or the
+    /// escape character is an error, rather than content.
+    ///
+    /// Bytes given to [`Iter::with_specials`] may also be escaped. A trailing escape character is
+    /// always an error.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ParseError, ParseErrorKind};
+    ///
+    /// let mut it = iter(b"a\\\\\\ b c\\d", b' ', b'\\').strict().reject_bare_escapes(true);
+    /// assert_eq!(it.next(), Some(Ok(&b"a\\\\\\ b"[..])));
+    /// assert_eq!(it.next(), Some(Err(ParseError { offset: 8, kind: ParseErrorKind::BareEscape })));
+    /// ```
+    #[inline]
+    pub fn reject_bare_escapes(self, reject_bare_escapes: bool) -> Self {
+        Self {
+            reject_bare_escapes,
+            ..self
+        }
+    }
+
+    /// Sets what happens after an error.
+    #[inline]
+    pub fn on_error(self, policy: ErrorPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// Returns an iterator of all the fields, malformed or not, which records every error in
+    /// them instead, for [`Lenient::problems`].
+    ///
+    /// Unlike the errors yielded by this iterator, there may be several in a field.
+    ///
+    /// ```
+    /// use escaped_delimiter::{iter, ParseErrorKind, Problem};
+    ///
+    /// let mut it = iter(b"a\\b\\c d e\\", b' ', b'\\')
+    ///     .strict()
+    ///     .reject_bare_escapes(true)
+    ///     .lenient();
+    /// assert_eq!(it.by_ref().count(), 3);
+    /// assert_eq!(it.problems(), &[
+    ///     Problem { index: 0, offset: 1, kind: ParseErrorKind::BareEscape },
+    ///     Problem { index: 0, offset: 3, kind: ParseErrorKind::BareEscape },
+    ///     Problem { index: 2, offset: 9, kind: ParseErrorKind::TrailingEscape },
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn lenient(self) -> Lenient<'a> {
+        Lenient {
+            index: 0,
+            problems: Vec::new(),
+            inner: self,
+        }
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+
+    #[inline]
+    fn check(&self, field: &'a [u8]) -> Result<(), ParseError> {
+        self.errors(field).next().map_or(Ok(()), Err)
+    }
+
+    /// Returns an iterator of the errors in the field, in the order of their offsets.
+    fn errors(&self, field: &'a [u8]) -> impl Iterator<Item = ParseError> + 'a {
+        let start = (field.as_ptr() as usize).wrapping_sub(self.base);
+        let error = move |i, kind| ParseError {
+            offset: start + i,
+            kind,
+        };
+
+        let too_long = self.max_len.is_some_and(|max_len| field.len() > max_len);
+        let unterminated = self.quote.is_some_and(|quote| {
+            // The opening quote is not an escape character, so the rest can be searched on its
+            // own.
+            field[0] == quote
+                && Scanner::new(quote, self.inner.escape)
+                    .with_run(self.inner.run)
+                    .find_boundary(&field[1..], Direction::Forward)
+                    .is_none()
+        });
+        let bare_escapes = self
+            .reject_bare_escapes
+            .then(|| self.bare_escapes(field))
+            .into_iter()
+            .flatten();
+
+        // A field ending with an odd run of escape characters would have escaped the following
+        // delimiter, so such a field is always at the end of the slice.
+        let escapes = field
+            .iter()
+            .rev()
+            .take_while(|&&c| c == self.inner.escape)
+            .count();
+        let trailing = escapes & 1 == 1;
+
+        too_long
+            .then(|| error(0, ParseErrorKind::TooLong))
+            .into_iter()
+            .chain(unterminated.then(|| error(0, ParseErrorKind::UnterminatedQuote)))
+            .chain(bare_escapes.map(move |i| error(i, ParseErrorKind::BareEscape)))
+            .chain(trailing.then(|| error(field.len() - 1, ParseErrorKind::TrailingEscape)))
+    }
+
+    /// Returns an iterator of the positions of the escape characters which escape a byte they
+    /// may not.
+    fn bare_escapes(&self, field: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let inner = self.inner;
+        let mut escaped = false;
+        field.iter().enumerate().filter_map(move |(i, &c)| {
+            if escaped {
+                escaped = false;
+                (!inner.needs_escape(c)).then(|| i - 1)
+            } else {
+                escaped = c == inner.escape;
+                None
+            }
+        })
+    }
+}
+
+/// A malformed field recorded by [`Lenient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Problem {
+    /// The index of the field, counted from the first field yielded by [`Lenient`].
+    pub index: usize,
+    /// The offset, as in [`ParseError::offset`].
+    pub offset: usize,
+    /// What is wrong with the field.
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lenient<'a> {
+    index: usize,
+    problems: Vec<Problem>,
+    inner: TryIter<'a>,
+}
+
+impl<'a> Iterator for Lenient<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.inner.inner.next()?;
+        let index = self.index;
+        self.problems
+            .extend(self.inner.errors(field).map(|e| Problem {
+                index,
+                offset: e.offset,
+                kind: e.kind,
+            }));
+        self.index += 1;
+        Some(field)
+    }
+}
+
+impl<'a> Lenient<'a> {
+    /// Returns the problems in the fields yielded so far.
+    #[inline]
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Returns the problems in the fields yielded so far, dropping the iterator.
+    #[inline]
+    pub fn into_problems(self) -> Vec<Problem> {
+        self.problems
+    }
+
+    /// Returns the rest of the inner slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.inner.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strict() {
+        let s = b"abXYXcdeXXfYYXgY";
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y').strict().collect();
+        assert_eq!(
+            v,
+            &[
+                Ok(&b"ab"[..]),
+                Ok(&b"YXcde"[..]),
+                Ok(&b"fYY"[..]),
+                Err(ParseError {
+                    offset: 15,
+                    kind: ParseErrorKind::TrailingEscape
+                }),
+            ]
+        );
+
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .strict()
+            .max_len(3)
+            .collect();
+        assert_eq!(
+            v,
+            &[
+                Ok(&b"ab"[..]),
+                Err(ParseError {
+                    offset: 3,
+                    kind: ParseErrorKind::TooLong
+                }),
+            ]
+        );
+
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .strict()
+            .max_len(3)
+            .on_error(ErrorPolicy::Continue)
+            .collect();
+        assert_eq!(
+            v,
+            &[
+                Ok(&b"ab"[..]),
+                Err(ParseError {
+                    offset: 3,
+                    kind: ParseErrorKind::TooLong
+                }),
+                Ok(&b"fYY"[..]),
+                Err(ParseError {
+                    offset: 15,
+                    kind: ParseErrorKind::TrailingEscape
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strict_offsets() {
+        let mut words = Iter::from_slice(b"aXbXcY", b'X', b'Y');
+        words.next();
+
+        let mut words = words.strict();
+        assert_eq!(words.next(), Some(Ok(&b"b"[..])));
+        assert_eq!(
+            words.next(),
+            Some(Err(ParseError {
+                offset: 4,
+                kind: ParseErrorKind::TrailingEscape
+            }))
+        );
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn test_reject_bare_escapes() {
+        fn check(s: &[u8]) -> Vec<Result<&[u8], ParseError>> {
+            Iter::from_slice(s, b'X', b'Y')
+                .strict()
+                .reject_bare_escapes(true)
+                .collect()
+        }
+        let err = |offset, kind| Err(ParseError { offset, kind });
+
+        assert_eq!(check(b"aYb"), &[err(1, ParseErrorKind::BareEscape)]);
+        assert_eq!(check(b"aYYb"), &[Ok(&b"aYYb"[..])]);
+        assert_eq!(check(b"aY"), &[err(1, ParseErrorKind::TrailingEscape)]);
+        assert_eq!(
+            check(b"aYXbXYYYc"),
+            &[Ok(&b"aYXb"[..]), err(7, ParseErrorKind::BareEscape)]
+        );
+        assert_eq!(check(b"aYbYXc"), &[err(1, ParseErrorKind::BareEscape)]);
+
+        let v: Vec<_> = Iter::with_specials(b"aY#XbYc", b'X', b'Y', b"#")
+            .strict()
+            .reject_bare_escapes(true)
+            .collect();
+        assert_eq!(v, &[Ok(&b"aY#"[..]), err(5, ParseErrorKind::BareEscape)]);
+
+        let v: Vec<_> = Iter::from_slice(b"aYb", b'X', b'Y').strict().collect();
+        assert_eq!(v, &[Ok(&b"aYb"[..])]);
+    }
+
+    #[test]
+    fn test_quote() {
+        fn check(s: &[u8]) -> Vec<Result<&[u8], ParseError>> {
+            Iter::from_slice(s, b'X', b'Y')
+                .strict()
+                .quote(b'Q')
+                .on_error(ErrorPolicy::Continue)
+                .collect()
+        }
+        let err = |offset, kind| Err(ParseError { offset, kind });
+
+        assert_eq!(check(b"QaQXbQ"), &[Ok(&b"QaQ"[..]), Ok(b"bQ")]);
+        assert_eq!(
+            check(b"QaQbXQ"),
+            &[Ok(&b"QaQb"[..]), err(5, ParseErrorKind::UnterminatedQuote)]
+        );
+        assert_eq!(
+            check(b"QaYQXQQ"),
+            &[err(0, ParseErrorKind::UnterminatedQuote), Ok(b"QQ")]
+        );
+        assert_eq!(check(b"QaYYQ"), &[Ok(&b"QaYYQ"[..])]);
+
+        // The quote does not hide a trailing escape character.
+        assert_eq!(check(b"QaY"), &[err(0, ParseErrorKind::UnterminatedQuote)]);
+        let mut it = Iter::from_slice(b"QaY", b'X', b'Y')
+            .strict()
+            .quote(b'Q')
+            .lenient();
+        it.by_ref().count();
+        let kinds: Vec<_> = it.problems().iter().map(|p| p.kind).collect();
+        assert_eq!(
+            kinds,
+            &[
+                ParseErrorKind::UnterminatedQuote,
+                ParseErrorKind::TrailingEscape
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_invalid_field() {
+        let cases: &[(&[u8], Option<usize>)] = &[
+            (b"", None),
+            (b"aXYXXbYYXcY", Some(3)),
+            (b"aXXbYcXd", Some(1)),
+            (b"YaXb", Some(0)),
+            (b"aXbYYXYXcXXYY", None),
+            (b"aXbXcYYYX", None),
+        ];
+        for &(s, expected) in cases {
+            assert_eq!(first_invalid_field(s, b'X', b'Y'), expected, "{:?}", s);
+        }
+
+        for s in crate::test::strings(b"aXY", 7) {
+            let mut it = Iter::from_slice(&s, b'X', b'Y')
+                .strict()
+                .reject_bare_escapes(true)
+                .lenient();
+            it.by_ref().count();
+            let expected = it.problems().first().map(|p| p.index);
+            assert_eq!(first_invalid_field(&s, b'X', b'Y'), expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_lenient() {
+        let s = b"aYbYYcYdXeXXfYXgYhXYXiYYjYkXlY";
+        let mut it = Iter::from_slice(s, b'X', b'Y')
+            .strict()
+            .max_len(4)
+            .reject_bare_escapes(true)
+            .lenient();
+        let fields: Vec<_> = it.by_ref().collect();
+        assert_eq!(fields, Iter::from_slice(s, b'X', b'Y').collect::<Vec<_>>());
+
+        let problem = |index, offset, kind| Problem {
+            index,
+            offset,
+            kind,
+        };
+        assert_eq!(
+            it.problems(),
+            &[
+                problem(0, 0, ParseErrorKind::TooLong),
+                problem(0, 1, ParseErrorKind::BareEscape),
+                problem(0, 6, ParseErrorKind::BareEscape),
+                problem(2, 12, ParseErrorKind::TooLong),
+                problem(2, 16, ParseErrorKind::BareEscape),
+                problem(3, 19, ParseErrorKind::TooLong),
+                problem(3, 25, ParseErrorKind::BareEscape),
+                problem(4, 29, ParseErrorKind::TrailingEscape),
+            ]
+        );
+        assert_eq!(it.next(), None);
+        assert_eq!(it.into_problems().len(), 8);
+
+        // The errors of a field, as the problems, start with the one in `TryIter`.
+        let v: Vec<_> = Iter::from_slice(s, b'X', b'Y')
+            .strict()
+            .max_len(4)
+            .reject_bare_escapes(true)
+            .on_error(ErrorPolicy::Continue)
+            .collect();
+        let offsets: Vec<_> = v.iter().filter_map(|r| r.err()).map(|e| e.offset).collect();
+        assert_eq!(offsets, &[0, 12, 19, 29]);
+
+        let it = Iter::from_slice(b"aXbYcXdY", b'X', b'Y').strict().lenient();
+        assert_eq!(it.count(), 3);
+    }
+}
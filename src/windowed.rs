@@ -0,0 +1,128 @@
+use std::num::NonZeroUsize;
+
+use crate::scanner::EscapeTracker;
+
+/// Calls `f` with each field of the first `window` bytes of `slice`, and returns where to
+/// resume, or `None` if the whole slice is done.
+///
+/// Each call scans at most `window` bytes, however long a field is, so scanning a gigantic field
+/// such as a run of escape characters in a memory-mapped file is spread over many calls, without
+/// copying. A field is passed to `f` once its end is scanned; the fields are the same as those of
+/// [`iter`](crate::iter).
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use escaped_delimiter::for_each_field_windowed;
+///
+/// let window = NonZeroUsize::new(5).unwrap();
+/// let mut v = Vec::new();
+/// let mut next = for_each_field_windowed(b"a\\ b  cd", b' ', b'\\', window, |f| v.push(f));
+/// assert_eq!(v, &[&b"a\\ b"[..]]);
+/// while let Some(cont) = next {
+///     next = cont.resume(window, |f| v.push(f));
+/// }
+/// assert_eq!(v, &[&b"a\\ b"[..], &b"cd"[..]]);
+/// ```
+pub fn for_each_field_windowed<'a, F: FnMut(&'a [u8])>(
+    slice: &'a [u8],
+    delim: u8,
+    escape: u8,
+    window: NonZeroUsize,
+    f: F,
+) -> Option<Continuation<'a>> {
+    let cont = Continuation {
+        delim,
+        slice,
+        start: 0,
+        pos: 0,
+        tracker: EscapeTracker::new(escape),
+    };
+    cont.resume(window, f)
+}
+
+/// The state of [`for_each_field_windowed`] between two calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Continuation<'a> {
+    delim: u8,
+    slice: &'a [u8],
+    start: usize,
+    pos: usize,
+    /// The escape parity at `pos`.
+    tracker: EscapeTracker,
+}
+
+impl<'a> Continuation<'a> {
+    /// Scans the next `window` bytes, in the same way as [`for_each_field_windowed`].
+    pub fn resume<F: FnMut(&'a [u8])>(mut self, window: NonZeroUsize, mut f: F) -> Option<Self> {
+        let end = self.slice.len().min(self.pos.saturating_add(window.get()));
+        for i in self.pos..end {
+            let c = self.slice[i];
+            if self.tracker.is_unescaped_target(c, c == self.delim) {
+                if self.start < i {
+                    f(&self.slice[self.start..i]);
+                }
+                self.start = i + 1;
+            }
+        }
+        self.pos = end;
+
+        if end < self.slice.len() {
+            return Some(self);
+        }
+        if self.start < end {
+            f(&self.slice[self.start..]);
+        }
+        None
+    }
+
+    /// Returns the number of bytes scanned so far.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Iter;
+
+    fn collect(s: &[u8], window: usize) -> (Vec<&[u8]>, usize) {
+        let window = NonZeroUsize::new(window).unwrap();
+        let mut fields = Vec::new();
+        let mut calls = 1;
+        let mut next = for_each_field_windowed(s, b'X', b'Y', window, |f| fields.push(f));
+        while let Some(cont) = next {
+            assert_eq!(cont.offset(), calls * window.get());
+            calls += 1;
+            next = cont.resume(window, |f| fields.push(f));
+        }
+        (fields, calls)
+    }
+
+    #[test]
+    fn test_for_each_field_windowed() {
+        let inputs: &[&[u8]] = &[b"", b"XX", b"Y", b"abc", b"XaYXXbYYXcXYX", b"YYYXXYaYbY"];
+        for s in inputs {
+            let expected: Vec<_> = Iter::from_slice(s, b'X', b'Y').collect();
+            for window in 1..=s.len() + 1 {
+                assert_eq!(collect(s, window).0, expected, "{:?} {}", s, window);
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_each_field_windowed_large() {
+        let mut s = vec![b'Y'; 1 << 20];
+        s.extend_from_slice(b"XaYXbX");
+        s.extend(std::iter::repeat_n(b'c', 1 << 20));
+
+        let (fields, calls) = collect(&s, 4096);
+        assert_eq!(calls, s.len().div_ceil(4096));
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].len(), 1 << 20);
+        assert_eq!(fields[1], b"aYXb");
+        assert_eq!(fields[2].len(), 1 << 20);
+    }
+}
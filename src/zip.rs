@@ -0,0 +1,57 @@
+use std::iter;
+
+use crate::Iter;
+
+/// Returns an iterator of the pairs of the fields of `a` and `b` at the same index, where the
+/// side with fewer fields gives `None` once it runs out.
+///
+/// ```
+/// use escaped_delimiter::zip_fields;
+///
+/// let v: Vec<_> = zip_fields(b"a b\\ c", b"d", b' ', b'\\').collect();
+/// assert_eq!(v, &[(Some(&b"a"[..]), Some(&b"d"[..])), (Some(&b"b\\ c"[..]), None)]);
+/// ```
+pub fn zip_fields<'a>(
+    a: &'a [u8],
+    b: &'a [u8],
+    delim: u8,
+    escape: u8,
+) -> impl Iterator<Item = (Option<&'a [u8]>, Option<&'a [u8]>)> {
+    let mut a = Iter::from_slice(a, delim, escape);
+    let mut b = Iter::from_slice(b, delim, escape);
+    iter::from_fn(move || match (a.next(), b.next()) {
+        (None, None) => None,
+        pair => Some(pair),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_zip_fields() {
+        let v: Vec<_> = zip_fields(b"aXbYXc", b"XdXXeYY", b'X', b'Y').collect();
+        assert_eq!(
+            v,
+            &[
+                (Some(&b"a"[..]), Some(&b"d"[..])),
+                (Some(b"bYXc"), Some(b"eYY")),
+            ]
+        );
+
+        let v: Vec<_> = zip_fields(b"a", b"bXcXd", b'X', b'Y').collect();
+        assert_eq!(
+            v,
+            &[
+                (Some(&b"a"[..]), Some(&b"b"[..])),
+                (None, Some(b"c")),
+                (None, Some(b"d")),
+            ]
+        );
+
+        let v: Vec<_> = zip_fields(b"aXb", b"XX", b'X', b'Y').collect();
+        assert_eq!(v, &[(Some(&b"a"[..]), None), (Some(b"b"), None)]);
+        assert_eq!(zip_fields(b"", b"X", b'X', b'Y').next(), None);
+    }
+}